@@ -0,0 +1,4 @@
+pub mod pearls;
+pub mod rendering;
+
+pub use boba_3d::glam;
@@ -0,0 +1,604 @@
+use std::cell::Cell;
+
+use boba_3d::{
+    glam::{Affine2, Vec2},
+    pearls::{ChangeStamps, HierarchyTransform, TransformLinks},
+};
+use boba_core::{Pearl, PearlError, PearlMutError};
+use log::error;
+
+/// The world space values of a 2D transform, cached until the transform or one of its parents changes
+#[derive(Clone, Copy)]
+struct WorldCache {
+    calculated: u64,
+    parent_matrix: Affine2,
+    parent_z_order: i32,
+    world_matrix: Affine2,
+    position: Vec2,
+    rotation: f32,
+    lossy_scale: Vec2,
+    z_order: i32,
+}
+
+impl WorldCache {
+    fn new(
+        parent_matrix: Affine2,
+        parent_z_order: i32,
+        local_matrix: Affine2,
+        local_z_order: i32,
+    ) -> Self {
+        let world_matrix = parent_matrix * local_matrix;
+        let (lossy_scale, rotation, position) = decompose(&world_matrix);
+        Self {
+            calculated: ChangeStamps::current(),
+            parent_matrix,
+            parent_z_order,
+            world_matrix,
+            position,
+            rotation,
+            lossy_scale,
+            z_order: parent_z_order.saturating_add(local_z_order),
+        }
+    }
+}
+
+/// Splits `matrix` into its scale, rotation angle and translation.
+///
+/// A mirrored matrix is given a negative `x` scale.
+fn decompose(matrix: &Affine2) -> (Vec2, f32, Vec2) {
+    let x_axis = matrix.matrix2.x_axis;
+    let y_axis = matrix.matrix2.y_axis;
+    let sign = matrix.matrix2.determinant().signum();
+    let scale = Vec2::new(x_axis.length() * sign, y_axis.length());
+    let rotation = (-y_axis.x).atan2(y_axis.y);
+    (scale, rotation, matrix.translation)
+}
+
+/// A 2D position, rotation angle, scale and z-order that can be arranged in a hierarchy.
+///
+/// Rotations are counter clockwise angles in radians. The z-order of a child is added to the z-order of its parent,
+/// and transforms with a higher world z-order are drawn on top.
+///
+/// Like the 3D transform, changing a transform only stamps it as changed,
+/// and world space values are recalculated the next time they are read.
+pub struct BobaTransform2D {
+    local_position: Vec2,
+    local_rotation: f32,
+    local_scale: Vec2,
+    local_z_order: i32,
+    local_matrix: Affine2,
+
+    world: Cell<WorldCache>,
+    stamps: ChangeStamps,
+
+    links: TransformLinks<BobaTransform2D>,
+}
+
+impl Default for BobaTransform2D {
+    fn default() -> Self {
+        Self::from_position(Vec2::ZERO)
+    }
+}
+
+impl BobaTransform2D {
+    pub fn from_position(position: Vec2) -> Self {
+        Self::new(position, 0., Vec2::ONE)
+    }
+
+    pub fn from_position_rotation(position: Vec2, rotation: f32) -> Self {
+        Self::new(position, rotation, Vec2::ONE)
+    }
+
+    pub fn from_position_scale(position: Vec2, scale: Vec2) -> Self {
+        Self::new(position, 0., scale)
+    }
+
+    pub fn new(position: Vec2, rotation: f32, scale: Vec2) -> Self {
+        let matrix = Affine2::from_scale_angle_translation(scale, rotation, position);
+
+        Self {
+            local_position: position,
+            local_rotation: rotation,
+            local_scale: scale,
+            local_z_order: 0,
+            local_matrix: matrix,
+
+            world: Cell::new(WorldCache::new(Affine2::IDENTITY, 0, matrix, 0)),
+            stamps: Default::default(),
+
+            links: Default::default(),
+        }
+    }
+
+    /// Sets the local z-order of the transform
+    pub fn with_z_order(mut self, z_order: i32) -> Self {
+        self.set_z_order(z_order);
+        self
+    }
+
+    pub fn world_position(&self) -> Vec2 {
+        self.world().position
+    }
+
+    pub fn world_rotation(&self) -> f32 {
+        self.world().rotation
+    }
+
+    pub fn lossy_scale(&self) -> Vec2 {
+        self.world().lossy_scale
+    }
+
+    /// The z-order of this transform added to the z-order of all of its ancestors
+    pub fn world_z_order(&self) -> i32 {
+        self.world().z_order
+    }
+
+    pub fn local_position(&self) -> Vec2 {
+        self.local_position
+    }
+
+    pub fn local_rotation(&self) -> f32 {
+        self.local_rotation
+    }
+
+    pub fn local_scale(&self) -> Vec2 {
+        self.local_scale
+    }
+
+    pub fn z_order(&self) -> i32 {
+        self.local_z_order
+    }
+
+    pub fn world_matrix(&self) -> Affine2 {
+        self.world().world_matrix
+    }
+
+    pub fn local_matrix(&self) -> Affine2 {
+        self.local_matrix
+    }
+
+    /// Gets the parent of this transform, if it has one that is still alive
+    pub fn parent(&self) -> Option<Pearl<BobaTransform2D>> {
+        self.links.parent()
+    }
+
+    /// Iterates over the children of this transform in sibling order.
+    ///
+    /// Children that were destroyed without using [`SetTransformParent`](boba_3d::pearls::SetTransformParent) are only removed when the hierarchy changes.
+    pub fn children(&self) -> impl Iterator<Item = &Pearl<BobaTransform2D>> {
+        self.links.children()
+    }
+
+    /// Gets the child at `index` in sibling order
+    pub fn child(&self, index: usize) -> Option<&Pearl<BobaTransform2D>> {
+        self.links.child(index)
+    }
+
+    pub fn child_count(&self) -> usize {
+        self.links.child_count()
+    }
+
+    /// The right direction of the transform in world space (its local `+X` axis)
+    pub fn right(&self) -> Vec2 {
+        Vec2::from_angle(self.world_rotation())
+    }
+
+    /// The up direction of the transform in world space (its local `+Y` axis)
+    pub fn up(&self) -> Vec2 {
+        self.right().perp()
+    }
+
+    /// The newest change stamp of this transform and all of its ancestors.
+    ///
+    /// The stamp grows whenever the world transform may have changed, including changes inherited from a parent.
+    pub fn world_change_stamp(&self) -> u64 {
+        self.stamps.newest()
+    }
+
+    /// Transforms `point` from the local space of this transform into world space
+    pub fn transform_point(&self, point: Vec2) -> Vec2 {
+        self.world_matrix().transform_point2(point)
+    }
+
+    /// Transforms `point` from world space into the local space of this transform
+    pub fn inverse_transform_point(&self, point: Vec2) -> Vec2 {
+        self.world_matrix().inverse().transform_point2(point)
+    }
+
+    /// Sets the local position of the transform.
+    ///
+    /// Marks the world position of this transform and all of its descendants as dirty.
+    pub fn set_local_position(&mut self, position: Vec2) {
+        self.local_position = position;
+        self.local_matrix.translation = position;
+        self.stamps.mark_changed();
+    }
+
+    /// Sets the local rotation angle of the transform.
+    ///
+    /// Marks the world rotation of this transform and all of its descendants as dirty.
+    pub fn set_local_rotation(&mut self, rotation: f32) {
+        self.local_rotation = rotation;
+        self.calculate_local_matrix();
+    }
+
+    /// Sets the local scale of the transform.
+    ///
+    /// Marks the lossy scale of this transform and all of its descendants as dirty.
+    pub fn set_local_scale(&mut self, scale: Vec2) {
+        self.local_scale = scale;
+        self.calculate_local_matrix();
+    }
+
+    /// Sets the local position, rotation and scale of the transform at once.
+    pub fn set_local(&mut self, position: Vec2, rotation: f32, scale: Vec2) {
+        self.local_position = position;
+        self.local_rotation = rotation;
+        self.local_scale = scale;
+        self.calculate_local_matrix();
+    }
+
+    /// Sets the local z-order of the transform.
+    ///
+    /// Marks the world z-order of this transform and all of its descendants as dirty.
+    pub fn set_z_order(&mut self, z_order: i32) {
+        self.local_z_order = z_order;
+        self.stamps.mark_changed();
+    }
+
+    /// Sets the world position of the transform.
+    ///
+    /// The local position is calculated relative to the parent.
+    pub fn set_world_position(&mut self, position: Vec2) {
+        let local = self
+            .world()
+            .parent_matrix
+            .inverse()
+            .transform_point2(position);
+        self.set_local_position(local);
+    }
+
+    /// Sets the world rotation angle of the transform.
+    ///
+    /// The local rotation is calculated relative to the parent.
+    pub fn set_world_rotation(&mut self, rotation: f32) {
+        let (_, parent_rotation, _) = decompose(&self.world().parent_matrix);
+        self.set_local_rotation(rotation - parent_rotation);
+    }
+
+    /// Sets the world scale of the transform.
+    ///
+    /// The local scale is calculated by dividing out the scale of the parent.
+    /// Like [`lossy_scale`](Self::lossy_scale), this is only exact when no parent is both rotated and non uniformly scaled.
+    pub fn set_world_scale(&mut self, scale: Vec2) {
+        let (parent_scale, _, _) = decompose(&self.world().parent_matrix);
+        self.set_local_scale(scale / parent_scale);
+    }
+
+    /// Sets the world z-order of the transform.
+    ///
+    /// The local z-order is calculated relative to the parent.
+    pub fn set_world_z_order(&mut self, z_order: i32) {
+        let parent_z_order = self.world().parent_z_order;
+        self.set_z_order(z_order.saturating_sub(parent_z_order));
+    }
+
+    /// Moves the transform by `offset` in world space.
+    pub fn translate(&mut self, offset: Vec2) {
+        self.set_world_position(self.world_position() + offset);
+    }
+
+    /// Rotates the transform counter clockwise by `angle` radians.
+    pub fn rotate(&mut self, angle: f32) {
+        self.set_world_rotation(self.world_rotation() + angle);
+    }
+
+    /// Rotates the transform counter clockwise by `angle` radians around the world space `point`.
+    ///
+    /// Both the position and the rotation of the transform are changed.
+    pub fn rotate_around(&mut self, point: Vec2, angle: f32) {
+        let position = point + Vec2::from_angle(angle).rotate(self.world_position() - point);
+        self.set_world_position(position);
+        self.rotate(angle);
+    }
+
+    /// Rotates the transform so that [`right`](Self::right) points at the world space `target`.
+    ///
+    /// Does nothing if `target` is at the world position of the transform.
+    pub fn look_at(&mut self, target: Vec2) {
+        let direction = target - self.world_position();
+        if direction != Vec2::ZERO {
+            self.set_world_rotation(direction.y.atan2(direction.x));
+        }
+    }
+
+    fn calculate_local_matrix(&mut self) {
+        self.local_matrix = Affine2::from_scale_angle_translation(
+            self.local_scale,
+            self.local_rotation,
+            self.local_position,
+        );
+        self.stamps.mark_changed();
+    }
+
+    fn set_local_matrix(&mut self, matrix: Affine2) {
+        (self.local_scale, self.local_rotation, self.local_position) = decompose(&matrix);
+        self.calculate_local_matrix();
+    }
+
+    /// Gets the cached world values, recalculating them if the transform is dirty
+    fn world(&self) -> WorldCache {
+        let world = self.world.get();
+        if !self.stamps.changed_since(world.calculated) {
+            return world;
+        }
+
+        // a parent that was dropped left its final matrix and z-order in the cache when it was dropped
+        let last_parent = (world.parent_matrix, world.parent_z_order);
+        let parent = self.links.parent_link().map(|parent| parent.upgrade());
+        let (parent_matrix, parent_z_order) = match parent {
+            None => (Affine2::IDENTITY, 0),
+            Some(None) => last_parent,
+            Some(Some(parent)) => match parent.borrow() {
+                Ok(parent) => {
+                    let parent = parent.world();
+                    (parent.world_matrix, parent.z_order)
+                }
+                Err(PearlError::Destroyed) => last_parent,
+                Err(e) => {
+                    error!("Could not sync with parent transform due to: {e}");
+                    let (matrix, z_order) = last_parent;
+                    return WorldCache::new(matrix, z_order, self.local_matrix, self.local_z_order);
+                }
+            },
+        };
+
+        let world = WorldCache::new(
+            parent_matrix,
+            parent_z_order,
+            self.local_matrix,
+            self.local_z_order,
+        );
+        self.world.set(world);
+        world
+    }
+}
+
+impl HierarchyTransform for BobaTransform2D {
+    type ParentWorld = (Affine2, i32);
+    const ROOT: (Affine2, i32) = (Affine2::IDENTITY, 0);
+
+    fn links(&self) -> &TransformLinks<Self> {
+        &self.links
+    }
+
+    fn links_mut(&mut self) -> &mut TransformLinks<Self> {
+        &mut self.links
+    }
+
+    fn stamps(&self) -> &ChangeStamps {
+        &self.stamps
+    }
+
+    fn stamps_mut(&mut self) -> &mut ChangeStamps {
+        &mut self.stamps
+    }
+
+    fn parent_world(&self) -> (Affine2, i32) {
+        let world = self.world();
+        (world.world_matrix, world.z_order)
+    }
+
+    fn prepare_relink(
+        &mut self,
+        (parent_matrix, parent_z_order): (Affine2, i32),
+        keep_world: bool,
+    ) {
+        if keep_world {
+            let (world_matrix, world_z_order) = self.parent_world();
+            self.local_z_order = world_z_order.saturating_sub(parent_z_order);
+            self.set_local_matrix(parent_matrix.inverse() * world_matrix);
+        }
+    }
+}
+
+impl Drop for BobaTransform2D {
+    fn drop(&mut self) {
+        if self.links.child_count() == 0 {
+            return;
+        }
+
+        // children only hold a weak link, so they have to be given the final matrix before it is gone
+        let world = self.world();
+        for child in self.links.children() {
+            match child.borrow_mut() {
+                Ok(child) => child.world.set(WorldCache::new(
+                    world.world_matrix,
+                    world.z_order,
+                    child.local_matrix,
+                    child.local_z_order,
+                )),
+                Err(PearlMutError::Destroyed) => (),
+                Err(e) => error!("Could not sync child transform due to: {e}"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    use boba_3d::pearls::{SetParentError, SetTransformParent};
+
+    use super::*;
+
+    const EPSILON: f32 = 0.0001;
+
+    fn assert_vec(a: Vec2, b: Vec2) {
+        assert!(a.abs_diff_eq(b, EPSILON), "{a} != {b}");
+    }
+
+    fn assert_angle(a: f32, b: f32) {
+        let difference = (a - b + PI).rem_euclid(2. * PI) - PI;
+        assert!(difference.abs() < EPSILON, "{a} != {b}");
+    }
+
+    /// Creates a parent at `(0, 10)` rotated a quarter turn and scaled by 2, with a child at `(1, 0)`
+    fn hierarchy() -> (Pearl<BobaTransform2D>, Pearl<BobaTransform2D>) {
+        let parent = Pearl::wrap(BobaTransform2D::default());
+        let mut child = Pearl::wrap(BobaTransform2D::from_position(Vec2::X));
+        child.set_parent(parent.clone()).unwrap();
+        parent
+            .borrow_mut()
+            .unwrap()
+            .set_local(Vec2::Y * 10., FRAC_PI_2, Vec2::splat(2.));
+
+        (parent, child)
+    }
+
+    #[test]
+    fn child_world() {
+        let (_, child) = hierarchy();
+        let child = child.borrow().unwrap();
+
+        assert_vec(child.world_position(), Vec2::new(0., 12.));
+        assert_angle(child.world_rotation(), FRAC_PI_2);
+        assert_vec(child.lossy_scale(), Vec2::splat(2.));
+        assert_vec(child.right(), Vec2::Y);
+        assert_vec(child.up(), Vec2::NEG_X);
+
+        let world = child.transform_point(Vec2::X);
+        assert_vec(world, Vec2::new(0., 14.));
+        assert_vec(child.inverse_transform_point(world), Vec2::X);
+    }
+
+    #[test]
+    fn world_setters() {
+        let (_, child) = hierarchy();
+        let mut child = child.borrow_mut().unwrap();
+
+        child.set_world_position(Vec2::new(2., 10.));
+        assert_vec(child.world_position(), Vec2::new(2., 10.));
+        assert_vec(child.local_position(), Vec2::new(0., -1.));
+
+        child.set_world_rotation(0.);
+        assert_angle(child.world_rotation(), 0.);
+        assert_angle(child.local_rotation(), -FRAC_PI_2);
+
+        child.set_world_scale(Vec2::ONE);
+        assert_vec(child.lossy_scale(), Vec2::ONE);
+        assert_vec(child.local_scale(), Vec2::splat(0.5));
+
+        child.rotate_around(Vec2::new(0., 10.), FRAC_PI_2);
+        assert_vec(child.world_position(), Vec2::new(0., 12.));
+        assert_angle(child.world_rotation(), FRAC_PI_2);
+
+        child.look_at(Vec2::new(-5., 12.));
+        assert_vec(child.right(), Vec2::NEG_X);
+    }
+
+    #[test]
+    fn mirrored_scale() {
+        let mut transform = BobaTransform2D::new(Vec2::ZERO, 1., Vec2::new(-2., 3.));
+        transform.set_local_position(Vec2::ONE);
+        assert_vec(transform.lossy_scale(), Vec2::new(-2., 3.));
+        assert_angle(transform.world_rotation(), 1.);
+    }
+
+    #[test]
+    fn z_order() {
+        let (parent, mut child) = hierarchy();
+        parent.borrow_mut().unwrap().set_z_order(5);
+        child.borrow_mut().unwrap().set_z_order(-2);
+        assert_eq!(child.borrow().unwrap().world_z_order(), 3);
+
+        child.borrow_mut().unwrap().set_world_z_order(10);
+        assert_eq!(child.borrow().unwrap().z_order(), 5);
+
+        // keeping the world transform keeps the world z-order too
+        child.remove_parent_keep_world().unwrap();
+        let data = child.borrow().unwrap();
+        assert_eq!(data.world_z_order(), 10);
+        assert_vec(data.world_position(), Vec2::new(0., 12.));
+        drop(data);
+
+        child.set_parent(parent.clone()).unwrap();
+        assert_eq!(child.borrow().unwrap().world_z_order(), 15);
+    }
+
+    #[test]
+    fn lazy_propagation() {
+        let root = Pearl::wrap(BobaTransform2D::default());
+        let mut chain = vec![root.clone()];
+        for _ in 0..10 {
+            let mut child = Pearl::wrap(BobaTransform2D::from_position(Vec2::X).with_z_order(1));
+            child.set_parent(chain.last().unwrap().clone()).unwrap();
+            chain.push(child);
+        }
+
+        let leaf = chain.last().unwrap().clone();
+        let stamp = leaf.borrow().unwrap().world_change_stamp();
+        assert_vec(leaf.borrow().unwrap().world_position(), Vec2::X * 10.);
+        assert_eq!(leaf.borrow().unwrap().world_z_order(), 10);
+
+        root.borrow_mut().unwrap().set_local_position(Vec2::Y * 5.);
+        assert_vec(
+            leaf.borrow().unwrap().world_position(),
+            Vec2::X * 10. + Vec2::Y * 5.,
+        );
+        assert!(leaf.borrow().unwrap().world_change_stamp() > stamp);
+    }
+
+    #[test]
+    fn parent_changed_then_dropped() {
+        let parent = Pearl::wrap(BobaTransform2D::default());
+        let mut child = Pearl::wrap(BobaTransform2D::from_position(Vec2::X));
+        child.set_parent(parent.clone()).unwrap();
+        assert_vec(child.borrow().unwrap().world_position(), Vec2::X);
+
+        let mut parent_data = parent.borrow_mut().unwrap();
+        parent_data.set_local_position(Vec2::Y * 10.);
+        parent_data.set_z_order(3);
+        drop(parent_data);
+        drop(parent);
+
+        let mut child = child.borrow_mut().unwrap();
+        assert_vec(child.world_position(), Vec2::new(1., 10.));
+        assert_eq!(child.world_z_order(), 3);
+
+        child.set_local_position(Vec2::NEG_X);
+        assert_vec(child.world_position(), Vec2::new(-1., 10.));
+    }
+
+    #[test]
+    fn hierarchy_changes() {
+        let (parent, mut child) = hierarchy();
+        let other = Pearl::wrap(BobaTransform2D::from_position(Vec2::Y));
+
+        child.set_parent(other.clone()).unwrap();
+        assert!(parent.borrow().unwrap().child_count() == 0);
+        assert!(child.sibling_index().unwrap() == Some(0));
+        assert_vec(child.borrow().unwrap().world_position(), Vec2::ONE);
+
+        let mut other = other;
+        assert!(matches!(
+            other.set_parent(child.clone()),
+            Err(SetParentError::RecursionError)
+        ));
+
+        // destroying the middle of a hierarchy keeps the world transform of its children
+        other.set_parent(parent.clone()).unwrap();
+        let world = child.borrow().unwrap().world_matrix();
+        other.destroy_keep_children().unwrap();
+        assert!(child.borrow().unwrap().parent() == Some(parent.clone()));
+        assert!(child
+            .borrow()
+            .unwrap()
+            .world_matrix()
+            .abs_diff_eq(world, EPSILON));
+
+        parent.destroy_recursive().unwrap();
+        assert!(child.is_destroyed().unwrap());
+    }
+}
@@ -0,0 +1,170 @@
+use boba_3d::glam::Vec2;
+use taro_core::{
+    data::{
+        texture::{Rgba8Srgb, Texture2DView},
+        Sampler,
+    },
+    wgpu, Bind, BindCompiler, BindGroup, BindGroupBuilder, Taro,
+};
+
+/// A rectangle of a texture in uv coordinates, from `min` at the top left to `max` at the bottom right
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasRegion {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Default for AtlasRegion {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+impl AtlasRegion {
+    /// The region that covers the whole texture
+    pub const FULL: Self = Self::new(Vec2::ZERO, Vec2::ONE);
+
+    pub const fn new(min: Vec2, max: Vec2) -> Self {
+        Self { min, max }
+    }
+
+    /// Creates a region from a rectangle of pixels in a texture that is `texture_size` pixels large
+    pub fn from_pixels(x: u32, y: u32, width: u32, height: u32, texture_size: (u32, u32)) -> Self {
+        let size = Vec2::new(texture_size.0 as f32, texture_size.1 as f32);
+        let min = Vec2::new(x as f32, y as f32) / size;
+        let max = Vec2::new((x + width) as f32, (y + height) as f32) / size;
+        Self::new(min, max)
+    }
+
+    /// The size of the region in uv coordinates
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+}
+
+/// A texture that is split into regions, so that many different sprites can be drawn from it in one batch.
+pub struct TextureAtlas {
+    texture: Taro<Texture2DView<Rgba8Srgb>>,
+    sampler: Taro<Sampler>,
+    regions: Vec<AtlasRegion>,
+    bindings: Taro<BindGroup>,
+}
+
+impl TextureAtlas {
+    /// Creates an atlas for `texture` with no regions
+    pub fn new(texture: Taro<Texture2DView<Rgba8Srgb>>) -> Self {
+        Self::with_sampler(texture, Sampler::new())
+    }
+
+    /// Creates an atlas for `texture` that is sampled using `sampler`.
+    ///
+    /// Pixel art usually wants a sampler with nearest filtering.
+    pub fn with_sampler(texture: Taro<Texture2DView<Rgba8Srgb>>, sampler: Taro<Sampler>) -> Self {
+        let bindings = BindGroupBuilder::new(0, Bind::new(sampler.clone()))
+            .insert(1, Bind::new(texture.clone()))
+            .build();
+
+        Self {
+            texture,
+            sampler,
+            regions: Vec::new(),
+            bindings,
+        }
+    }
+
+    /// Creates an atlas for `texture` that is split into a grid of equally sized cells.
+    ///
+    /// Regions are added row by row, starting at the top left.
+    pub fn from_grid(texture: Taro<Texture2DView<Rgba8Srgb>>, columns: u32, rows: u32) -> Self {
+        let mut atlas = Self::new(texture);
+        let cell = Vec2::ONE / Vec2::new(columns as f32, rows as f32);
+        for row in 0..rows {
+            for column in 0..columns {
+                let min = Vec2::new(column as f32, row as f32) * cell;
+                atlas.regions.push(AtlasRegion::new(min, min + cell));
+            }
+        }
+        atlas
+    }
+
+    /// Adds a region that covers a rectangle of pixels, returning its index
+    pub fn add_region(&mut self, x: u32, y: u32, width: u32, height: u32) -> usize {
+        let region = AtlasRegion::from_pixels(x, y, width, height, self.texture_size());
+        self.add_uv_region(region)
+    }
+
+    /// Adds a region in uv coordinates, returning its index
+    pub fn add_uv_region(&mut self, region: AtlasRegion) -> usize {
+        self.regions.push(region);
+        self.regions.len() - 1
+    }
+
+    pub fn region(&self, index: usize) -> Option<AtlasRegion> {
+        self.regions.get(index).copied()
+    }
+
+    pub fn regions(&self) -> &[AtlasRegion] {
+        &self.regions
+    }
+
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    /// Gets the width and height of the atlas texture in pixels
+    pub fn texture_size(&self) -> (u32, u32) {
+        self.texture.texture().size()
+    }
+
+    pub fn texture(&self) -> &Taro<Texture2DView<Rgba8Srgb>> {
+        &self.texture
+    }
+
+    pub fn sampler(&self) -> &Taro<Sampler> {
+        &self.sampler
+    }
+
+    /// Gets the bindings for this atlas
+    ///
+    /// The bind group is in this order `0 => sampler` ` 1 => texture_view`
+    pub fn bindings(&self) -> &Taro<BindGroup> {
+        &self.bindings
+    }
+
+    /// The kind of sampler binding, which decides which pipeline layout the atlas is compatible with
+    pub(crate) fn sampler_type(&self) -> wgpu::SamplerBindingType {
+        match self.sampler.bind_type() {
+            wgpu::BindingType::Sampler(sampler_type) => sampler_type,
+            _ => wgpu::SamplerBindingType::Filtering,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use taro_core::data::texture::Texture2D;
+
+    use super::*;
+
+    #[test]
+    fn grid_and_pixel_regions() {
+        let texture = Texture2DView::from_texture(Texture2D::empty(64, 32));
+        let mut atlas = TextureAtlas::from_grid(texture, 4, 2);
+        assert_eq!(atlas.len(), 8);
+        assert_eq!(
+            atlas.region(5),
+            Some(AtlasRegion::new(Vec2::new(0.25, 0.5), Vec2::new(0.5, 1.)))
+        );
+
+        let index = atlas.add_region(16, 8, 32, 16);
+        assert_eq!(index, 8);
+        let region = atlas.region(index).unwrap();
+        assert_eq!(region.min, Vec2::new(0.25, 0.25));
+        assert_eq!(region.size(), Vec2::new(0.5, 0.5));
+        assert_eq!(atlas.region(9), None);
+    }
+}
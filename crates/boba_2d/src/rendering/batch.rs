@@ -0,0 +1,148 @@
+use std::{ops::Range, rc::Rc};
+
+use log::error;
+
+use super::{SpriteError, SpriteVertex, TaroSpriteRenderer, TextureAtlas};
+
+/// A run of sprites that share a [`TextureAtlas`], drawn with a single draw call
+pub struct SpriteBatch {
+    pub atlas: Rc<TextureAtlas>,
+    pub indices: Range<u32>,
+}
+
+/// Sorts sprites by z-order and merges them into as few [`SpriteBatch`] draws as possible.
+///
+/// Sprites are drawn from the lowest world z-order to the highest, and sprites with the same z-order keep the order they were given in.
+/// Neighbouring sprites in that order that use the same atlas are merged into one batch,
+/// so sprites from a shared atlas batch best when they are on the same z-order.
+#[derive(Default)]
+pub struct SpriteBatcher {
+    vertices: Vec<SpriteVertex>,
+    indices: Vec<u32>,
+    batches: Vec<SpriteBatch>,
+}
+
+impl SpriteBatcher {
+    /// Replaces the current batches with batches for `sprites`.
+    ///
+    /// Sprites that cannot be drawn are skipped.
+    pub fn batch<'a>(&mut self, sprites: impl IntoIterator<Item = &'a TaroSpriteRenderer>) {
+        self.clear();
+
+        let mut sorted = Vec::new();
+        for sprite in sprites {
+            match sprite_quad(sprite) {
+                Ok((z_order, vertices)) => sorted.push((z_order, sprite, vertices)),
+                Err(e) => error!("Could not batch sprite. Error: {e}"),
+            }
+        }
+        sorted.sort_by_key(|(z_order, _, _)| *z_order);
+
+        for (_, sprite, vertices) in sorted {
+            let base = self.vertices.len() as u32;
+            self.vertices.extend(vertices);
+            let start = self.indices.len() as u32;
+            self.indices
+                .extend([0, 1, 2, 0, 2, 3].map(|index| base + index));
+            let end = self.indices.len() as u32;
+
+            match self.batches.last_mut() {
+                Some(batch) if Rc::ptr_eq(&batch.atlas, &sprite.atlas) => batch.indices.end = end,
+                _ => self.batches.push(SpriteBatch {
+                    atlas: sprite.atlas.clone(),
+                    indices: start..end,
+                }),
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.indices.clear();
+        self.batches.clear();
+    }
+
+    pub fn vertices(&self) -> &[SpriteVertex] {
+        &self.vertices
+    }
+
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    pub fn batches(&self) -> &[SpriteBatch] {
+        &self.batches
+    }
+}
+
+fn sprite_quad(sprite: &TaroSpriteRenderer) -> Result<(i32, [SpriteVertex; 4]), SpriteError> {
+    let z_order = sprite.transform.borrow()?.world_z_order();
+    Ok((z_order, sprite.vertices()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use boba_3d::glam::Vec2;
+    use taro_core::data::texture::{Texture2D, Texture2DView};
+
+    use crate::pearls::BobaTransform2D;
+
+    use super::*;
+
+    fn atlas() -> Rc<TextureAtlas> {
+        let texture = Texture2DView::from_texture(Texture2D::empty(16, 16));
+        Rc::new(TextureAtlas::new(texture))
+    }
+
+    fn sprite(x: f32, z_order: i32, atlas: &Rc<TextureAtlas>) -> TaroSpriteRenderer {
+        let transform = BobaTransform2D::from_position(Vec2::X * x).with_z_order(z_order);
+        TaroSpriteRenderer::new_simple(transform, atlas.clone())
+    }
+
+    #[test]
+    fn sorts_and_batches() {
+        let (first, second) = (atlas(), atlas());
+        let sprites = [
+            sprite(0., 1, &first),
+            sprite(1., 0, &second),
+            sprite(2., 1, &first),
+            sprite(3., 0, &first),
+            sprite(4., 2, &second),
+        ];
+
+        let mut batcher = SpriteBatcher::default();
+        batcher.batch(&sprites);
+        assert_eq!(batcher.vertices().len(), 20);
+        assert_eq!(batcher.indices().len(), 30);
+
+        // sprites are drawn by z-order, keeping the given order within a z-order
+        let order = batcher
+            .vertices()
+            .chunks(4)
+            .map(|quad| quad[0].position[0] + 0.5)
+            .collect::<Vec<_>>();
+        assert_eq!(order, vec![1., 3., 0., 2., 4.]);
+
+        // the three sprites from the first atlas in a row share one draw
+        let batches = batcher
+            .batches()
+            .iter()
+            .map(|batch| (Rc::ptr_eq(&batch.atlas, &first), batch.indices.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(batches, vec![(false, 0..6), (true, 6..24), (false, 24..30)]);
+        assert_eq!(&batcher.indices()[6..12], &[4, 5, 6, 4, 6, 7]);
+    }
+
+    #[test]
+    fn skips_broken_sprites() {
+        let atlas = atlas();
+        let destroyed = sprite(0., 0, &atlas);
+        destroyed.transform.destroy().unwrap();
+        let missing = sprite(1., 0, &atlas).with_region(3);
+
+        let mut batcher = SpriteBatcher::default();
+        batcher.batch([&destroyed, &missing, &sprite(2., 0, &atlas)]);
+        assert_eq!(batcher.batches().len(), 1);
+        assert_eq!(batcher.indices().len(), 6);
+    }
+}
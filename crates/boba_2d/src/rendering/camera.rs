@@ -0,0 +1,190 @@
+use boba_3d::glam::{Mat4, Quat, Vec2, Vec4Swizzles};
+use boba_core::{Pearl, PearlError};
+use log::error;
+use taro_core::{
+    data::{buffers::CameraMatrix, Buffer, UniformBinding},
+    rendering::{RenderPipeline, RenderTexture, TaroRenderPearls},
+    wgpu, Bind, Taro, TaroHardware,
+};
+
+use crate::pearls::BobaTransform2D;
+
+/// Settings for [`TaroCamera2D`]
+#[derive(Debug, Clone)]
+pub struct TaroCamera2DSettings {
+    /// Half of the height of the view in world units
+    pub size: f32,
+}
+
+impl Default for TaroCamera2DSettings {
+    fn default() -> Self {
+        Self { size: 5. }
+    }
+}
+
+impl TaroCamera2DSettings {
+    /// Creates the orthographic projection matrix for a camera with these settings
+    pub fn projection_matrix(&self, aspect: f32) -> Mat4 {
+        let (width, height) = (self.size * aspect, self.size);
+        Mat4::orthographic_rh(-width, width, -height, height, -1., 1.)
+    }
+}
+
+/// An orthographic camera that renders a 2D world to a [`RenderTexture`]
+///
+/// The camera looks at the position of its transform and turns with its rotation. The scale of the transform is ignored,
+/// so the camera is zoomed by changing the size in its settings.
+///
+/// Viewport coordinates go from `(0, 0)` in the top left of the rendered image to `(1, 1)` in the bottom right,
+/// the same as the 3D camera.
+pub struct TaroCamera2D {
+    aspect_ratio: f32,
+    camera_matrix: Taro<UniformBinding<CameraMatrix>>,
+    pipeline: Box<dyn RenderPipeline>,
+
+    pub transform: Pearl<BobaTransform2D>,
+    pub settings: TaroCamera2DSettings,
+}
+
+impl TaroCamera2D {
+    /// Creates a new camera with `transform`
+    pub fn new_simple(transform: BobaTransform2D, pipeline: impl RenderPipeline) -> Self {
+        Self::new_with_settings(
+            Pearl::wrap(transform),
+            TaroCamera2DSettings::default(),
+            pipeline,
+        )
+    }
+
+    /// Creates a new camera with `transform` and `settings`
+    pub fn new_with_settings(
+        transform: Pearl<BobaTransform2D>,
+        settings: TaroCamera2DSettings,
+        pipeline: impl RenderPipeline,
+    ) -> Self {
+        Self {
+            aspect_ratio: 1.,
+            camera_matrix: Bind::new(Buffer::new(wgpu::BufferUsages::UNIFORM)),
+            pipeline: Box::new(pipeline),
+            transform,
+            settings,
+        }
+    }
+
+    /// Gets the aspect ratio of the last texture the camera rendered to
+    pub fn aspect_ratio(&self) -> f32 {
+        self.aspect_ratio
+    }
+
+    /// Sets the aspect ratio of the camera. It is replaced the next time the camera renders.
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f32) {
+        self.aspect_ratio = aspect_ratio;
+    }
+
+    /// Gets the `projection * view` matrix of the camera
+    pub fn view_projection_matrix(&self) -> Result<Mat4, PearlError> {
+        let transform = self.transform.borrow()?;
+        let camera = Mat4::from_rotation_translation(
+            Quat::from_rotation_z(transform.world_rotation()),
+            transform.world_position().extend(0.),
+        );
+        Ok(self.settings.projection_matrix(self.aspect_ratio) * camera.inverse())
+    }
+
+    /// Converts a world space `point` into viewport coordinates
+    pub fn world_to_viewport(&self, point: Vec2) -> Result<Vec2, PearlError> {
+        let ndc = self.view_projection_matrix()? * point.extend(0.).extend(1.);
+        Ok(Vec2::new((ndc.x + 1.) * 0.5, (1. - ndc.y) * 0.5))
+    }
+
+    /// Converts `viewport` coordinates into a world space point
+    pub fn viewport_to_world(&self, viewport: Vec2) -> Result<Vec2, PearlError> {
+        let inverse = self.view_projection_matrix()?.inverse();
+        let ndc = Vec2::new(viewport.x * 2. - 1., 1. - viewport.y * 2.);
+        Ok((inverse * ndc.extend(0.).extend(1.)).xy())
+    }
+
+    /// Replaces the cameras current [`RenderPipeline`] with a new `pipeline`
+    pub fn set_pipeline(&mut self, pipeline: impl RenderPipeline) {
+        self.pipeline = Box::new(pipeline)
+    }
+
+    /// Renders the camera to the given `texture` using the provided `pearls` and `hardware`
+    pub fn render(
+        &mut self,
+        texture: &RenderTexture,
+        pearls: &TaroRenderPearls,
+        hardware: &TaroHardware,
+    ) {
+        let size = texture.size();
+        self.aspect_ratio = size.0 as f32 / size.1 as f32;
+        match self.view_projection_matrix() {
+            Ok(matrix) => {
+                let matrix: CameraMatrix = matrix.into();
+                self.camera_matrix
+                    .bind_data()
+                    .write_to_hardware(matrix.into(), hardware);
+            }
+            Err(e) => {
+                error!("Error when calculating camera matrix. Error: {e}");
+            }
+        };
+
+        self.pipeline
+            .render(texture, pearls, &self.camera_matrix, hardware);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    const EPSILON: f32 = 0.0001;
+
+    struct EmptyPipeline;
+
+    impl RenderPipeline for EmptyPipeline {
+        fn render(
+            &mut self,
+            _: &RenderTexture,
+            _: &TaroRenderPearls,
+            _: &Taro<UniformBinding<CameraMatrix>>,
+            _: &TaroHardware,
+        ) {
+        }
+    }
+
+    #[test]
+    fn viewport_conversion() {
+        let transform = BobaTransform2D::from_position(Vec2::new(10., 5.));
+        let mut camera = TaroCamera2D::new_simple(transform, EmptyPipeline);
+        camera.set_aspect_ratio(2.);
+
+        // the view is 20 units wide and 10 units high, with up at the top of the viewport
+        let corners = [
+            (Vec2::new(0.5, 0.5), Vec2::new(10., 5.)),
+            (Vec2::new(0., 0.), Vec2::new(0., 10.)),
+            (Vec2::new(1., 1.), Vec2::new(20., 0.)),
+        ];
+        for (viewport, world) in corners {
+            let point = camera.viewport_to_world(viewport).unwrap();
+            assert!(point.abs_diff_eq(world, EPSILON), "{point} != {world}");
+            let back = camera.world_to_viewport(world).unwrap();
+            assert!(back.abs_diff_eq(viewport, EPSILON), "{back} != {viewport}");
+        }
+    }
+
+    #[test]
+    fn rotated_and_zoomed() {
+        let transform = BobaTransform2D::from_position_rotation(Vec2::ZERO, FRAC_PI_2);
+        let settings = TaroCamera2DSettings { size: 1. };
+        let camera =
+            TaroCamera2D::new_with_settings(Pearl::wrap(transform), settings, EmptyPipeline);
+
+        // turning the camera a quarter turn counter clockwise makes world -x the top of the viewport
+        let top = camera.world_to_viewport(Vec2::NEG_X).unwrap();
+        assert!(top.abs_diff_eq(Vec2::new(0.5, 0.), EPSILON), "{top}");
+    }
+}
@@ -0,0 +1,11 @@
+mod atlas;
+mod batch;
+mod camera;
+mod pipeline;
+mod sprite;
+
+pub use atlas::*;
+pub use batch::*;
+pub use camera::*;
+pub use pipeline::*;
+pub use sprite::*;
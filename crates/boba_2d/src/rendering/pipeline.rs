@@ -0,0 +1,237 @@
+use once_map::OnceMap;
+use taro_core::{
+    data::{buffers::CameraMatrix, UniformBinding},
+    rendering::{RenderPipeline, RenderTexture, TaroRenderPearls},
+    wgpu, HardwareId, Taro, TaroHardware,
+};
+
+use super::{SpriteBatcher, SpriteVertex, TaroSpriteRenderer, TextureAtlas};
+
+/// A [`RenderPipeline`] that draws every [`TaroSpriteRenderer`] straight into the render texture.
+///
+/// Sprites are sorted and batched with a [`SpriteBatcher`], so each run of sprites that share an atlas is a single draw call.
+/// They are alpha blended in z-order, so there is no depth buffer.
+pub struct TaroSpritePipeline {
+    batcher: SpriteBatcher,
+    vertices: StreamBuffer,
+    indices: StreamBuffer,
+
+    /// The color the texture is cleared to before drawing, or `None` to draw over what is already in the texture
+    pub clear_color: Option<wgpu::Color>,
+}
+
+impl Default for TaroSpritePipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaroSpritePipeline {
+    /// Creates a pipeline that clears the texture to black before drawing
+    pub fn new() -> Self {
+        Self::with_clear_color(Some(wgpu::Color::BLACK))
+    }
+
+    /// Creates a pipeline that draws sprites over whatever was rendered to the texture before it,
+    /// like the image from a 3D camera
+    pub fn overlay() -> Self {
+        Self::with_clear_color(None)
+    }
+
+    pub fn with_clear_color(clear_color: Option<wgpu::Color>) -> Self {
+        Self {
+            batcher: Default::default(),
+            vertices: StreamBuffer::new("Sprite Vertex Buffer", wgpu::BufferUsages::VERTEX),
+            indices: StreamBuffer::new("Sprite Index Buffer", wgpu::BufferUsages::INDEX),
+            clear_color,
+        }
+    }
+}
+
+impl RenderPipeline for TaroSpritePipeline {
+    fn render(
+        &mut self,
+        texture: &RenderTexture,
+        pearls: &TaroRenderPearls,
+        camera_matrix: &Taro<UniformBinding<CameraMatrix>>,
+        hardware: &TaroHardware,
+    ) {
+        let sprites = pearls.collect::<TaroSpriteRenderer>();
+        self.batcher.batch(sprites.iter().map(|sprite| &**sprite));
+
+        let mut encoder =
+            hardware
+                .device()
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Sprite Pipeline Command Encoder"),
+                });
+
+        // the geometry for every batch is uploaded at once, and each batch draws its own range of indices
+        let buffers = match self.batcher.batches().is_empty() {
+            true => None,
+            false => {
+                let vertices = bytemuck::cast_slice(self.batcher.vertices());
+                let indices = bytemuck::cast_slice(self.batcher.indices());
+                let vertex_slice = self.vertices.write(vertices, hardware);
+                let index_slice = self.indices.write(indices, hardware);
+                Some((vertex_slice, index_slice))
+            }
+        };
+
+        let load = match self.clear_color {
+            Some(color) => wgpu::LoadOp::Clear(color),
+            None => wgpu::LoadOp::Load,
+        };
+
+        let camera_binding = camera_matrix.get_or_compile(hardware);
+
+        // --- SPRITE PASS ---
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Sprite Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: texture.view(),
+                    resolve_target: None,
+                    ops: wgpu::Operations { load, store: true },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            if let Some((vertices, indices)) = buffers {
+                pass.set_bind_group(0, camera_binding.bind_group(), &[]);
+                pass.set_vertex_buffer(0, vertices);
+                pass.set_index_buffer(indices, wgpu::IndexFormat::Uint32);
+
+                for batch in self.batcher.batches() {
+                    let atlas_binding = batch.atlas.bindings().get_or_compile(hardware);
+                    let pipeline = sprite_pipeline(
+                        &batch.atlas,
+                        camera_binding.layout(),
+                        atlas_binding.layout(),
+                        hardware,
+                    );
+
+                    pass.set_pipeline(pipeline);
+                    pass.set_bind_group(1, atlas_binding.bind_group(), &[]);
+                    pass.draw_indexed(batch.indices.clone(), 0, 0..1);
+                }
+            }
+        }
+
+        // submit command encoder for rendering
+        hardware.queue().submit(std::iter::once(encoder.finish()));
+    }
+}
+
+/// A buffer that sprite geometry is written into every frame.
+///
+/// The buffer is only recreated when the geometry outgrows it, or when drawing on different hardware.
+struct StreamBuffer {
+    label: &'static str,
+    usage: wgpu::BufferUsages,
+    buffer: Option<(HardwareId, wgpu::Buffer)>,
+}
+
+impl StreamBuffer {
+    fn new(label: &'static str, usage: wgpu::BufferUsages) -> Self {
+        Self {
+            label,
+            usage: usage | wgpu::BufferUsages::COPY_DST,
+            buffer: None,
+        }
+    }
+
+    /// Writes `data` to the start of the buffer, growing it if needed, and returns the slice that was written
+    fn write(&mut self, data: &[u8], hardware: &TaroHardware) -> wgpu::BufferSlice<'_> {
+        let size = data.len() as wgpu::BufferAddress;
+        let fits = match &self.buffer {
+            Some((id, buffer)) => id == hardware.id() && buffer.size() >= size,
+            None => false,
+        };
+
+        if !fits {
+            let buffer = hardware.device().create_buffer(&wgpu::BufferDescriptor {
+                label: Some(self.label),
+                size: size.next_power_of_two(),
+                usage: self.usage,
+                mapped_at_creation: false,
+            });
+            self.buffer = Some((*hardware.id(), buffer));
+        }
+
+        let (_, buffer) = self.buffer.as_ref().unwrap();
+        hardware.queue().write_buffer(buffer, 0, data);
+        buffer.slice(..size)
+    }
+}
+
+/// Gets the render pipeline for sprites drawn from `atlas`, creating it the first time it is needed on `hardware`.
+///
+/// Atlases with filtering and non filtering samplers have different bind group layouts, so they each get a pipeline.
+fn sprite_pipeline(
+    atlas: &TextureAtlas,
+    camera_layout: &wgpu::BindGroupLayout,
+    atlas_layout: &wgpu::BindGroupLayout,
+    hardware: &TaroHardware,
+) -> &'static wgpu::RenderPipeline {
+    static PIPELINES: OnceMap<(HardwareId, wgpu::SamplerBindingType), wgpu::RenderPipeline> =
+        OnceMap::new();
+
+    PIPELINES
+        .get_or_init((*hardware.id(), atlas.sampler_type()), || {
+            let layout =
+                hardware
+                    .device()
+                    .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                        label: Some("Sprite Pipeline Layout"),
+                        bind_group_layouts: &[camera_layout, atlas_layout],
+                        push_constant_ranges: &[],
+                    });
+
+            let module = &hardware
+                .device()
+                .create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some("Sprite Shader"),
+                    source: wgpu::ShaderSource::Wgsl(include_str!("sprite.wgsl").into()),
+                });
+
+            hardware
+                .device()
+                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("Sprite Pipeline"),
+                    layout: Some(&layout),
+                    vertex: wgpu::VertexState {
+                        module,
+                        entry_point: "vs_main",
+                        buffers: &[SpriteVertex::BUFFER_LAYOUT],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module,
+                        entry_point: "fs_main",
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format: *hardware.format(),
+                            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                    }),
+                    // flipped and mirrored sprites wind the other way, so nothing is culled
+                    primitive: wgpu::PrimitiveState {
+                        topology: wgpu::PrimitiveTopology::TriangleList,
+                        strip_index_format: None,
+                        front_face: wgpu::FrontFace::Ccw,
+                        cull_mode: None,
+                        polygon_mode: wgpu::PolygonMode::Fill,
+                        unclipped_depth: false,
+                        conservative: false,
+                    },
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState {
+                        count: 1,
+                        mask: !0,
+                        alpha_to_coverage_enabled: false,
+                    },
+                    multiview: None,
+                })
+        })
+        .into_data()
+}
@@ -0,0 +1,200 @@
+use std::rc::Rc;
+
+use boba_3d::glam::Vec2;
+use boba_core::{Pearl, PearlError};
+use taro_core::{data::buffers::Color, wgpu};
+use thiserror::Error;
+
+use crate::pearls::BobaTransform2D;
+
+use super::TextureAtlas;
+
+/// A corner of a sprite quad, in world space
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SpriteVertex {
+    pub position: [f32; 2],
+    pub uv: [f32; 2],
+    pub color: [f32; 4],
+}
+
+impl SpriteVertex {
+    pub const BUFFER_LAYOUT: wgpu::VertexBufferLayout<'_> = wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<SpriteVertex>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4],
+    };
+}
+
+#[derive(Debug, Error)]
+pub enum SpriteError {
+    #[error("Region {0} does not exist in the sprite's texture atlas")]
+    MissingRegion(usize),
+    #[error("Could not read the sprite transform. Error: {0}")]
+    TransformError(#[from] PearlError),
+}
+
+/// Renders a textured quad from a region of a [`TextureAtlas`].
+///
+/// The quad is `size` world units large and placed so that `pivot` is at the position of the transform,
+/// where a pivot of `(0, 0)` is the bottom left corner and `(1, 1)` is the top right corner.
+pub struct TaroSpriteRenderer {
+    pub transform: Pearl<BobaTransform2D>,
+    pub atlas: Rc<TextureAtlas>,
+    /// The atlas region to draw, or `None` to draw the whole texture
+    pub region: Option<usize>,
+    pub size: Vec2,
+    pub pivot: Vec2,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    pub tint: Color,
+}
+
+impl TaroSpriteRenderer {
+    /// Creates a one unit square sprite that draws the whole texture of `atlas`
+    pub fn new(transform: Pearl<BobaTransform2D>, atlas: Rc<TextureAtlas>) -> Self {
+        Self {
+            transform,
+            atlas,
+            region: None,
+            size: Vec2::ONE,
+            pivot: Vec2::splat(0.5),
+            flip_x: false,
+            flip_y: false,
+            tint: Color::default(),
+        }
+    }
+
+    pub fn new_simple(transform: BobaTransform2D, atlas: Rc<TextureAtlas>) -> Self {
+        Self::new(Pearl::wrap(transform), atlas)
+    }
+
+    pub fn with_region(mut self, region: usize) -> Self {
+        self.region = Some(region);
+        self
+    }
+
+    pub fn with_size(mut self, size: Vec2) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_pivot(mut self, pivot: Vec2) -> Self {
+        self.pivot = pivot;
+        self
+    }
+
+    pub fn with_flip(mut self, flip_x: bool, flip_y: bool) -> Self {
+        self.flip_x = flip_x;
+        self.flip_y = flip_y;
+        self
+    }
+
+    pub fn with_tint(mut self, tint: Color) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// Creates the world space corners of the sprite quad.
+    ///
+    /// The corners are in counter clockwise order, starting at the bottom left.
+    pub fn vertices(&self) -> Result<[SpriteVertex; 4], SpriteError> {
+        let region = match self.region {
+            None => Default::default(),
+            Some(index) => self
+                .atlas
+                .region(index)
+                .ok_or(SpriteError::MissingRegion(index))?,
+        };
+
+        let (mut left, mut right) = (region.min.x, region.max.x);
+        let (mut top, mut bottom) = (region.min.y, region.max.y);
+        if self.flip_x {
+            std::mem::swap(&mut left, &mut right);
+        }
+        if self.flip_y {
+            std::mem::swap(&mut top, &mut bottom);
+        }
+
+        let matrix = self.transform.borrow()?.world_matrix();
+        let corners = [
+            (Vec2::new(0., 0.), [left, bottom]),
+            (Vec2::new(1., 0.), [right, bottom]),
+            (Vec2::new(1., 1.), [right, top]),
+            (Vec2::new(0., 1.), [left, top]),
+        ];
+
+        Ok(corners.map(|(corner, uv)| SpriteVertex {
+            position: matrix
+                .transform_point2((corner - self.pivot) * self.size)
+                .to_array(),
+            uv,
+            color: self.tint.values,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use taro_core::data::texture::{Texture2D, Texture2DView};
+
+    use super::*;
+
+    const EPSILON: f32 = 0.0001;
+
+    fn atlas() -> Rc<TextureAtlas> {
+        let texture = Texture2DView::from_texture(Texture2D::empty(32, 32));
+        Rc::new(TextureAtlas::from_grid(texture, 2, 2))
+    }
+
+    fn positions(vertices: &[SpriteVertex; 4]) -> Vec<Vec2> {
+        vertices.iter().map(|v| Vec2::from(v.position)).collect()
+    }
+
+    #[test]
+    fn quad_in_world_space() {
+        let transform = BobaTransform2D::new(Vec2::new(5., 0.), FRAC_PI_2, Vec2::ONE);
+        let sprite = TaroSpriteRenderer::new_simple(transform, atlas())
+            .with_size(Vec2::new(2., 1.))
+            .with_pivot(Vec2::ZERO);
+
+        let expected = [(0., 0.), (0., 2.), (-1., 2.), (-1., 0.)];
+        let vertices = sprite.vertices().unwrap();
+        for (position, (x, y)) in positions(&vertices).into_iter().zip(expected) {
+            assert!(position.abs_diff_eq(Vec2::new(5. + x, y), EPSILON));
+        }
+    }
+
+    #[test]
+    fn regions_flipping_and_tint() {
+        let tint = Color::from_rgba(1., 0., 0., 0.5);
+        let mut sprite = TaroSpriteRenderer::new_simple(BobaTransform2D::default(), atlas())
+            .with_region(1)
+            .with_tint(tint);
+
+        // region 1 is the top right cell, and v grows downwards
+        let uvs = sprite.vertices().unwrap().map(|v| v.uv);
+        assert_eq!(uvs, [[0.5, 0.5], [1., 0.5], [1., 0.], [0.5, 0.]]);
+        assert!(sprite
+            .vertices()
+            .unwrap()
+            .iter()
+            .all(|v| v.color == tint.values));
+
+        sprite.flip_x = true;
+        let uvs = sprite.vertices().unwrap().map(|v| v.uv);
+        assert_eq!(uvs, [[1., 0.5], [0.5, 0.5], [0.5, 0.], [1., 0.]]);
+
+        sprite.flip_y = true;
+        let uvs = sprite.vertices().unwrap().map(|v| v.uv);
+        assert_eq!(uvs, [[1., 0.], [0.5, 0.], [0.5, 0.5], [1., 0.5]]);
+
+        sprite.region = Some(4);
+        assert!(matches!(
+            sprite.vertices(),
+            Err(SpriteError::MissingRegion(4))
+        ));
+    }
+}
@@ -0,0 +1,36 @@
+@group(0) @binding(0)
+var<uniform> camera_matrix: mat4x4<f32>;
+
+@group(1) @binding(0)
+var s_sprite: sampler;
+@group(1) @binding(1)
+var t_sprite: texture_2d<f32>;
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(
+    sprite: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+
+    out.tex_coords = sprite.tex_coords;
+    out.color = sprite.color;
+    out.clip_position = camera_matrix * vec4<f32>(sprite.position, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(t_sprite, s_sprite, in.tex_coords) * in.color;
+}
@@ -0,0 +1,579 @@
+use std::{fs, path::Path};
+
+use glam::{Quat, Vec3, Vec4};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::tween::Lerp;
+
+use super::{AnimationPose, TransformPose};
+
+/// An error returned when loading an [`AnimationClip`].
+#[derive(Debug, Error)]
+pub enum AnimationClipError {
+    #[error("Could not read animation clip. Error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse animation clip. Error: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("Track for '{0}' has no keyframes")]
+    EmptyTrack(String),
+}
+
+/// How values are interpolated between the keyframes of a track.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Interpolation {
+    /// Holds the value of the previous keyframe
+    Step,
+    #[default]
+    Linear,
+    /// Cubic hermite interpolation. Keyframes without tangents use smooth catmull-rom tangents.
+    Cubic,
+}
+
+/// A value that can be stored in a keyframe.
+pub trait KeyframeValue: Lerp + Copy + Serialize + DeserializeOwned {
+    fn to_vec4(self) -> Vec4;
+    fn from_vec4(value: Vec4) -> Self;
+
+    /// The sign that moves `value` into the same hemisphere as `reference`.
+    ///
+    /// Only needed for values like quaternions, where `v` and `-v` are the same value.
+    fn hemisphere(_value: Vec4, _reference: Vec4) -> f32 {
+        1.
+    }
+}
+
+impl KeyframeValue for Vec3 {
+    fn to_vec4(self) -> Vec4 {
+        self.extend(0.)
+    }
+
+    fn from_vec4(value: Vec4) -> Self {
+        value.truncate()
+    }
+}
+
+impl KeyframeValue for Quat {
+    fn to_vec4(self) -> Vec4 {
+        Vec4::from(self)
+    }
+
+    fn from_vec4(value: Vec4) -> Self {
+        Quat::from_vec4(value).normalize()
+    }
+
+    fn hemisphere(value: Vec4, reference: Vec4) -> f32 {
+        match value.dot(reference) < 0. {
+            true => -1.,
+            false => 1.,
+        }
+    }
+}
+
+/// A value at a point in time.
+///
+/// Tangents are only used by [`Interpolation::Cubic`], and are measured in value per second.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Keyframe<V> {
+    pub time: f32,
+    pub value: V,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub in_tangent: Option<V>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub out_tangent: Option<V>,
+}
+
+impl<V> Keyframe<V> {
+    pub fn new(time: f32, value: V) -> Self {
+        Self {
+            time,
+            value,
+            in_tangent: None,
+            out_tangent: None,
+        }
+    }
+
+    /// Sets the incoming and outgoing tangents
+    pub fn with_tangents(mut self, in_tangent: V, out_tangent: V) -> Self {
+        self.in_tangent = Some(in_tangent);
+        self.out_tangent = Some(out_tangent);
+        self
+    }
+}
+
+/// Samples the keyframes in `keys` at `time`. `keys` must be sorted by time and not empty.
+fn sample_keys<V: KeyframeValue>(
+    keys: &[Keyframe<V>],
+    interpolation: Interpolation,
+    time: f32,
+) -> V {
+    let next = keys.partition_point(|key| key.time <= time);
+    if next == 0 {
+        return keys[0].value;
+    }
+    if next == keys.len() {
+        return keys[next - 1].value;
+    }
+
+    let (a, b) = (&keys[next - 1], &keys[next]);
+    let span = b.time - a.time;
+    let s = (time - a.time) / span;
+    match interpolation {
+        Interpolation::Step => a.value,
+        Interpolation::Linear => a.value.lerp(&b.value, s),
+        Interpolation::Cubic => {
+            // b and its tangent are flipped into the hemisphere of a, so quaternions take the short way around
+            let a_value = a.value.to_vec4();
+            let sign = V::hemisphere(b.value.to_vec4(), a_value);
+            let b_value = b.value.to_vec4() * sign;
+
+            let out_tangent = a
+                .out_tangent
+                .map_or_else(|| tangent(keys, next - 1), |t| t.to_vec4());
+            let in_tangent = b
+                .in_tangent
+                .map_or_else(|| tangent(keys, next), |t| t.to_vec4())
+                * sign;
+
+            let (s2, s3) = (s * s, s * s * s);
+            let value = (2. * s3 - 3. * s2 + 1.) * a_value
+                + (s3 - 2. * s2 + s) * span * out_tangent
+                + (-2. * s3 + 3. * s2) * b_value
+                + (s3 - s2) * span * in_tangent;
+
+            // quaternions are normalized again by from_vec4
+            V::from_vec4(value)
+        }
+    }
+}
+
+/// Calculates a catmull-rom tangent for the keyframe at `index`, in the hemisphere of its value
+fn tangent<V: KeyframeValue>(keys: &[Keyframe<V>], index: usize) -> Vec4 {
+    let value = keys[index].value.to_vec4();
+    let previous = &keys[index.saturating_sub(1)];
+    let next = &keys[(index + 1).min(keys.len() - 1)];
+    let span = next.time - previous.time;
+    if span <= 0. {
+        return Vec4::ZERO;
+    }
+
+    let aligned = |key: &Keyframe<V>| {
+        let key_value = key.value.to_vec4();
+        key_value * V::hemisphere(key_value, value)
+    };
+    (aligned(next) - aligned(previous)) / span
+}
+
+/// The transform property animated by a track, and its keyframes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "property", content = "keyframes")]
+pub enum TrackChannel {
+    Translation(Vec<Keyframe<Vec3>>),
+    Rotation(Vec<Keyframe<Quat>>),
+    Scale(Vec<Keyframe<Vec3>>),
+}
+
+/// Animates one property of a named target.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnimationTrack {
+    pub target: String,
+    #[serde(default)]
+    pub interpolation: Interpolation,
+    pub channel: TrackChannel,
+}
+
+impl AnimationTrack {
+    pub fn translation(
+        target: &str,
+        interpolation: Interpolation,
+        keys: Vec<Keyframe<Vec3>>,
+    ) -> Self {
+        Self::new(target, interpolation, TrackChannel::Translation(keys))
+    }
+
+    pub fn rotation(target: &str, interpolation: Interpolation, keys: Vec<Keyframe<Quat>>) -> Self {
+        Self::new(target, interpolation, TrackChannel::Rotation(keys))
+    }
+
+    pub fn scale(target: &str, interpolation: Interpolation, keys: Vec<Keyframe<Vec3>>) -> Self {
+        Self::new(target, interpolation, TrackChannel::Scale(keys))
+    }
+
+    fn new(target: &str, interpolation: Interpolation, channel: TrackChannel) -> Self {
+        let mut track = Self {
+            target: target.into(),
+            interpolation,
+            channel,
+        };
+        track.sort();
+        track
+    }
+
+    fn sort(&mut self) {
+        match &mut self.channel {
+            TrackChannel::Translation(keys) | TrackChannel::Scale(keys) => {
+                keys.sort_by(|a, b| a.time.total_cmp(&b.time))
+            }
+            TrackChannel::Rotation(keys) => keys.sort_by(|a, b| a.time.total_cmp(&b.time)),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match &self.channel {
+            TrackChannel::Translation(keys) | TrackChannel::Scale(keys) => keys.is_empty(),
+            TrackChannel::Rotation(keys) => keys.is_empty(),
+        }
+    }
+
+    /// The time of the last keyframe
+    fn end(&self) -> f32 {
+        let last = match &self.channel {
+            TrackChannel::Translation(keys) | TrackChannel::Scale(keys) => {
+                keys.last().map(|k| k.time)
+            }
+            TrackChannel::Rotation(keys) => keys.last().map(|k| k.time),
+        };
+        last.unwrap_or(0.)
+    }
+
+    fn sample_into(&self, time: f32, pose: &mut TransformPose) {
+        if self.is_empty() {
+            return;
+        }
+
+        match &self.channel {
+            TrackChannel::Translation(keys) => {
+                pose.translation = Some(sample_keys(keys, self.interpolation, time))
+            }
+            TrackChannel::Rotation(keys) => {
+                pose.rotation = Some(sample_keys(keys, self.interpolation, time))
+            }
+            TrackChannel::Scale(keys) => {
+                pose.scale = Some(sample_keys(keys, self.interpolation, time))
+            }
+        }
+    }
+}
+
+/// A named marker in a clip. [`AnimationPlayer`](super::AnimationPlayer)s fire it when playback passes its time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnimationEvent {
+    pub time: f32,
+    pub name: String,
+}
+
+/// Authored animation made of keyframe tracks that target named transforms.
+///
+/// Clips can be built in code, or loaded from JSON:
+/// ```json
+/// {
+///   "name": "open",
+///   "tracks": [{
+///     "target": "door",
+///     "interpolation": "Cubic",
+///     "channel": {
+///       "property": "Translation",
+///       "keyframes": [{ "time": 0, "value": [0, 0, 0] }, { "time": 2, "value": [0, 3, 0] }]
+///     }
+///   }],
+///   "events": [{ "time": 2, "name": "opened" }]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "ClipData")]
+pub struct AnimationClip {
+    name: String,
+    looping: bool,
+    tracks: Vec<AnimationTrack>,
+    events: Vec<AnimationEvent>,
+    #[serde(skip)]
+    duration: f32,
+}
+
+/// The serialized fields of an [`AnimationClip`], which are sorted and measured when converted into a clip
+#[derive(Deserialize)]
+struct ClipData {
+    name: String,
+    #[serde(default)]
+    looping: bool,
+    #[serde(default)]
+    tracks: Vec<AnimationTrack>,
+    #[serde(default)]
+    events: Vec<AnimationEvent>,
+}
+
+impl From<ClipData> for AnimationClip {
+    fn from(data: ClipData) -> Self {
+        let mut clip = Self {
+            name: data.name,
+            looping: data.looping,
+            tracks: data.tracks,
+            events: data.events,
+            duration: 0.,
+        };
+
+        clip.tracks.iter_mut().for_each(AnimationTrack::sort);
+        clip.events.sort_by(|a, b| a.time.total_cmp(&b.time));
+        clip.calculate_duration();
+        clip
+    }
+}
+
+impl AnimationClip {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            looping: false,
+            tracks: Vec::new(),
+            events: Vec::new(),
+            duration: 0.,
+        }
+    }
+
+    /// Parses a clip from JSON
+    pub fn from_json(json: &str) -> Result<Self, AnimationClipError> {
+        let clip = serde_json::from_str::<Self>(json)?;
+        if let Some(track) = clip.tracks.iter().find(|track| track.is_empty()) {
+            return Err(AnimationClipError::EmptyTrack(track.target.clone()));
+        }
+
+        Ok(clip)
+    }
+
+    /// Loads a clip from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AnimationClipError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Serializes the clip into pretty printed JSON
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Sets whether the clip loops by default when played
+    pub fn with_looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    pub fn with_track(mut self, track: AnimationTrack) -> Self {
+        self.tracks.push(track);
+        self.calculate_duration();
+        self
+    }
+
+    /// Adds an event named `name` at `time`
+    pub fn with_event(mut self, time: f32, name: &str) -> Self {
+        self.events.push(AnimationEvent {
+            time,
+            name: name.into(),
+        });
+        self.events.sort_by(|a, b| a.time.total_cmp(&b.time));
+        self.calculate_duration();
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn looping(&self) -> bool {
+        self.looping
+    }
+
+    /// The time of the last keyframe or event
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    pub fn tracks(&self) -> &[AnimationTrack] {
+        &self.tracks
+    }
+
+    /// The events in the clip, sorted by time
+    pub fn events(&self) -> &[AnimationEvent] {
+        &self.events
+    }
+
+    /// Samples every track at `time`
+    pub fn sample(&self, time: f32) -> AnimationPose {
+        let mut pose = AnimationPose::default();
+        for track in self.tracks.iter() {
+            track.sample_into(time, pose.target_mut(&track.target));
+        }
+        pose
+    }
+
+    fn calculate_duration(&mut self) {
+        let tracks = self.tracks.iter().map(AnimationTrack::end);
+        let events = self.events.iter().map(|event| event.time);
+        self.duration = tracks.chain(events).fold(0., f32::max);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Quat, Vec3};
+
+    use super::{AnimationClip, AnimationClipError, AnimationTrack, Interpolation, Keyframe};
+
+    fn keys() -> Vec<Keyframe<Vec3>> {
+        vec![
+            Keyframe::new(0., Vec3::ZERO),
+            Keyframe::new(1., Vec3::X),
+            Keyframe::new(3., Vec3::X * 3.),
+        ]
+    }
+
+    fn sample(clip: &AnimationClip, time: f32) -> Vec3 {
+        clip.sample(time)
+            .get("target")
+            .unwrap()
+            .translation
+            .unwrap()
+    }
+
+    #[test]
+    fn step_and_linear() {
+        let step = AnimationClip::new("step").with_track(AnimationTrack::translation(
+            "target",
+            Interpolation::Step,
+            keys(),
+        ));
+        assert!(sample(&step, 0.5) == Vec3::ZERO);
+        assert!(sample(&step, 2.) == Vec3::X);
+        assert!(sample(&step, 10.) == Vec3::X * 3.);
+
+        let linear = AnimationClip::new("linear").with_track(AnimationTrack::translation(
+            "target",
+            Interpolation::Linear,
+            keys(),
+        ));
+        assert!(sample(&linear, 0.5) == Vec3::X * 0.5);
+        assert!(sample(&linear, 2.) == Vec3::X * 2.);
+        assert!(sample(&linear, -1.) == Vec3::ZERO);
+        assert!(linear.duration() == 3.);
+    }
+
+    #[test]
+    fn cubic() {
+        let keys = vec![
+            Keyframe::new(0., Vec3::ZERO).with_tangents(Vec3::ZERO, Vec3::ZERO),
+            Keyframe::new(1., Vec3::X).with_tangents(Vec3::ZERO, Vec3::ZERO),
+        ];
+        let clip = AnimationClip::new("cubic").with_track(AnimationTrack::translation(
+            "target",
+            Interpolation::Cubic,
+            keys,
+        ));
+
+        // zero tangents ease in and out like smoothstep
+        assert!(sample(&clip, 0.5).abs_diff_eq(Vec3::X * 0.5, 0.0001));
+        assert!(sample(&clip, 0.25).abs_diff_eq(Vec3::X * 0.15625, 0.0001));
+
+        // catmull-rom tangents on evenly spaced points stay on the line
+        let clip = AnimationClip::new("smooth").with_track(AnimationTrack::translation(
+            "target",
+            Interpolation::Cubic,
+            vec![
+                Keyframe::new(0., Vec3::ZERO),
+                Keyframe::new(1., Vec3::X),
+                Keyframe::new(2., Vec3::X * 2.),
+            ],
+        ));
+        assert!(sample(&clip, 1.5).abs_diff_eq(Vec3::X * 1.5, 0.0001));
+    }
+
+    #[test]
+    fn rotation() {
+        let clip = AnimationClip::new("turn").with_track(AnimationTrack::rotation(
+            "target",
+            Interpolation::Linear,
+            vec![
+                Keyframe::new(0., Quat::IDENTITY),
+                Keyframe::new(1., Quat::from_rotation_y(1.)),
+            ],
+        ));
+
+        let rotation = clip.sample(0.5).get("target").unwrap().rotation.unwrap();
+        assert!(rotation.abs_diff_eq(Quat::from_rotation_y(0.5), 0.0001));
+    }
+
+    #[test]
+    fn cubic_rotation_hemisphere() {
+        // the middle key is stored with the opposite sign, which is the same rotation
+        let clip = AnimationClip::new("turn").with_track(AnimationTrack::rotation(
+            "target",
+            Interpolation::Cubic,
+            vec![
+                Keyframe::new(0., Quat::IDENTITY),
+                Keyframe::new(1., -Quat::from_rotation_y(1.)),
+                Keyframe::new(2., Quat::from_rotation_y(2.)),
+            ],
+        ));
+
+        for (time, angle) in [(0.5, 0.5), (1., 1.), (1.5, 1.5)] {
+            let rotation = clip.sample(time).get("target").unwrap().rotation.unwrap();
+            let expected = Quat::from_rotation_y(angle);
+            assert!(rotation.is_normalized());
+            assert!(rotation.angle_between(expected) < 0.02, "{rotation}");
+        }
+    }
+
+    #[test]
+    fn load_json() {
+        let json = r#"{
+            "name": "open",
+            "looping": true,
+            "tracks": [{
+                "target": "door",
+                "channel": {
+                    "property": "Translation",
+                    "keyframes": [
+                        { "time": 2, "value": [0, 3, 0] },
+                        { "time": 0, "value": [0, 0, 0] }
+                    ]
+                }
+            }, {
+                "target": "door",
+                "interpolation": "Step",
+                "channel": {
+                    "property": "Scale",
+                    "keyframes": [{ "time": 0, "value": [1, 1, 1] }, { "time": 1, "value": [2, 2, 2] }]
+                }
+            }],
+            "events": [{ "time": 2.5, "name": "opened" }]
+        }"#;
+
+        let clip = AnimationClip::from_json(json).unwrap();
+        assert!(clip.name() == "open");
+        assert!(clip.looping());
+        assert!(clip.duration() == 2.5);
+
+        let pose = clip.sample(1.5);
+        let door = pose.get("door").unwrap();
+        assert!(door.translation == Some(Vec3::Y * 2.25));
+        assert!(door.scale == Some(Vec3::splat(2.)));
+        assert!(door.rotation.is_none());
+
+        let round_trip = AnimationClip::from_json(&clip.to_json().unwrap()).unwrap();
+        assert!(round_trip == clip);
+
+        // plain serde sorts and measures the clip as well
+        let deserialized = serde_json::from_str::<AnimationClip>(json).unwrap();
+        assert!(deserialized == clip);
+    }
+
+    #[test]
+    fn empty_track() {
+        let json = r#"{
+            "name": "broken",
+            "tracks": [{ "target": "door", "channel": { "property": "Rotation", "keyframes": [] } }]
+        }"#;
+
+        assert!(matches!(
+            AnimationClip::from_json(json),
+            Err(AnimationClipError::EmptyTrack(_))
+        ));
+    }
+}
@@ -0,0 +1,9 @@
+mod clip;
+mod player;
+mod pose;
+mod skeleton;
+
+pub use clip::*;
+pub use player::*;
+pub use pose::*;
+pub use skeleton::*;
@@ -0,0 +1,422 @@
+use std::rc::Rc;
+
+use boba_core::{
+    register_pearl_stages, stages::BobaUpdate, BobaResources, BobaResult, Pearl, PearlStage,
+};
+use indexmap::IndexMap;
+use log::error;
+use thiserror::Error;
+
+use crate::pearls::BobaTransform;
+
+use super::{AnimationClip, AnimationEvent, AnimationPose, TransformPose};
+
+/// An error returned by [`AnimationPlayer`].
+#[derive(Debug, Error)]
+pub enum AnimationError {
+    #[error("Animation clip '{0}' has not been added to the player")]
+    UnknownClip(String),
+}
+
+type EventHandler = Box<dyn FnMut(&AnimationEvent, &mut BobaResources) -> BobaResult>;
+
+/// The playback state of a single clip
+struct ClipState {
+    clip: Rc<AnimationClip>,
+    time: f32,
+    looping: bool,
+    started: bool,
+}
+
+impl ClipState {
+    fn new(clip: Rc<AnimationClip>) -> Self {
+        Self {
+            looping: clip.looping(),
+            clip,
+            time: 0.,
+            started: false,
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        !self.looping && self.time >= self.clip.duration()
+    }
+
+    /// Moves the playback time by `delta`, returning the events that were passed in order
+    fn advance(&mut self, delta: f32) -> Vec<AnimationEvent> {
+        let duration = self.clip.duration();
+        let events = self.clip.events();
+        let mut passed = Vec::new();
+
+        // events at the start time fire when a clip is first played
+        let mut inclusive = !self.started;
+        self.started = true;
+
+        let mut from = self.time;
+        let mut to = self.time + delta;
+        loop {
+            let end = to.clamp(0., duration);
+            passed.extend(
+                events
+                    .iter()
+                    .filter(|event| match delta >= 0. {
+                        true => {
+                            (event.time > from || (inclusive && event.time == from))
+                                && event.time <= end
+                        }
+                        false => {
+                            (event.time < from || (inclusive && event.time == from))
+                                && event.time >= end
+                        }
+                    })
+                    .cloned(),
+            );
+
+            let wrapped = to > duration || to < 0.;
+            if !wrapped || !self.looping || duration <= 0. {
+                self.time = end;
+                return passed;
+            }
+
+            // wrap around and continue from the other end of the clip
+            (from, to) = match delta >= 0. {
+                true => (0., to - duration),
+                false => (duration, to + duration),
+            };
+            inclusive = true;
+        }
+    }
+}
+
+/// A crossfade from a previous clip that is being blended out
+struct Fade {
+    from: ClipState,
+    elapsed: f32,
+    duration: f32,
+}
+
+/// A pearl that plays [`AnimationClip`]s on named [`BobaTransform`] targets every [`BobaUpdate`].
+///
+/// Tracks in a clip are matched to targets by name using [`bind`](Self::bind).
+/// Targets that are destroyed are unbound automatically.
+pub struct AnimationPlayer {
+    clips: IndexMap<String, Rc<AnimationClip>>,
+    targets: IndexMap<String, Pearl<BobaTransform>>,
+    bind_pose: AnimationPose,
+    current: Option<ClipState>,
+    fade: Option<Fade>,
+    speed: f32,
+    on_event: Option<EventHandler>,
+}
+
+impl Default for AnimationPlayer {
+    fn default() -> Self {
+        Self {
+            clips: Default::default(),
+            targets: Default::default(),
+            bind_pose: Default::default(),
+            current: None,
+            fade: None,
+            speed: 1.,
+            on_event: None,
+        }
+    }
+}
+
+register_pearl_stages!(AnimationPlayer: BobaUpdate);
+
+impl PearlStage<BobaUpdate> for AnimationPlayer {
+    fn update(pearl: &Pearl<Self>, delta: &f32, resources: &mut BobaResources) -> BobaResult {
+        pearl.borrow_mut()?.advance(*delta, resources);
+        Ok(())
+    }
+}
+
+impl AnimationPlayer {
+    /// Adds `clip` to the player, so that it can be played by name
+    pub fn add_clip(&mut self, clip: AnimationClip) {
+        self.clips.insert(clip.name().into(), Rc::new(clip));
+    }
+
+    /// Binds `transform` to tracks that target `name`.
+    ///
+    /// The current local properties of `transform` are kept as its bind pose, which crossfades blend with.
+    pub fn bind(&mut self, name: &str, transform: Pearl<BobaTransform>) {
+        self.bind_pose.remove(name);
+        if let Ok(data) = transform.borrow() {
+            *self.bind_pose.target_mut(name) = TransformPose::from_transform(&data);
+        }
+        self.targets.insert(name.into(), transform);
+    }
+
+    pub fn unbind(&mut self, name: &str) -> Option<Pearl<BobaTransform>> {
+        self.bind_pose.remove(name);
+        self.targets.shift_remove(name)
+    }
+
+    /// Sets the playback speed multiplier. Negative speeds play backwards. Defaults to `1`.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Overrides whether the current clip loops
+    pub fn set_looping(&mut self, looping: bool) {
+        if let Some(current) = &mut self.current {
+            current.looping = looping;
+        }
+    }
+
+    /// Calls `handler` for every event that playback passes
+    pub fn on_event(
+        &mut self,
+        handler: impl FnMut(&AnimationEvent, &mut BobaResources) -> BobaResult + 'static,
+    ) {
+        self.on_event = Some(Box::new(handler));
+    }
+
+    /// Starts playing the clip named `name` from the beginning, stopping any other clip
+    pub fn play(&mut self, name: &str) -> Result<(), AnimationError> {
+        self.current = Some(ClipState::new(self.clip(name)?));
+        self.fade = None;
+        Ok(())
+    }
+
+    /// Starts playing the clip named `name`, blending from the current pose over `duration` seconds.
+    ///
+    /// A property that only one of the clips animates is blended with the bind pose of its target,
+    /// so properties that the new clip does not animate ease back to where they were bound.
+    pub fn crossfade(&mut self, name: &str, duration: f32) -> Result<(), AnimationError> {
+        let next = ClipState::new(self.clip(name)?);
+        self.fade = self.current.take().map(|from| Fade {
+            from,
+            elapsed: 0.,
+            duration,
+        });
+        self.current = Some(next);
+        Ok(())
+    }
+
+    /// Stops playback, leaving targets in their current pose
+    pub fn stop(&mut self) {
+        self.current = None;
+        self.fade = None;
+    }
+
+    /// The name of the clip that is playing
+    pub fn current_clip(&self) -> Option<&str> {
+        self.current.as_ref().map(|state| state.clip.name())
+    }
+
+    /// The playback time of the current clip
+    pub fn time(&self) -> Option<f32> {
+        self.current.as_ref().map(|state| state.time)
+    }
+
+    /// Returns `true` if a clip is playing and has not reached its end
+    pub fn is_playing(&self) -> bool {
+        self.current
+            .as_ref()
+            .is_some_and(|state| !state.is_finished())
+    }
+
+    /// Advances playback by `delta` seconds, fires passed events and applies the pose to all targets
+    pub fn advance(&mut self, delta: f32, resources: &mut BobaResources) {
+        let Some(current) = &mut self.current else {
+            return;
+        };
+
+        let delta = delta * self.speed;
+        let events = current.advance(delta);
+        let mut pose = current.clip.sample(current.time);
+
+        if let Some(fade) = &mut self.fade {
+            fade.from.advance(delta);
+            fade.elapsed += delta.abs();
+
+            let weight = match fade.duration > 0. {
+                true => (fade.elapsed / fade.duration).min(1.),
+                false => 1.,
+            };
+
+            let from = fade.from.clip.sample(fade.from.time);
+            pose = from.blend_with_rest(&pose, &self.bind_pose, weight);
+            if weight >= 1. {
+                self.fade = None;
+            }
+        }
+
+        pose.apply(&mut self.targets);
+        let targets = &self.targets;
+        self.bind_pose.retain(|name| targets.contains_key(name));
+
+        if let Some(handler) = &mut self.on_event {
+            for event in events.iter() {
+                if let Err(e) = handler(event, resources) {
+                    error!("There was an error in an animation event handler. Error: {e}");
+                }
+            }
+        }
+    }
+
+    fn clip(&self, name: &str) -> Result<Rc<AnimationClip>, AnimationError> {
+        match self.clips.get(name) {
+            Some(clip) => Ok(clip.clone()),
+            None => Err(AnimationError::UnknownClip(name.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use boba_core::{BobaResources, Pearl};
+    use glam::{Quat, Vec3};
+
+    use crate::{
+        animation::{AnimationClip, AnimationTrack, Interpolation, Keyframe},
+        pearls::BobaTransform,
+    };
+
+    use super::AnimationPlayer;
+
+    fn slide(name: &str, to: Vec3) -> AnimationClip {
+        AnimationClip::new(name).with_track(AnimationTrack::translation(
+            "box",
+            Interpolation::Linear,
+            vec![Keyframe::new(0., Vec3::ZERO), Keyframe::new(2., to)],
+        ))
+    }
+
+    fn position(transform: &Pearl<BobaTransform>) -> Vec3 {
+        transform.borrow().unwrap().local_position()
+    }
+
+    #[test]
+    fn play_speed_and_looping() {
+        let mut resources = BobaResources::default();
+        let target = Pearl::wrap(BobaTransform::default());
+        let mut player = AnimationPlayer::default();
+        player.add_clip(slide("slide", Vec3::X * 2.).with_looping(true));
+        player.bind("box", target.clone());
+        player.play("slide").unwrap();
+
+        player.advance(0.5, &mut resources);
+        assert!(position(&target) == Vec3::X * 0.5);
+
+        player.set_speed(2.);
+        player.advance(1., &mut resources);
+        assert!(position(&target) == Vec3::X * 0.5);
+        assert!(player.time() == Some(0.5));
+
+        player.set_looping(false);
+        player.advance(5., &mut resources);
+        assert!(position(&target) == Vec3::X * 2.);
+        assert!(!player.is_playing());
+
+        assert!(player.play("missing").is_err());
+    }
+
+    #[test]
+    fn crossfade() {
+        let mut resources = BobaResources::default();
+        let target = Pearl::wrap(BobaTransform::default());
+        let mut player = AnimationPlayer::default();
+        player.add_clip(slide("right", Vec3::X * 2.));
+        player.add_clip(slide("up", Vec3::Y * 2.));
+        player.bind("box", target.clone());
+
+        player.play("right").unwrap();
+        player.advance(1., &mut resources);
+        player.crossfade("up", 1.).unwrap();
+
+        player.advance(0.5, &mut resources);
+        let expected = Vec3::X * 1.5 * 0.5 + Vec3::Y * 0.5 * 0.5;
+        assert!(position(&target).abs_diff_eq(expected, 0.0001));
+
+        player.advance(0.5, &mut resources);
+        assert!(position(&target).abs_diff_eq(Vec3::Y, 0.0001));
+        assert!(player.current_clip() == Some("up"));
+    }
+
+    #[test]
+    fn crossfade_to_bind_pose() {
+        let mut resources = BobaResources::default();
+        let target = Pearl::wrap(BobaTransform::from_position(Vec3::Z));
+        let mut player = AnimationPlayer::default();
+        player.add_clip(slide("right", Vec3::X * 2.));
+        player.add_clip(
+            AnimationClip::new("turn").with_track(AnimationTrack::rotation(
+                "box",
+                Interpolation::Linear,
+                vec![
+                    Keyframe::new(0., Quat::IDENTITY),
+                    Keyframe::new(2., Quat::from_rotation_y(2.)),
+                ],
+            )),
+        );
+        player.bind("box", target.clone());
+
+        player.play("right").unwrap();
+        player.advance(1., &mut resources);
+        player.crossfade("turn", 1.).unwrap();
+
+        // the translation only animated by the previous clip eases back to the bind pose
+        player.advance(0.5, &mut resources);
+        let expected = Vec3::X * 1.5 * 0.5 + Vec3::Z * 0.5;
+        assert!(position(&target).abs_diff_eq(expected, 0.0001));
+        let rotation = target.borrow().unwrap().local_rotation();
+        assert!(rotation.abs_diff_eq(Quat::from_rotation_y(0.25), 0.0001));
+
+        player.advance(0.5, &mut resources);
+        assert!(position(&target).abs_diff_eq(Vec3::Z, 0.0001));
+        let rotation = target.borrow().unwrap().local_rotation();
+        assert!(rotation.abs_diff_eq(Quat::from_rotation_y(1.), 0.0001));
+    }
+
+    #[test]
+    fn events() {
+        let mut resources = BobaResources::default();
+        let fired = Rc::new(RefCell::new(Vec::new()));
+        let mut player = AnimationPlayer::default();
+        let clip = slide("slide", Vec3::X)
+            .with_looping(true)
+            .with_event(0., "start")
+            .with_event(1., "middle");
+        player.add_clip(clip);
+
+        let handler_fired = fired.clone();
+        player.on_event(move |event, _| {
+            handler_fired.borrow_mut().push(event.name.clone());
+            Ok(())
+        });
+        player.play("slide").unwrap();
+
+        player.advance(0.5, &mut resources);
+        player.advance(1., &mut resources);
+        player.advance(1., &mut resources);
+        assert!(*fired.borrow() == vec!["start", "middle", "start"]);
+
+        player.advance(2., &mut resources);
+        assert!(*fired.borrow() == vec!["start", "middle", "start", "middle", "start"]);
+    }
+
+    #[test]
+    fn unbind_destroyed_targets() {
+        let mut resources = BobaResources::default();
+        let target = Pearl::wrap(BobaTransform::default());
+        let mut player = AnimationPlayer::default();
+        player.add_clip(slide("slide", Vec3::X));
+        player.bind("box", target.clone());
+        player.play("slide").unwrap();
+
+        target.destroy().unwrap();
+        player.advance(0.5, &mut resources);
+        assert!(player.bind_pose.get("box").is_none());
+        assert!(player.unbind("box").is_none());
+    }
+}
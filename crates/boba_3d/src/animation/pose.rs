@@ -0,0 +1,155 @@
+use boba_core::{Pearl, PearlMutError};
+use glam::{Quat, Vec3};
+use indexmap::IndexMap;
+use log::warn;
+
+use crate::{pearls::BobaTransform, tween::Lerp};
+
+/// The animated local properties of a single transform. Properties that are not animated are `None`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TransformPose {
+    pub translation: Option<Vec3>,
+    pub rotation: Option<Quat>,
+    pub scale: Option<Vec3>,
+}
+
+impl TransformPose {
+    /// Captures every local property of `transform`
+    pub fn from_transform(transform: &BobaTransform) -> Self {
+        Self {
+            translation: Some(transform.local_position()),
+            rotation: Some(transform.local_rotation()),
+            scale: Some(transform.local_scale()),
+        }
+    }
+
+    /// Blends from `self` towards `other` by `weight`.
+    ///
+    /// A property that only one of the poses animates is taken from that pose.
+    pub fn blend(&self, other: &Self, weight: f32) -> Self {
+        self.blend_with_rest(other, &Self::default(), weight)
+    }
+
+    /// Blends from `self` towards `other` by `weight`.
+    ///
+    /// A property that only one of the poses animates is blended with its value in `rest` instead,
+    /// so that it eases back to rest as `other` takes over. Without a rest value it is taken from the pose that animates it.
+    pub fn blend_with_rest(&self, other: &Self, rest: &Self, weight: f32) -> Self {
+        Self {
+            translation: blend(
+                self.translation,
+                other.translation,
+                rest.translation,
+                weight,
+            ),
+            rotation: blend(self.rotation, other.rotation, rest.rotation, weight),
+            scale: blend(self.scale, other.scale, rest.scale, weight),
+        }
+    }
+
+    /// Writes the animated properties to `transform`
+    pub fn apply(&self, transform: &mut BobaTransform) {
+        let position = self.translation.unwrap_or(transform.local_position());
+        let rotation = self.rotation.unwrap_or(transform.local_rotation());
+        let scale = self.scale.unwrap_or(transform.local_scale());
+        transform.set_local(position, rotation, scale);
+    }
+}
+
+fn blend<V: Lerp + Copy>(
+    from: Option<V>,
+    to: Option<V>,
+    rest: Option<V>,
+    weight: f32,
+) -> Option<V> {
+    if from.is_none() && to.is_none() {
+        return None;
+    }
+
+    match (from.or(rest), to.or(rest)) {
+        (Some(from), Some(to)) => Some(from.lerp(&to, weight)),
+        (from, to) => to.or(from),
+    }
+}
+
+/// A sampled pose for every target of a clip, by target name.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AnimationPose {
+    targets: IndexMap<String, TransformPose>,
+}
+
+impl AnimationPose {
+    pub fn get(&self, target: &str) -> Option<&TransformPose> {
+        self.targets.get(target)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &TransformPose)> {
+        self.targets
+            .iter()
+            .map(|(name, pose)| (name.as_str(), pose))
+    }
+
+    pub(crate) fn remove(&mut self, target: &str) -> Option<TransformPose> {
+        self.targets.shift_remove(target)
+    }
+
+    /// Keeps only the targets for which `keep` returns `true`
+    pub(crate) fn retain(&mut self, mut keep: impl FnMut(&str) -> bool) {
+        self.targets.retain(|name, _| keep(name));
+    }
+
+    pub(crate) fn target_mut(&mut self, target: &str) -> &mut TransformPose {
+        if !self.targets.contains_key(target) {
+            self.targets.insert(target.into(), TransformPose::default());
+        }
+
+        self.targets.get_mut(target).unwrap()
+    }
+
+    /// Blends from `self` towards `other` by `weight`, for every target in either pose
+    pub fn blend(&self, other: &Self, weight: f32) -> Self {
+        self.blend_with_rest(other, &Self::default(), weight)
+    }
+
+    /// Blends from `self` towards `other` by `weight`, for every target in either pose.
+    ///
+    /// Properties that only one of the poses animates are blended with the matching target in `rest`,
+    /// like [`TransformPose::blend_with_rest`].
+    pub fn blend_with_rest(&self, other: &Self, rest: &Self, weight: f32) -> Self {
+        let mut blended = Self::default();
+        for name in self.targets.keys().chain(other.targets.keys()) {
+            if blended.targets.contains_key(name) {
+                continue;
+            }
+
+            let pose = |source: &Self| source.get(name).copied().unwrap_or_default();
+            let target = pose(self).blend_with_rest(&pose(other), &pose(rest), weight);
+            blended.targets.insert(name.clone(), target);
+        }
+
+        blended
+    }
+
+    /// Applies the pose to every bound target.
+    ///
+    /// Targets whose pearl was destroyed are removed from `targets`.
+    pub fn apply(&self, targets: &mut IndexMap<String, Pearl<BobaTransform>>) {
+        targets.retain(|name, transform| {
+            let Some(pose) = self.targets.get(name) else {
+                return true;
+            };
+
+            match transform.borrow_mut() {
+                Ok(mut transform) => {
+                    pose.apply(&mut transform);
+                    true
+                }
+                Err(PearlMutError::Destroyed) => false,
+                Err(e) => {
+                    warn!("Could not apply animation to '{name}'. Error: {e}");
+                    true
+                }
+            }
+        });
+    }
+}
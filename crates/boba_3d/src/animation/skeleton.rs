@@ -0,0 +1,306 @@
+use boba_core::Pearl;
+use glam::{Mat3, Mat4, Vec3};
+use log::error;
+use thiserror::Error;
+
+use crate::pearls::BobaTransform;
+
+/// The maximum number of joints that can influence a single vertex
+pub const MAX_JOINT_INFLUENCES: usize = 4;
+
+/// An error returned when creating a [`Skeleton`].
+#[derive(Debug, Error)]
+pub enum SkeletonError {
+    #[error("Skeleton has {joints} joints but {matrices} inverse bind matrices")]
+    BindCountMismatch { joints: usize, matrices: usize },
+}
+
+/// A set of [`BobaTransform`] joints used to deform a skinned mesh.
+///
+/// Each joint has an inverse bind matrix, that moves a vertex from mesh space into the space of the joint
+/// when the mesh is in its bind pose.
+pub struct Skeleton {
+    joints: Vec<Pearl<BobaTransform>>,
+    inverse_bind_matrices: Vec<Mat4>,
+}
+
+impl Skeleton {
+    /// Creates a new skeleton using the current pose of the `joints` as the bind pose.
+    ///
+    /// This assumes the mesh is at the world origin with no rotation or scale in the bind pose.
+    /// Use [`Skeleton::with_mesh_bind`] if it is not.
+    pub fn new(joints: Vec<Pearl<BobaTransform>>) -> Self {
+        Self::with_mesh_bind(joints, Mat4::IDENTITY)
+    }
+
+    /// Creates a new skeleton using the current pose of the `joints` as the bind pose,
+    /// where `mesh_world` is the world matrix of the mesh in that same pose.
+    pub fn with_mesh_bind(joints: Vec<Pearl<BobaTransform>>, mesh_world: Mat4) -> Self {
+        let inverse_bind_matrices = joints
+            .iter()
+            .map(|joint| match joint.borrow() {
+                Ok(joint) => joint.world_matrix().inverse() * mesh_world,
+                Err(e) => {
+                    error!("Could not read bind pose of joint. Identity will be used. Error: {e}");
+                    Mat4::IDENTITY
+                }
+            })
+            .collect();
+
+        Self {
+            joints,
+            inverse_bind_matrices,
+        }
+    }
+
+    /// Creates a new skeleton with a known inverse bind matrix for each joint
+    pub fn with_inverse_bind_matrices(
+        joints: Vec<Pearl<BobaTransform>>,
+        inverse_bind_matrices: Vec<Mat4>,
+    ) -> Result<Self, SkeletonError> {
+        if joints.len() != inverse_bind_matrices.len() {
+            return Err(SkeletonError::BindCountMismatch {
+                joints: joints.len(),
+                matrices: inverse_bind_matrices.len(),
+            });
+        }
+
+        Ok(Self {
+            joints,
+            inverse_bind_matrices,
+        })
+    }
+
+    pub fn joints(&self) -> &[Pearl<BobaTransform>] {
+        &self.joints
+    }
+
+    pub fn joint(&self, index: usize) -> Option<&Pearl<BobaTransform>> {
+        self.joints.get(index)
+    }
+
+    pub fn inverse_bind_matrices(&self) -> &[Mat4] {
+        &self.inverse_bind_matrices
+    }
+
+    pub fn len(&self) -> usize {
+        self.joints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.joints.is_empty()
+    }
+
+    /// Calculates the skinning matrix of every joint, relative to the mesh matrix `mesh_world`.
+    ///
+    /// If a joint cannot be read, it will stay in its bind pose.
+    pub fn joint_matrices(&self, mesh_world: Mat4) -> Vec<Mat4> {
+        let mut matrices = Vec::with_capacity(self.len());
+        self.write_joint_matrices(mesh_world, &mut matrices);
+        matrices
+    }
+
+    /// Same as [`Skeleton::joint_matrices`], but reuses the allocation of `matrices`
+    pub fn write_joint_matrices(&self, mesh_world: Mat4, matrices: &mut Vec<Mat4>) {
+        let mesh_inverse = mesh_world.inverse();
+        matrices.clear();
+        matrices.extend(self.joints.iter().zip(&self.inverse_bind_matrices).map(
+            |(joint, inverse_bind)| match joint.borrow() {
+                Ok(joint) => mesh_inverse * joint.world_matrix() * *inverse_bind,
+                Err(_) => Mat4::IDENTITY,
+            },
+        ));
+    }
+}
+
+/// Blends the joint `matrices` selected by `joints` using `weights`.
+///
+/// Weights are normalized before blending. Joints with an index out of range are ignored,
+/// and if nothing influences the vertex the identity matrix is returned.
+pub fn blend_joint_matrices(
+    joints: [u32; MAX_JOINT_INFLUENCES],
+    weights: [f32; MAX_JOINT_INFLUENCES],
+    matrices: &[Mat4],
+) -> Mat4 {
+    let mut blended = Mat4::ZERO;
+    let mut total = 0.;
+    for (joint, weight) in joints.into_iter().zip(weights) {
+        let Some(matrix) = matrices.get(joint as usize) else {
+            continue;
+        };
+
+        if weight <= 0. {
+            continue;
+        }
+
+        blended += *matrix * weight;
+        total += weight;
+    }
+
+    match total > 0. {
+        true => blended * (1. / total),
+        false => Mat4::IDENTITY,
+    }
+}
+
+/// Applies linear blend skinning to a vertex `position`
+pub fn skin_position(
+    position: Vec3,
+    joints: [u32; MAX_JOINT_INFLUENCES],
+    weights: [f32; MAX_JOINT_INFLUENCES],
+    matrices: &[Mat4],
+) -> Vec3 {
+    blend_joint_matrices(joints, weights, matrices).transform_point3(position)
+}
+
+/// Applies linear blend skinning to a vertex `normal`.
+///
+/// Normals are transformed with [`normal_matrix`], so they stay perpendicular to the surface under non uniform scale.
+pub fn skin_normal(
+    normal: Vec3,
+    joints: [u32; MAX_JOINT_INFLUENCES],
+    weights: [f32; MAX_JOINT_INFLUENCES],
+    matrices: &[Mat4],
+) -> Vec3 {
+    let skin = blend_joint_matrices(joints, weights, matrices);
+    (normal_matrix(&skin) * normal).normalize_or_zero()
+}
+
+/// The matrix that transforms normals for `matrix`.
+///
+/// This is the cofactor matrix of the upper 3x3, which is the inverse transpose scaled by the determinant.
+/// Unlike the inverse, it exists for every matrix. The sign of the determinant is removed, so mirrored matrices
+/// keep normals on the same side of the surface as the inverse transpose does.
+pub fn normal_matrix(matrix: &Mat4) -> Mat3 {
+    let x = matrix.x_axis.truncate();
+    let y = matrix.y_axis.truncate();
+    let z = matrix.z_axis.truncate();
+    let cofactor = Mat3::from_cols(y.cross(z), z.cross(x), x.cross(y));
+    cofactor * x.dot(y.cross(z)).signum()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use glam::Quat;
+
+    use crate::pearls::SetTransformParent;
+
+    use super::*;
+
+    const EPSILON: f32 = 0.0001;
+
+    fn arm() -> (Pearl<BobaTransform>, Pearl<BobaTransform>) {
+        let shoulder = Pearl::wrap(BobaTransform::from_position(Vec3::new(0., 1., 0.)));
+        let mut elbow = Pearl::wrap(BobaTransform::from_position(Vec3::new(1., 0., 0.)));
+        elbow.set_parent(shoulder.clone()).unwrap();
+        (shoulder, elbow)
+    }
+
+    #[test]
+    fn bind_pose_is_identity() {
+        let (shoulder, elbow) = arm();
+        let skeleton = Skeleton::new(vec![shoulder, elbow]);
+
+        for matrix in skeleton.joint_matrices(Mat4::IDENTITY) {
+            assert!(matrix.abs_diff_eq(Mat4::IDENTITY, EPSILON));
+        }
+    }
+
+    #[test]
+    fn rigid_joint() {
+        let (shoulder, elbow) = arm();
+        let skeleton = Skeleton::new(vec![shoulder.clone(), elbow.clone()]);
+
+        // rotating the shoulder carries the elbow with it
+        shoulder
+            .borrow_mut()
+            .unwrap()
+            .set_local_rotation(Quat::from_rotation_z(FRAC_PI_2));
+        let matrices = skeleton.joint_matrices(Mat4::IDENTITY);
+
+        let tip = Vec3::new(2., 1., 0.);
+        let skinned = skin_position(tip, [1, 0, 0, 0], [1., 0., 0., 0.], &matrices);
+        assert!(skinned.abs_diff_eq(Vec3::new(0., 3., 0.), EPSILON));
+
+        let normal = skin_normal(Vec3::Y, [1, 0, 0, 0], [1., 0., 0., 0.], &matrices);
+        assert!(normal.abs_diff_eq(Vec3::NEG_X, EPSILON));
+    }
+
+    #[test]
+    fn blended_weights() {
+        let matrices = [
+            Mat4::IDENTITY,
+            Mat4::from_translation(Vec3::new(0., 2., 0.)),
+        ];
+
+        let skinned = skin_position(Vec3::ZERO, [0, 1, 0, 0], [0.5, 0.5, 0., 0.], &matrices);
+        assert!(skinned.abs_diff_eq(Vec3::new(0., 1., 0.), EPSILON));
+
+        // weights are normalized
+        let skinned = skin_position(Vec3::ZERO, [0, 1, 0, 0], [1., 3., 0., 0.], &matrices);
+        assert!(skinned.abs_diff_eq(Vec3::new(0., 1.5, 0.), EPSILON));
+
+        // no influence and invalid joints leave the vertex in place
+        let skinned = skin_position(Vec3::ONE, [0; 4], [0.; 4], &matrices);
+        assert!(skinned.abs_diff_eq(Vec3::ONE, EPSILON));
+        let skinned = skin_position(Vec3::ONE, [7, 0, 0, 0], [1., 0., 0., 0.], &matrices);
+        assert!(skinned.abs_diff_eq(Vec3::ONE, EPSILON));
+    }
+
+    #[test]
+    fn relative_to_mesh() {
+        let (shoulder, elbow) = arm();
+        let skeleton = Skeleton::new(vec![shoulder.clone(), elbow]);
+        let mesh_world = Mat4::from_translation(Vec3::new(5., 0., 0.));
+
+        // moving the mesh and the skeleton together does not deform the mesh
+        shoulder
+            .borrow_mut()
+            .unwrap()
+            .translate(Vec3::new(5., 0., 0.));
+        for matrix in skeleton.joint_matrices(mesh_world) {
+            assert!(matrix.abs_diff_eq(Mat4::IDENTITY, EPSILON));
+        }
+    }
+
+    #[test]
+    fn mesh_bind() {
+        let (shoulder, elbow) = arm();
+        let mesh_world = Mat4::from_translation(Vec3::new(0., 0., 3.));
+        let skeleton = Skeleton::with_mesh_bind(vec![shoulder, elbow], mesh_world);
+
+        for matrix in skeleton.joint_matrices(mesh_world) {
+            assert!(matrix.abs_diff_eq(Mat4::IDENTITY, EPSILON));
+        }
+    }
+
+    #[test]
+    fn scaled_normals() {
+        // a slope stretched along x gets flatter, so its normal tilts towards y
+        let matrices = [Mat4::from_scale(Vec3::new(2., 1., 1.))];
+        let normal = Vec3::new(1., 1., 0.).normalize();
+        let skinned = skin_normal(normal, [0; 4], [1., 0., 0., 0.], &matrices);
+        let expected = Vec3::new(1., 2., 0.).normalize();
+        assert!(skinned.abs_diff_eq(expected, EPSILON), "{skinned}");
+
+        // mirroring keeps the normal matching the inverse transpose
+        let mirror = Mat4::from_scale(Vec3::new(-1., 1., 1.));
+        let skinned = skin_normal(Vec3::X, [0; 4], [1., 0., 0., 0.], &[mirror]);
+        assert!(skinned.abs_diff_eq(Vec3::NEG_X, EPSILON), "{skinned}");
+    }
+
+    #[test]
+    fn bind_count_mismatch() {
+        let (shoulder, elbow) = arm();
+        let result = Skeleton::with_inverse_bind_matrices(vec![shoulder, elbow], vec![]);
+        assert!(matches!(
+            result,
+            Err(SkeletonError::BindCountMismatch {
+                joints: 2,
+                matrices: 0
+            })
+        ));
+    }
+}
@@ -0,0 +1,175 @@
+use boba_core::{BobaCommand, BobaResources, BobaResult, Pearl};
+use glam::{Quat, Vec3};
+
+use crate::pearls::BobaTransform;
+
+/// An undoable command that sets the local position of a [`BobaTransform`].
+///
+/// Consecutive commands on the same transform merge when executed in a group.
+pub struct SetLocalPosition {
+    transform: Pearl<BobaTransform>,
+    position: Vec3,
+    previous: Vec3,
+}
+
+impl SetLocalPosition {
+    pub fn new(transform: Pearl<BobaTransform>, position: Vec3) -> Self {
+        Self {
+            transform,
+            position,
+            previous: Vec3::ZERO,
+        }
+    }
+}
+
+impl BobaCommand for SetLocalPosition {
+    fn apply(&mut self, _: &BobaResources) -> BobaResult {
+        let mut transform = self.transform.borrow_mut()?;
+        self.previous = transform.local_position();
+        transform.set_local_position(self.position);
+        Ok(())
+    }
+
+    fn revert(&mut self, _: &BobaResources) -> BobaResult {
+        self.transform
+            .borrow_mut()?
+            .set_local_position(self.previous);
+        Ok(())
+    }
+
+    fn merge(&mut self, next: &Self) -> bool {
+        if self.transform != next.transform {
+            return false;
+        }
+
+        self.position = next.position;
+        true
+    }
+}
+
+/// An undoable command that sets the local rotation of a [`BobaTransform`].
+///
+/// Consecutive commands on the same transform merge when executed in a group.
+pub struct SetLocalRotation {
+    transform: Pearl<BobaTransform>,
+    rotation: Quat,
+    previous: Quat,
+}
+
+impl SetLocalRotation {
+    pub fn new(transform: Pearl<BobaTransform>, rotation: Quat) -> Self {
+        Self {
+            transform,
+            rotation,
+            previous: Quat::IDENTITY,
+        }
+    }
+}
+
+impl BobaCommand for SetLocalRotation {
+    fn apply(&mut self, _: &BobaResources) -> BobaResult {
+        let mut transform = self.transform.borrow_mut()?;
+        self.previous = transform.local_rotation();
+        transform.set_local_rotation(self.rotation);
+        Ok(())
+    }
+
+    fn revert(&mut self, _: &BobaResources) -> BobaResult {
+        self.transform
+            .borrow_mut()?
+            .set_local_rotation(self.previous);
+        Ok(())
+    }
+
+    fn merge(&mut self, next: &Self) -> bool {
+        if self.transform != next.transform {
+            return false;
+        }
+
+        self.rotation = next.rotation;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boba_core::{BobaResources, CommandHistory, Pearl};
+    use glam::{Quat, Vec3};
+
+    use crate::pearls::{BobaTransform, SetTransformParent};
+
+    use super::{SetLocalPosition, SetLocalRotation};
+
+    #[test]
+    fn undo_redo_position() {
+        let resources = BobaResources::default();
+        let mut history = CommandHistory::default();
+        let transform = Pearl::wrap(BobaTransform::from_position(Vec3::X));
+
+        let command = SetLocalPosition::new(transform.clone(), Vec3::Y);
+        history.execute(command, &resources).unwrap();
+        assert!(transform.borrow().unwrap().local_position() == Vec3::Y);
+
+        history.undo(&resources).unwrap();
+        assert!(transform.borrow().unwrap().local_position() == Vec3::X);
+
+        history.redo(&resources).unwrap();
+        assert!(transform.borrow().unwrap().local_position() == Vec3::Y);
+    }
+
+    #[test]
+    fn undo_child_world_position() {
+        let resources = BobaResources::default();
+        let mut history = CommandHistory::default();
+        let parent = Pearl::wrap(BobaTransform::from_position(Vec3::ZERO));
+        let mut child = Pearl::wrap(BobaTransform::from_position(Vec3::X));
+        child.set_parent(parent.clone()).unwrap();
+
+        let command = SetLocalPosition::new(parent.clone(), Vec3::Z);
+        history.execute(command, &resources).unwrap();
+        assert!(child.borrow().unwrap().world_position() == Vec3::X + Vec3::Z);
+
+        history.undo(&resources).unwrap();
+        assert!(child.borrow().unwrap().world_position() == Vec3::X);
+    }
+
+    #[test]
+    fn merge_drag() {
+        let resources = BobaResources::default();
+        let mut history = CommandHistory::default();
+        let transform = Pearl::wrap(BobaTransform::from_position(Vec3::ZERO));
+        let other = Pearl::wrap(BobaTransform::from_position(Vec3::ZERO));
+
+        history.begin_group();
+        for i in 1..=10 {
+            let position = Vec3::X * i as f32;
+            let command = SetLocalPosition::new(transform.clone(), position);
+            history.execute(command, &resources).unwrap();
+        }
+        let command = SetLocalRotation::new(other.clone(), Quat::from_rotation_y(1.));
+        history.execute(command, &resources).unwrap();
+        history.end_group();
+
+        assert!(history.undo_len() == 1);
+        assert!(transform.borrow().unwrap().local_position() == Vec3::X * 10.);
+
+        history.undo(&resources).unwrap();
+        assert!(transform.borrow().unwrap().local_position() == Vec3::ZERO);
+        assert!(other.borrow().unwrap().local_rotation() == Quat::IDENTITY);
+    }
+
+    #[test]
+    fn history_limit() {
+        let resources = BobaResources::default();
+        let mut history = CommandHistory::new(2);
+        let transform = Pearl::wrap(BobaTransform::from_position(Vec3::ZERO));
+
+        for i in 1..=4 {
+            let command = SetLocalPosition::new(transform.clone(), Vec3::Y * i as f32);
+            history.execute(command, &resources).unwrap();
+        }
+
+        while history.undo(&resources).unwrap() {}
+        assert!(transform.borrow().unwrap().local_position() == Vec3::Y * 2.);
+    }
+}
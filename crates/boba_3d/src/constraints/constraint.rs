@@ -0,0 +1,418 @@
+use boba_core::{
+    register_pearl_stages, BobaResources, BobaResult, Pearl, PearlError, PearlMutError, PearlStage,
+};
+use glam::{BVec3, Quat, Vec3};
+use log::warn;
+use thiserror::Error;
+
+use crate::pearls::{look_rotation, BobaTransform};
+
+use super::OnConstraintUpdate;
+
+/// The world space channels that a copy constraint writes to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstraintChannels {
+    /// The position axes to copy
+    pub position: BVec3,
+    pub rotation: bool,
+    /// The scale axes to copy
+    pub scale: BVec3,
+}
+
+impl Default for ConstraintChannels {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl ConstraintChannels {
+    pub const ALL: Self = Self {
+        position: BVec3::TRUE,
+        rotation: true,
+        scale: BVec3::TRUE,
+    };
+
+    pub const POSITION: Self = Self {
+        position: BVec3::TRUE,
+        rotation: false,
+        scale: BVec3::FALSE,
+    };
+
+    pub const ROTATION: Self = Self {
+        position: BVec3::FALSE,
+        rotation: true,
+        scale: BVec3::FALSE,
+    };
+
+    pub const SCALE: Self = Self {
+        position: BVec3::FALSE,
+        rotation: false,
+        scale: BVec3::TRUE,
+    };
+
+    pub const POSITION_ROTATION: Self = Self {
+        position: BVec3::TRUE,
+        rotation: true,
+        scale: BVec3::FALSE,
+    };
+}
+
+/// An error returned when a [`TransformConstraint`] could not access one of its transforms.
+#[derive(Debug, Error)]
+pub enum ConstraintError {
+    #[error("Could not read the source transform. Error: {0}")]
+    SourceError(#[from] PearlError),
+    #[error("Could not move the constrained transform. Error: {0}")]
+    TransformError(#[from] PearlMutError),
+}
+
+/// How a [`TransformConstraint`] moves its transform relative to the source.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConstraintKind {
+    /// Points the forward axis at the source, keeping the up axis close to `up`
+    LookAt { up: Vec3 },
+    /// Points the local `aim_axis` at the source, keeping the local `up_axis` close to the world `up`
+    Aim {
+        aim_axis: Vec3,
+        up_axis: Vec3,
+        up: Vec3,
+    },
+    /// Copies the selected world space channels of the source
+    CopyTransform { channels: ConstraintChannels },
+}
+
+/// A pearl that keeps a transform looking at, aiming at, or copying a source transform.
+///
+/// Constraints are applied in [`OnConstraintUpdate`].
+/// With a weight below `1`, the result is blended with the current world values of the transform,
+/// which are usually set by gameplay, animation or physics earlier in the frame.
+///
+/// The source is read before the transform is moved, so a source that is a child of the transform lags one frame behind.
+pub struct TransformConstraint {
+    transform: Pearl<BobaTransform>,
+    source: Pearl<BobaTransform>,
+    pub kind: ConstraintKind,
+    /// How strongly the constraint is applied, from `0` to `1`
+    pub weight: f32,
+    /// A point in the local space of the source that is looked at, aimed at, or copied instead of its origin
+    pub offset: Vec3,
+    /// A rotation applied after the constrained rotation
+    pub rotation_offset: Quat,
+}
+
+register_pearl_stages!(TransformConstraint: OnConstraintUpdate);
+
+impl PearlStage<OnConstraintUpdate> for TransformConstraint {
+    fn update(pearl: &Pearl<Self>, _: &(), _: &mut BobaResources) -> BobaResult {
+        match pearl.borrow()?.apply() {
+            Ok(())
+            | Err(ConstraintError::SourceError(PearlError::Destroyed))
+            | Err(ConstraintError::TransformError(PearlMutError::Destroyed)) => (),
+            Err(e) => {
+                warn!("Skipping constraint, because a transform could not be borrowed. Error: {e}")
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TransformConstraint {
+    pub fn new(
+        transform: Pearl<BobaTransform>,
+        source: Pearl<BobaTransform>,
+        kind: ConstraintKind,
+    ) -> Self {
+        Self {
+            transform,
+            source,
+            kind,
+            weight: 1.,
+            offset: Vec3::ZERO,
+            rotation_offset: Quat::IDENTITY,
+        }
+    }
+
+    /// Creates a constraint that keeps the forward axis of `transform` pointing at `source`
+    pub fn look_at(
+        transform: Pearl<BobaTransform>,
+        source: Pearl<BobaTransform>,
+        up: Vec3,
+    ) -> Self {
+        Self::new(transform, source, ConstraintKind::LookAt { up })
+    }
+
+    /// Creates a constraint that keeps the local `aim_axis` of `transform` pointing at `source`,
+    /// with its local `+Y` axis kept close to the world `+Y` axis
+    pub fn aim(
+        transform: Pearl<BobaTransform>,
+        source: Pearl<BobaTransform>,
+        aim_axis: Vec3,
+    ) -> Self {
+        let kind = ConstraintKind::Aim {
+            aim_axis,
+            up_axis: Vec3::Y,
+            up: Vec3::Y,
+        };
+        Self::new(transform, source, kind)
+    }
+
+    /// Creates a constraint that copies the selected `channels` of `source` onto `transform`
+    pub fn copy(
+        transform: Pearl<BobaTransform>,
+        source: Pearl<BobaTransform>,
+        channels: ConstraintChannels,
+    ) -> Self {
+        Self::new(
+            transform,
+            source,
+            ConstraintKind::CopyTransform { channels },
+        )
+    }
+
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn with_offset(mut self, offset: Vec3) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_rotation_offset(mut self, rotation: Quat) -> Self {
+        self.rotation_offset = rotation;
+        self
+    }
+
+    /// The transform that is moved by the constraint
+    pub fn transform(&self) -> &Pearl<BobaTransform> {
+        &self.transform
+    }
+
+    /// The transform that is followed by the constraint
+    pub fn source(&self) -> &Pearl<BobaTransform> {
+        &self.source
+    }
+
+    /// Moves the transform to match the source
+    pub fn apply(&self) -> Result<(), ConstraintError> {
+        let weight = self.weight.clamp(0., 1.);
+        if weight == 0. {
+            return Ok(());
+        }
+
+        let source = self.source.borrow()?;
+        let source_point = source.transform_point(self.offset);
+        let source_rotation = source.world_rotation();
+        let source_scale = source.lossy_scale();
+        drop(source);
+
+        let mut transform = self.transform.borrow_mut()?;
+        let position = transform.world_position();
+        let (aim_axis, up_axis, up) = match self.kind {
+            ConstraintKind::LookAt { up } => (Vec3::Z, Vec3::Y, up),
+            ConstraintKind::Aim {
+                aim_axis,
+                up_axis,
+                up,
+            } => (aim_axis, up_axis, up),
+            ConstraintKind::CopyTransform { channels } => {
+                if channels.position.any() {
+                    let copied = Vec3::select(channels.position, source_point, position);
+                    transform.set_world_position(position.lerp(copied, weight));
+                }
+                if channels.rotation {
+                    let copied = source_rotation * self.rotation_offset;
+                    let rotation = transform.world_rotation().slerp(copied, weight);
+                    transform.set_world_rotation(rotation);
+                }
+                if channels.scale.any() {
+                    let scale = transform.lossy_scale();
+                    let copied = Vec3::select(channels.scale, source_scale, scale);
+                    transform.set_world_scale(scale.lerp(copied, weight));
+                }
+                return Ok(());
+            }
+        };
+
+        let Some(rotation) = aim_rotation(source_point - position, up, aim_axis, up_axis) else {
+            return Ok(());
+        };
+
+        let rotation = rotation * self.rotation_offset;
+        let rotation = transform.world_rotation().slerp(rotation, weight);
+        transform.set_world_rotation(rotation);
+        Ok(())
+    }
+}
+
+/// Creates a rotation that points the local `aim_axis` along `direction`, keeping the local `up_axis` close to `up`.
+///
+/// Returns `None` if `direction` or `aim_axis` is zero.
+fn aim_rotation(direction: Vec3, up: Vec3, aim_axis: Vec3, up_axis: Vec3) -> Option<Quat> {
+    // the rotation from a local frame where the aim axis is forward and the up axis is up
+    let local = look_rotation(aim_axis, up_axis)?;
+    let world = look_rotation(direction, up)?;
+    Some((world * local.inverse()).normalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use boba_core::{BobaStage, PearlRegistry};
+    use glam::Mat3;
+
+    use crate::pearls::SetTransformParent;
+
+    use super::*;
+
+    const EPSILON: f32 = 0.001;
+
+    fn assert_vec(a: Vec3, b: Vec3) {
+        assert!(a.abs_diff_eq(b, EPSILON), "{a} != {b}");
+    }
+
+    fn pair(source: Vec3) -> (Pearl<BobaTransform>, Pearl<BobaTransform>) {
+        (
+            Pearl::wrap(BobaTransform::default()),
+            Pearl::wrap(BobaTransform::from_position(source)),
+        )
+    }
+
+    #[test]
+    fn look_at_in_stage() {
+        let (transform, source) = pair(Vec3::new(0., 0., -5.));
+        let mut registry = PearlRegistry::default();
+        registry.add(Pearl::wrap(TransformConstraint::look_at(
+            transform.clone(),
+            source.clone(),
+            Vec3::Y,
+        )));
+
+        let mut resources = BobaResources::default();
+        OnConstraintUpdate
+            .run(&mut registry, &mut resources)
+            .unwrap();
+        assert_vec(transform.borrow().unwrap().forward(), -Vec3::Z);
+        assert_vec(transform.borrow().unwrap().up(), Vec3::Y);
+
+        // the constraint keeps following the source
+        source
+            .borrow_mut()
+            .unwrap()
+            .set_world_position(Vec3::new(3., 0., 0.));
+        OnConstraintUpdate
+            .run(&mut registry, &mut resources)
+            .unwrap();
+        assert_vec(transform.borrow().unwrap().forward(), Vec3::X);
+    }
+
+    #[test]
+    fn degenerate_look_at() {
+        // looking straight up uses any perpendicular up axis
+        let (transform, source) = pair(Vec3::Y * 2.);
+        let constraint = TransformConstraint::look_at(transform.clone(), source, Vec3::Y);
+        constraint.apply().unwrap();
+        let rotation = transform.borrow().unwrap().world_rotation();
+        assert_vec(rotation * Vec3::Z, Vec3::Y);
+        assert!(rotation.is_normalized());
+
+        // a source on top of the transform leaves its rotation alone
+        let rotation = Quat::from_rotation_x(0.5);
+        let transform = Pearl::wrap(BobaTransform::from_position_rotation(Vec3::ONE, rotation));
+        let source = Pearl::wrap(BobaTransform::from_position(Vec3::ONE));
+        TransformConstraint::look_at(transform.clone(), source, Vec3::Y)
+            .apply()
+            .unwrap();
+        assert!(transform
+            .borrow()
+            .unwrap()
+            .world_rotation()
+            .abs_diff_eq(rotation, EPSILON));
+    }
+
+    #[test]
+    fn aim_with_offsets() {
+        let (transform, source) = pair(Vec3::new(0., 0., 4.));
+        source
+            .borrow_mut()
+            .unwrap()
+            .set_local_rotation(Quat::from_rotation_y(FRAC_PI_2));
+
+        // the offset is in the space of the source, so it moves along the rotated source
+        let constraint = TransformConstraint::aim(transform.clone(), source, Vec3::X)
+            .with_offset(Vec3::new(0., 0., -4.));
+        constraint.apply().unwrap();
+
+        let data = transform.borrow().unwrap();
+        let aimed = data.world_rotation() * Vec3::X;
+        assert_vec(aimed, Vec3::new(-1., 0., 1.).normalize());
+        assert_vec(data.up(), Vec3::Y);
+    }
+
+    #[test]
+    fn copy_channels_and_weight() {
+        let source = Pearl::wrap(BobaTransform::new(
+            Vec3::new(2., 4., 6.),
+            Quat::from_rotation_z(1.),
+            Vec3::splat(3.),
+        ));
+
+        let transform = Pearl::wrap(BobaTransform::default());
+        let channels = ConstraintChannels {
+            position: BVec3::new(true, false, true),
+            rotation: false,
+            scale: BVec3::new(false, true, false),
+        };
+        TransformConstraint::copy(transform.clone(), source.clone(), channels)
+            .apply()
+            .unwrap();
+        let data = transform.borrow().unwrap();
+        assert_vec(data.world_position(), Vec3::new(2., 0., 6.));
+        assert_vec(data.lossy_scale(), Vec3::new(1., 3., 1.));
+        assert!(data.world_rotation().abs_diff_eq(Quat::IDENTITY, EPSILON));
+        drop(data);
+
+        let transform = Pearl::wrap(BobaTransform::default());
+        TransformConstraint::copy(transform.clone(), source, ConstraintChannels::ALL)
+            .with_weight(0.5)
+            .apply()
+            .unwrap();
+        let data = transform.borrow().unwrap();
+        assert_vec(data.world_position(), Vec3::new(1., 2., 3.));
+        assert_vec(data.lossy_scale(), Vec3::splat(2.));
+        assert!(data
+            .world_rotation()
+            .abs_diff_eq(Quat::from_rotation_z(0.5), EPSILON));
+    }
+
+    #[test]
+    fn copy_into_parented_transform() {
+        let parent = Pearl::wrap(BobaTransform::new(
+            Vec3::new(0., 10., 0.),
+            Quat::from_rotation_y(FRAC_PI_2),
+            Vec3::ONE,
+        ));
+        let mut transform = Pearl::wrap(BobaTransform::default());
+        transform.set_parent(parent.clone()).unwrap();
+
+        let source = Pearl::wrap(BobaTransform::from_position_rotation(
+            Vec3::new(1., 2., 3.),
+            Quat::from_rotation_x(0.3),
+        ));
+        TransformConstraint::copy(
+            transform.clone(),
+            source,
+            ConstraintChannels::POSITION_ROTATION,
+        )
+        .apply()
+        .unwrap();
+
+        let data = transform.borrow().unwrap();
+        assert_vec(data.world_position(), Vec3::new(1., 2., 3.));
+        let expected = Mat3::from_quat(Quat::from_rotation_x(0.3));
+        let actual = Mat3::from_quat(data.world_rotation());
+        assert!(actual.abs_diff_eq(expected, EPSILON));
+    }
+}
@@ -0,0 +1,5 @@
+mod constraint;
+mod stage;
+
+pub use constraint::*;
+pub use stage::*;
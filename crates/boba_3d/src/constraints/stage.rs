@@ -0,0 +1,18 @@
+use boba_core::{BobaResources, BobaResult, BobaStage, PearlRegistry};
+
+/// Runs [`TransformConstraint`](super::TransformConstraint) pearls.
+///
+/// Constraints should see the final positions of the frame,
+/// so `MilkTeaApp` runs this stage after [`BobaLateUpdate`](boba_core::stages::BobaLateUpdate),
+/// which comes after the gameplay and physics stages.
+#[derive(Default)]
+pub struct OnConstraintUpdate;
+
+impl BobaStage for OnConstraintUpdate {
+    type Data = ();
+
+    fn run(&mut self, registry: &mut PearlRegistry, resources: &mut BobaResources) -> BobaResult {
+        registry.run_stage::<OnConstraintUpdate>(&(), resources);
+        Ok(())
+    }
+}
@@ -0,0 +1,240 @@
+use glam::{Mat4, Vec3};
+use serde::{Deserialize, Serialize};
+
+use super::BoundingSphere;
+
+/// An axis aligned bounding box
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Default for Aabb {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl Aabb {
+    /// An empty box at the origin
+    pub const ZERO: Self = Self {
+        min: Vec3::ZERO,
+        max: Vec3::ZERO,
+    };
+
+    /// Creates a box that spans between two corners, in any order
+    pub fn new(a: Vec3, b: Vec3) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    pub fn from_center_half_extents(center: Vec3, half_extents: Vec3) -> Self {
+        let half_extents = half_extents.abs();
+        Self {
+            min: center - half_extents,
+            max: center + half_extents,
+        }
+    }
+
+    /// Creates the smallest box that contains all `points`.
+    ///
+    /// Returns `None` if there are no points.
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Self::new(first, first), |aabb, point| {
+            aabb.union_point(point)
+        }))
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    pub fn size(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    pub fn volume(&self) -> f32 {
+        let size = self.size();
+        size.x * size.y * size.z
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        let size = self.size();
+        2. * (size.x * size.y + size.y * size.z + size.z * size.x)
+    }
+
+    /// Gets the 8 corners of the box
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (min, max) = (self.min, self.max);
+        [
+            Vec3::new(min.x, min.y, min.z),
+            Vec3::new(max.x, min.y, min.z),
+            Vec3::new(min.x, max.y, min.z),
+            Vec3::new(max.x, max.y, min.z),
+            Vec3::new(min.x, min.y, max.z),
+            Vec3::new(max.x, min.y, max.z),
+            Vec3::new(min.x, max.y, max.z),
+            Vec3::new(max.x, max.y, max.z),
+        ]
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    /// Checks if `other` is completely inside this box
+    pub fn contains(&self, other: &Aabb) -> bool {
+        other.min.cmpge(self.min).all() && other.max.cmple(self.max).all()
+    }
+
+    /// Checks if this box and `other` overlap. Boxes that only touch are also overlapping.
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.cmple(other.max).all() && self.max.cmpge(other.min).all()
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.distance_squared_to_point(sphere.center) <= sphere.radius * sphere.radius
+    }
+
+    /// Gets the point inside the box that is closest to `point`
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        point.clamp(self.min, self.max)
+    }
+
+    /// Gets the squared distance from the box to `point`, which is zero for points inside the box
+    pub fn distance_squared_to_point(&self, point: Vec3) -> f32 {
+        self.closest_point(point).distance_squared(point)
+    }
+
+    /// Creates the smallest box that contains both this box and `other`
+    pub fn union(&self, other: &Aabb) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// Creates the smallest box that contains both this box and `point`
+    pub fn union_point(&self, point: Vec3) -> Self {
+        Self {
+            min: self.min.min(point),
+            max: self.max.max(point),
+        }
+    }
+
+    /// Gets the overlapping region of this box and `other`, if they overlap
+    pub fn intersection(&self, other: &Aabb) -> Option<Self> {
+        match self.intersects(other) {
+            false => None,
+            true => Some(Self {
+                min: self.min.max(other.min),
+                max: self.max.min(other.max),
+            }),
+        }
+    }
+
+    /// Grows the box by `margin` on every side
+    pub fn grow(&self, margin: f32) -> Self {
+        Self::from_center_half_extents(self.center(), self.half_extents() + Vec3::splat(margin))
+    }
+
+    /// Transforms the box by `matrix`, returning a new box that contains the transformed box.
+    ///
+    /// Rotated boxes become larger, so the result is not always the tightest box around the original contents.
+    pub fn transformed(&self, matrix: &Mat4) -> Self {
+        let center = matrix.transform_point3(self.center());
+        let half_extents = self.half_extents();
+        let extents = matrix.x_axis.truncate().abs() * half_extents.x
+            + matrix.y_axis.truncate().abs() * half_extents.y
+            + matrix.z_axis.truncate().abs() * half_extents.z;
+        Self::from_center_half_extents(center, extents)
+    }
+
+    /// Gets the smallest sphere that contains this box
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        BoundingSphere::new(self.center(), self.half_extents().length())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use glam::Quat;
+
+    use super::*;
+
+    const EPSILON: f32 = 0.0001;
+
+    #[test]
+    fn from_points() {
+        assert!(Aabb::from_points([]).is_none());
+
+        let aabb = Aabb::from_points([
+            Vec3::new(1., -2., 3.),
+            Vec3::new(-1., 4., 0.),
+            Vec3::new(0., 0., 5.),
+        ])
+        .unwrap();
+        assert_eq!(aabb.min, Vec3::new(-1., -2., 0.));
+        assert_eq!(aabb.max, Vec3::new(1., 4., 5.));
+        assert_eq!(aabb.center(), Vec3::new(0., 1., 2.5));
+    }
+
+    #[test]
+    fn intersections() {
+        let a = Aabb::new(Vec3::ZERO, Vec3::ONE * 2.);
+        let b = Aabb::new(Vec3::ONE, Vec3::ONE * 3.);
+        let c = Aabb::new(Vec3::ONE * 5., Vec3::ONE * 6.);
+
+        assert!(a.intersects(&b));
+        assert!(!a.intersects(&c));
+        assert_eq!(
+            a.intersection(&b),
+            Some(Aabb::new(Vec3::ONE, Vec3::ONE * 2.))
+        );
+        assert_eq!(a.intersection(&c), None);
+        assert_eq!(a.union(&c), Aabb::new(Vec3::ZERO, Vec3::ONE * 6.));
+
+        assert!(a.contains_point(Vec3::ONE));
+        assert!(!a.contains_point(Vec3::ONE * 3.));
+        assert!(a.union(&b).contains(&b));
+        assert!(!a.contains(&b));
+
+        let sphere = BoundingSphere::new(Vec3::new(3., 1., 1.), 1.);
+        assert!(a.intersects_sphere(&sphere));
+        let sphere = BoundingSphere::new(Vec3::new(3.1, 1., 1.), 1.);
+        assert!(!a.intersects_sphere(&sphere));
+    }
+
+    #[test]
+    fn transformed() {
+        let aabb = Aabb::new(-Vec3::ONE, Vec3::ONE);
+
+        let matrix = Mat4::from_scale_rotation_translation(
+            Vec3::splat(2.),
+            Quat::IDENTITY,
+            Vec3::new(10., 0., 0.),
+        );
+        let world = aabb.transformed(&matrix);
+        assert!(world.min.abs_diff_eq(Vec3::new(8., -2., -2.), EPSILON));
+        assert!(world.max.abs_diff_eq(Vec3::new(12., 2., 2.), EPSILON));
+
+        // a rotated box grows to contain all of its corners
+        let matrix = Mat4::from_rotation_y(FRAC_PI_4);
+        let world = aabb.transformed(&matrix);
+        let expected =
+            Aabb::from_points(aabb.corners().map(|c| matrix.transform_point3(c))).unwrap();
+        assert!(world.min.abs_diff_eq(expected.min, EPSILON));
+        assert!(world.max.abs_diff_eq(expected.max, EPSILON));
+    }
+}
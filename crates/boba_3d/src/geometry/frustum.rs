@@ -0,0 +1,92 @@
+use glam::{Mat4, Vec3};
+use serde::{Deserialize, Serialize};
+
+use super::{Aabb, BoundingSphere, Plane};
+
+/// The volume visible to a camera, made of six planes facing inwards
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Frustum {
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    /// Extracts the frustum from a `projection * view` matrix.
+    ///
+    /// Expects clip space depth to go from 0 to 1, like [`Mat4::perspective_rh`] and wgpu do.
+    /// The planes are in the order left, right, bottom, top, near, far.
+    pub fn from_matrix(matrix: &Mat4) -> Self {
+        let rows = matrix.transpose();
+        let (x, y, z, w) = (rows.x_axis, rows.y_axis, rows.z_axis, rows.w_axis);
+        Self {
+            planes: [
+                Plane::from_coefficients(w + x),
+                Plane::from_coefficients(w - x),
+                Plane::from_coefficients(w + y),
+                Plane::from_coefficients(w - y),
+                Plane::from_coefficients(z),
+                Plane::from_coefficients(w - z),
+            ],
+        }
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(point) >= 0.)
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(sphere.center) >= -sphere.radius)
+    }
+
+    /// Checks if `aabb` is at least partly inside the frustum.
+    ///
+    /// This is conservative, so some large boxes near the corners of the frustum are reported as inside.
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            // the corner of the box that is furthest along the plane normal
+            let corner = Vec3::select(plane.normal.cmpge(Vec3::ZERO), aabb.max, aabb.min);
+            plane.signed_distance(corner) >= 0.
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera() -> Frustum {
+        // a camera at the origin looking down +Z
+        let view = Mat4::look_at_rh(Vec3::ZERO, Vec3::Z, Vec3::Y);
+        let proj = Mat4::perspective_rh(90f32.to_radians(), 1., 0.1, 100.);
+        Frustum::from_matrix(&(proj * view))
+    }
+
+    #[test]
+    fn points() {
+        let frustum = camera();
+        assert!(frustum.contains_point(Vec3::new(0., 0., 10.)));
+        assert!(frustum.contains_point(Vec3::new(9., -9., 10.)));
+        assert!(!frustum.contains_point(Vec3::new(11., 0., 10.)));
+        assert!(!frustum.contains_point(Vec3::new(0., 0., -10.)));
+        assert!(!frustum.contains_point(Vec3::new(0., 0., 0.05)));
+        assert!(!frustum.contains_point(Vec3::new(0., 0., 101.)));
+    }
+
+    #[test]
+    fn volumes() {
+        let frustum = camera();
+
+        let aabb = Aabb::from_center_half_extents(Vec3::new(12., 0., 10.), Vec3::splat(1.5));
+        assert!(frustum.intersects_aabb(&aabb));
+        let aabb = Aabb::from_center_half_extents(Vec3::new(0., 0., -10.), Vec3::ONE);
+        assert!(!frustum.intersects_aabb(&aabb));
+
+        let sphere = BoundingSphere::new(Vec3::new(0., 12., 10.), 2.);
+        assert!(frustum.intersects_sphere(&sphere));
+        let sphere = BoundingSphere::new(Vec3::new(0., 15., 10.), 2.);
+        assert!(!frustum.intersects_sphere(&sphere));
+    }
+}
@@ -0,0 +1,44 @@
+use glam::{Vec3, Vec4};
+use serde::{Deserialize, Serialize};
+
+/// An infinite plane of points `p` where `normal.dot(p) + distance == 0`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Plane {
+    pub normal: Vec3,
+    pub distance: f32,
+}
+
+impl Plane {
+    /// Creates a plane facing `normal` that goes through `point`
+    pub fn from_point_normal(point: Vec3, normal: Vec3) -> Self {
+        let normal = normal.normalize_or_zero();
+        Self {
+            normal,
+            distance: -normal.dot(point),
+        }
+    }
+
+    /// Creates a plane from the coefficients `ax + by + cz + d = 0`, normalizing them
+    pub fn from_coefficients(coefficients: Vec4) -> Self {
+        let length = coefficients.truncate().length();
+        let coefficients = match length > 0. {
+            true => coefficients / length,
+            false => coefficients,
+        };
+
+        Self {
+            normal: coefficients.truncate(),
+            distance: coefficients.w,
+        }
+    }
+
+    /// Gets the distance from the plane to `point`, which is negative behind the plane
+    pub fn signed_distance(&self, point: Vec3) -> f32 {
+        self.normal.dot(point) + self.distance
+    }
+
+    /// Gets the point on the plane that is closest to `point`
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        point - self.normal * self.signed_distance(point)
+    }
+}
@@ -0,0 +1,209 @@
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+use super::{Aabb, BoundingSphere, Plane};
+
+/// A half line starting at `origin` and going in `direction`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+impl Ray {
+    /// Creates a new ray. The `direction` is normalized, so distances along the ray are in world units.
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize_or_zero(),
+        }
+    }
+
+    /// Gets the point at `distance` along the ray
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    /// Gets the point on the ray that is closest to `point`
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        self.at((point - self.origin).dot(self.direction).max(0.))
+    }
+
+    /// Gets the distance along the ray where it crosses `plane`, from either side.
+    ///
+    /// Returns `None` if the ray is parallel to the plane or points away from it.
+    pub fn intersect_plane(&self, plane: &Plane) -> Option<f32> {
+        let facing = plane.normal.dot(self.direction);
+        if facing.abs() <= f32::EPSILON {
+            return None;
+        }
+
+        let distance = -plane.signed_distance(self.origin) / facing;
+        (distance >= 0.).then_some(distance)
+    }
+
+    /// Gets the distance along the ray where it enters `sphere`.
+    ///
+    /// Returns zero if the ray starts inside the sphere, or `None` if it misses.
+    pub fn intersect_sphere(&self, sphere: &BoundingSphere) -> Option<f32> {
+        let offset = self.origin - sphere.center;
+        let b = offset.dot(self.direction);
+        let c = offset.length_squared() - sphere.radius * sphere.radius;
+        if c <= 0. {
+            return Some(0.);
+        }
+
+        let discriminant = b * b - c;
+        if b > 0. || discriminant < 0. {
+            return None;
+        }
+
+        Some(-b - discriminant.sqrt())
+    }
+
+    /// Gets the distance along the ray where it hits the triangle `a`, `b`, `c` from either side
+    pub fn intersect_triangle(&self, a: Vec3, b: Vec3, c: Vec3) -> Option<f32> {
+        // Möller–Trumbore intersection
+        let edge1 = b - a;
+        let edge2 = c - a;
+        let p = self.direction.cross(edge2);
+        let determinant = edge1.dot(p);
+        if determinant.abs() <= f32::EPSILON {
+            return None;
+        }
+
+        let inverse = 1. / determinant;
+        let t = self.origin - a;
+        let u = t.dot(p) * inverse;
+        if !(0. ..=1.).contains(&u) {
+            return None;
+        }
+
+        let q = t.cross(edge1);
+        let v = self.direction.dot(q) * inverse;
+        if v < 0. || u + v > 1. {
+            return None;
+        }
+
+        let distance = edge2.dot(q) * inverse;
+        (distance >= 0.).then_some(distance)
+    }
+
+    /// Gets the distance along the ray where it enters `aabb`.
+    ///
+    /// Returns zero if the ray starts inside the box, or `None` if it misses.
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let inverse = self.direction.recip();
+        let t1 = (aabb.min - self.origin) * inverse;
+        let t2 = (aabb.max - self.origin) * inverse;
+
+        // a zero direction on an axis gives infinite distances for that axis,
+        // which are both positive when the origin is outside of the slab
+        let near = t1.min(t2).max_element();
+        let far = t1.max(t2).min_element();
+
+        match near < f32::INFINITY && far >= near.max(0.) {
+            true => Some(near.max(0.)),
+            false => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aabb() {
+        let aabb = Aabb::new(Vec3::new(-1., -1., 4.), Vec3::new(1., 1., 6.));
+
+        let ray = Ray::new(Vec3::ZERO, Vec3::Z * 3.);
+        assert_eq!(ray.direction, Vec3::Z);
+        assert_eq!(ray.intersect_aabb(&aabb), Some(4.));
+        assert_eq!(ray.at(4.), Vec3::new(0., 0., 4.));
+
+        let inside = Ray::new(Vec3::new(0., 0., 5.), Vec3::X);
+        assert_eq!(inside.intersect_aabb(&aabb), Some(0.));
+
+        let behind = Ray::new(Vec3::ZERO, -Vec3::Z);
+        assert_eq!(behind.intersect_aabb(&aabb), None);
+
+        let miss = Ray::new(Vec3::new(2., 0., 0.), Vec3::Z);
+        assert_eq!(miss.intersect_aabb(&aabb), None);
+
+        let parallel = Ray::new(Vec3::new(0., 0., 2.), Vec3::X);
+        assert_eq!(parallel.intersect_aabb(&aabb), None);
+    }
+
+    #[test]
+    fn plane() {
+        let plane = Plane::from_point_normal(Vec3::Y * 2., Vec3::Y);
+
+        let ray = Ray::new(Vec3::ZERO, Vec3::new(1., 1., 0.));
+        let distance = ray.intersect_plane(&plane).unwrap();
+        assert!(ray.at(distance).abs_diff_eq(Vec3::new(2., 2., 0.), 0.0001));
+
+        // planes are hit from behind too
+        let ray = Ray::new(Vec3::Y * 5., -Vec3::Y);
+        assert_eq!(ray.intersect_plane(&plane), Some(3.));
+
+        assert_eq!(Ray::new(Vec3::ZERO, Vec3::X).intersect_plane(&plane), None);
+        assert_eq!(Ray::new(Vec3::ZERO, -Vec3::Y).intersect_plane(&plane), None);
+    }
+
+    #[test]
+    fn sphere() {
+        let sphere = BoundingSphere::new(Vec3::Z * 5., 1.);
+
+        assert_eq!(
+            Ray::new(Vec3::ZERO, Vec3::Z).intersect_sphere(&sphere),
+            Some(4.)
+        );
+        assert_eq!(
+            Ray::new(Vec3::Z * 5., Vec3::X).intersect_sphere(&sphere),
+            Some(0.)
+        );
+        assert_eq!(
+            Ray::new(Vec3::ZERO, -Vec3::Z).intersect_sphere(&sphere),
+            None
+        );
+        assert_eq!(
+            Ray::new(Vec3::X * 2., Vec3::Z).intersect_sphere(&sphere),
+            None
+        );
+    }
+
+    #[test]
+    fn triangle() {
+        let (a, b, c) = (
+            Vec3::new(-1., -1., 3.),
+            Vec3::new(1., -1., 3.),
+            Vec3::new(0., 1., 3.),
+        );
+
+        assert_eq!(
+            Ray::new(Vec3::ZERO, Vec3::Z).intersect_triangle(a, b, c),
+            Some(3.)
+        );
+        // triangles are hit from both sides
+        let back = Ray::new(Vec3::Z * 6., -Vec3::Z);
+        assert_eq!(back.intersect_triangle(a, b, c), Some(3.));
+
+        assert_eq!(
+            Ray::new(Vec3::ZERO, -Vec3::Z).intersect_triangle(a, b, c),
+            None
+        );
+        assert_eq!(Ray::new(Vec3::X, Vec3::Z).intersect_triangle(a, b, c), None);
+        assert_eq!(
+            Ray::new(Vec3::ZERO, Vec3::X).intersect_triangle(a, b, c),
+            None
+        );
+    }
+
+    #[test]
+    fn closest_point() {
+        let ray = Ray::new(Vec3::ZERO, Vec3::X);
+        assert_eq!(ray.closest_point(Vec3::new(3., 2., 0.)), Vec3::X * 3.);
+        assert_eq!(ray.closest_point(Vec3::new(-3., 2., 0.)), Vec3::ZERO);
+    }
+}
@@ -0,0 +1,137 @@
+use glam::{Mat4, Vec3};
+use serde::{Deserialize, Serialize};
+
+use super::Aabb;
+
+/// A sphere that contains some volume
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn new(center: Vec3, radius: f32) -> Self {
+        Self {
+            center,
+            radius: radius.abs(),
+        }
+    }
+
+    /// Creates a sphere that contains all `points`.
+    ///
+    /// The sphere is centered on the bounding box of the points, so it is not always the smallest sphere possible.
+    /// Returns `None` if there are no points.
+    pub fn from_points(points: impl IntoIterator<Item = Vec3> + Clone) -> Option<Self> {
+        let center = Aabb::from_points(points.clone())?.center();
+        let radius_squared = points
+            .into_iter()
+            .map(|point| point.distance_squared(center))
+            .fold(0., f32::max);
+        Some(Self::new(center, radius_squared.sqrt()))
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.center.distance_squared(point) <= self.radius * self.radius
+    }
+
+    /// Checks if `other` is completely inside this sphere
+    pub fn contains(&self, other: &BoundingSphere) -> bool {
+        self.center.distance(other.center) + other.radius <= self.radius
+    }
+
+    /// Checks if this sphere and `other` overlap. Spheres that only touch are also overlapping.
+    pub fn intersects(&self, other: &BoundingSphere) -> bool {
+        let radius = self.radius + other.radius;
+        self.center.distance_squared(other.center) <= radius * radius
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        aabb.intersects_sphere(self)
+    }
+
+    /// Creates the smallest sphere that contains both this sphere and `other`
+    pub fn union(&self, other: &BoundingSphere) -> Self {
+        let offset = other.center - self.center;
+        let distance = offset.length();
+        if distance + other.radius <= self.radius {
+            return *self;
+        }
+        if distance + self.radius <= other.radius {
+            return *other;
+        }
+
+        let radius = (distance + self.radius + other.radius) * 0.5;
+        let center = self.center + offset * ((radius - self.radius) / distance);
+        Self::new(center, radius)
+    }
+
+    /// Transforms the sphere by `matrix`.
+    ///
+    /// The radius is scaled by the largest scale of the matrix, so non uniform scales still fit inside the sphere.
+    pub fn transformed(&self, matrix: &Mat4) -> Self {
+        let scale = matrix
+            .x_axis
+            .truncate()
+            .length()
+            .max(matrix.y_axis.truncate().length())
+            .max(matrix.z_axis.truncate().length());
+        Self::new(matrix.transform_point3(self.center), self.radius * scale)
+    }
+
+    /// Gets the smallest box that contains this sphere
+    pub fn aabb(&self) -> Aabb {
+        Aabb::from_center_half_extents(self.center, Vec3::splat(self.radius))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 0.0001;
+
+    #[test]
+    fn from_points() {
+        assert!(BoundingSphere::from_points(Vec::<Vec3>::new()).is_none());
+
+        let points = [
+            Vec3::new(-1., 0., 0.),
+            Vec3::new(3., 0., 0.),
+            Vec3::new(1., 1., 0.),
+        ];
+        let sphere = BoundingSphere::from_points(points).unwrap();
+        assert!(sphere.center.abs_diff_eq(Vec3::new(1., 0.5, 0.), EPSILON));
+        for point in points {
+            assert!(sphere.contains_point(point));
+        }
+    }
+
+    #[test]
+    fn union() {
+        let a = BoundingSphere::new(Vec3::ZERO, 1.);
+        let b = BoundingSphere::new(Vec3::new(4., 0., 0.), 1.);
+
+        let union = a.union(&b);
+        assert!(union.center.abs_diff_eq(Vec3::new(2., 0., 0.), EPSILON));
+        assert!((union.radius - 3.).abs() < EPSILON);
+        assert!(union.contains(&a) && union.contains(&b));
+
+        let inner = BoundingSphere::new(Vec3::new(0.5, 0., 0.), 0.25);
+        assert_eq!(a.union(&inner), a);
+        assert_eq!(inner.union(&a), a);
+
+        assert!(!a.intersects(&b));
+        assert!(a.intersects(&BoundingSphere::new(Vec3::new(2., 0., 0.), 1.)));
+    }
+
+    #[test]
+    fn transformed() {
+        let sphere = BoundingSphere::new(Vec3::X, 1.);
+        let matrix = Mat4::from_translation(Vec3::Y) * Mat4::from_scale(Vec3::new(1., 3., 2.));
+
+        let world = sphere.transformed(&matrix);
+        assert!(world.center.abs_diff_eq(Vec3::new(1., 1., 0.), EPSILON));
+        assert!((world.radius - 3.).abs() < EPSILON);
+    }
+}
@@ -0,0 +1,340 @@
+use std::f32::consts::PI;
+
+use boba_core::{
+    register_pearl_stages, stages::BobaLateUpdate, BobaResources, BobaResult, Pearl, PearlError,
+    PearlMutError, PearlStage,
+};
+use glam::Vec3;
+use log::warn;
+use thiserror::Error;
+
+use crate::pearls::BobaTransform;
+
+use super::{rotation_arc, solve_ccd, solve_fabrik, solve_two_bone, IkSettings};
+
+/// An error returned when creating an [`IkChain`].
+#[derive(Debug, Error)]
+pub enum IkChainError {
+    #[error("IK chain needs at least {required} joints, but only {found} were given")]
+    NotEnoughJoints { required: usize, found: usize },
+    #[error("IK chain takes at most {maximum} joints, but {found} were given")]
+    TooManyJoints { maximum: usize, found: usize },
+}
+
+/// An error returned when an [`IkChain`] could not access one of its transforms while solving.
+#[derive(Debug, Error)]
+pub enum IkSolveError {
+    #[error("Could not read the target, pole or a joint. Error: {0}")]
+    ReadError(#[from] PearlError),
+    #[error("Could not rotate a joint. Error: {0}")]
+    JointError(#[from] PearlMutError),
+}
+
+/// The algorithm an [`IkChain`] uses to reach its target.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum IkSolver {
+    /// An exact solver for chains of three joints, like arms and legs
+    TwoBone,
+    /// Forward and backward reaching inverse kinematics
+    #[default]
+    Fabrik,
+    /// Cyclic coordinate descent
+    Ccd,
+}
+
+/// A pearl that rotates a chain of transforms every [`BobaLateUpdate`], so that the last joint reaches a target.
+///
+/// Each joint should be a descendant of the joint before it, like the bones of an arm.
+/// Only rotations are changed, so bone lengths are kept.
+pub struct IkChain {
+    joints: Vec<Pearl<BobaTransform>>,
+    max_bends: Vec<f32>,
+    target: Pearl<BobaTransform>,
+    pub pole: Option<Pearl<BobaTransform>>,
+    pub solver: IkSolver,
+    pub settings: IkSettings,
+}
+
+register_pearl_stages!(IkChain: BobaLateUpdate);
+
+impl PearlStage<BobaLateUpdate> for IkChain {
+    fn update(pearl: &Pearl<Self>, _: &f32, _: &mut BobaResources) -> BobaResult {
+        match pearl.borrow()?.solve() {
+            Ok(_)
+            | Err(IkSolveError::ReadError(PearlError::Destroyed))
+            | Err(IkSolveError::JointError(PearlMutError::Destroyed)) => (),
+            Err(e) => {
+                warn!("Skipping IK chain, because a transform could not be borrowed. Error: {e}")
+            }
+        }
+        Ok(())
+    }
+}
+
+impl IkChain {
+    /// Creates a chain that moves the last of `joints` to `target`.
+    ///
+    /// [`IkSolver::TwoBone`] needs exactly three joints, and the other solvers need at least two.
+    pub fn new(
+        joints: Vec<Pearl<BobaTransform>>,
+        target: Pearl<BobaTransform>,
+        solver: IkSolver,
+    ) -> Result<Self, IkChainError> {
+        let found = joints.len();
+        let (required, maximum) = match solver {
+            IkSolver::TwoBone => (3, Some(3)),
+            _ => (2, None),
+        };
+        if found < required {
+            return Err(IkChainError::NotEnoughJoints { required, found });
+        }
+        if let Some(maximum) = maximum.filter(|&maximum| found > maximum) {
+            return Err(IkChainError::TooManyJoints { maximum, found });
+        }
+
+        Ok(Self {
+            max_bends: vec![PI; found],
+            joints,
+            target,
+            pole: None,
+            solver,
+            settings: Default::default(),
+        })
+    }
+
+    /// Bends the chain towards `pole`
+    pub fn with_pole(mut self, pole: Pearl<BobaTransform>) -> Self {
+        self.pole = Some(pole);
+        self
+    }
+
+    pub fn with_settings(mut self, settings: IkSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn joints(&self) -> &[Pearl<BobaTransform>] {
+        &self.joints
+    }
+
+    pub fn target(&self) -> &Pearl<BobaTransform> {
+        &self.target
+    }
+
+    pub fn set_target(&mut self, target: Pearl<BobaTransform>) {
+        self.target = target;
+    }
+
+    /// Gets the most the joint at `index` can bend in radians
+    pub fn max_bend(&self, index: usize) -> Option<f32> {
+        self.max_bends.get(index).copied()
+    }
+
+    /// Limits the joint at `index` to bend at most `max_bend` radians away from the bone that leads into it.
+    ///
+    /// The root joint cannot be limited. Does nothing if there is no joint at `index`.
+    pub fn set_max_bend(&mut self, index: usize, max_bend: f32) {
+        if let Some(bend) = self.max_bends.get_mut(index) {
+            *bend = max_bend.clamp(0., PI);
+        }
+    }
+
+    /// Rotates the joints to reach the target.
+    ///
+    /// Returns `true` if the solver reached the target within the tolerance of the settings.
+    pub fn solve(&self) -> Result<bool, IkSolveError> {
+        let target = self.target.borrow()?.world_position();
+        let pole = match &self.pole {
+            Some(pole) => Some(pole.borrow()?.world_position()),
+            None => None,
+        };
+
+        let mut positions = Vec::with_capacity(self.joints.len());
+        for joint in &self.joints {
+            positions.push(joint.borrow()?.world_position());
+        }
+
+        let reached = match self.solver {
+            IkSolver::TwoBone => {
+                let mut bones = [positions[0], positions[1], positions[2]];
+                solve_two_bone(&mut bones, target, pole, self.max_bends[1]);
+                positions.copy_from_slice(&bones);
+                bones[2].distance(target) <= self.settings.tolerance
+            }
+            IkSolver::Fabrik => {
+                solve_fabrik(&mut positions, target, pole, &self.max_bends, self.settings)
+            }
+            IkSolver::Ccd => {
+                solve_ccd(&mut positions, target, pole, &self.max_bends, self.settings)
+            }
+        };
+
+        self.apply(&positions)?;
+        Ok(reached)
+    }
+
+    /// Rotates each joint from the root down so that the next joint lands on its solved position
+    fn apply(&self, positions: &[Vec3]) -> Result<(), IkSolveError> {
+        for (i, bone) in self.joints.windows(2).enumerate() {
+            let child = bone[1].borrow()?.world_position();
+            let mut joint = bone[0].borrow_mut()?;
+            let origin = joint.world_position();
+
+            let (Some(from), Some(to)) = (
+                (child - origin).try_normalize(),
+                (positions[i + 1] - origin).try_normalize(),
+            ) else {
+                continue;
+            };
+
+            let rotation = rotation_arc(from, to) * joint.world_rotation();
+            joint.set_world_rotation(rotation.normalize());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use boba_core::{BobaStage, PearlRegistry, StageClock, StageDelta};
+    use glam::Quat;
+
+    use crate::pearls::SetTransformParent;
+
+    use super::*;
+
+    const EPSILON: f32 = 0.01;
+
+    /// Creates a chain of `count` joints that are each one unit above their parent
+    fn chain(count: usize) -> Vec<Pearl<BobaTransform>> {
+        let mut joints: Vec<Pearl<BobaTransform>> = Vec::new();
+        for i in 0..count {
+            let offset = match i {
+                0 => Vec3::ZERO,
+                _ => Vec3::Y,
+            };
+            let mut joint = Pearl::wrap(BobaTransform::from_position(offset));
+            if let Some(parent) = joints.last() {
+                joint.set_parent(parent.clone()).unwrap();
+            }
+            joints.push(joint);
+        }
+        joints
+    }
+
+    fn end_position(joints: &[Pearl<BobaTransform>]) -> Vec3 {
+        joints.last().unwrap().borrow().unwrap().world_position()
+    }
+
+    #[test]
+    fn every_solver_reaches() {
+        let target = Pearl::wrap(BobaTransform::from_position(Vec3::new(1.2, 0.8, 0.5)));
+        for solver in [IkSolver::TwoBone, IkSolver::Fabrik, IkSolver::Ccd] {
+            let joints = chain(3);
+            let settings = IkSettings {
+                iterations: 64,
+                ..Default::default()
+            };
+            let ik = IkChain::new(joints.clone(), target.clone(), solver)
+                .unwrap()
+                .with_settings(settings);
+            assert!(ik.solve().unwrap(), "{solver:?} did not reach the target");
+            assert!(end_position(&joints).distance(Vec3::new(1.2, 0.8, 0.5)) < EPSILON);
+
+            // bones keep their lengths, because only rotations change
+            for joint in &joints[1..] {
+                assert!((joint.borrow().unwrap().local_position() - Vec3::Y).length() < EPSILON);
+            }
+        }
+    }
+
+    #[test]
+    fn solve_in_late_update() {
+        let mut clock = StageClock::replaying();
+        clock.queue_replay([StageDelta::new::<BobaLateUpdate>(0.1)]);
+        let mut resources = BobaResources::default();
+        resources.add(clock);
+
+        let joints = chain(5);
+        let target = Pearl::wrap(BobaTransform::from_position(Vec3::new(-2., 1., 1.)));
+        let pole = Pearl::wrap(BobaTransform::from_position(Vec3::Z * 5.));
+        let ik = IkChain::new(joints.clone(), target.clone(), IkSolver::Fabrik)
+            .unwrap()
+            .with_pole(pole);
+
+        let mut registry = PearlRegistry::default();
+        registry.add(Pearl::wrap(ik));
+        BobaLateUpdate::default()
+            .run(&mut registry, &mut resources)
+            .unwrap();
+
+        assert!(end_position(&joints).distance(Vec3::new(-2., 1., 1.)) < EPSILON);
+    }
+
+    #[test]
+    fn two_bone_pole_and_limit() {
+        let joints = chain(3);
+        let target = Pearl::wrap(BobaTransform::from_position(Vec3::new(0., 1., 0.5)));
+        let pole = Pearl::wrap(BobaTransform::from_position(Vec3::new(-5., 1., 0.)));
+        let mut ik = IkChain::new(joints.clone(), target, IkSolver::TwoBone)
+            .unwrap()
+            .with_pole(pole);
+        assert!(ik.solve().unwrap());
+        assert!(joints[1].borrow().unwrap().world_position().x < -0.5);
+
+        // a straight limit keeps the arm from bending at all
+        ik.set_max_bend(1, 0.);
+        assert!(!ik.solve().unwrap());
+        let mid = joints[1].borrow().unwrap().world_position();
+        let end = end_position(&joints);
+        assert!((end - mid).angle_between(mid) < EPSILON);
+
+        assert!(matches!(
+            IkChain::new(
+                chain(4),
+                Pearl::wrap(BobaTransform::default()),
+                IkSolver::TwoBone
+            ),
+            Err(IkChainError::TooManyJoints {
+                maximum: 3,
+                found: 4
+            })
+        ));
+        assert!(matches!(
+            IkChain::new(
+                chain(2),
+                Pearl::wrap(BobaTransform::default()),
+                IkSolver::TwoBone
+            ),
+            Err(IkChainError::NotEnoughJoints {
+                required: 3,
+                found: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn rotated_root_parent() {
+        // joints keep working when the whole chain is moved by a parent
+        let parent = Pearl::wrap(BobaTransform::from_position_rotation(
+            Vec3::new(10., 0., 0.),
+            Quat::from_rotation_z(FRAC_PI_2),
+        ));
+        let joints = chain(4);
+        joints[0].clone().set_parent(parent.clone()).unwrap();
+
+        let goal = Vec3::new(8., 1., 1.);
+        let target = Pearl::wrap(BobaTransform::from_position(goal));
+        let ik = IkChain::new(joints.clone(), target, IkSolver::Ccd)
+            .unwrap()
+            .with_settings(IkSettings {
+                iterations: 64,
+                ..Default::default()
+            });
+        assert!(ik.solve().unwrap());
+        assert!(end_position(&joints).distance(goal) < EPSILON);
+    }
+}
@@ -0,0 +1,5 @@
+mod chain;
+mod solvers;
+
+pub use chain::*;
+pub use solvers::*;
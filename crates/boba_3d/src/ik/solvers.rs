@@ -0,0 +1,369 @@
+use std::f32::consts::PI;
+
+use glam::{Quat, Vec3};
+
+/// Settings for the iterative [`solve_fabrik`] and [`solve_ccd`] solvers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IkSettings {
+    /// The most iterations to run each solve
+    pub iterations: usize,
+    /// How close the end effector has to be to the target to stop iterating
+    pub tolerance: f32,
+}
+
+impl Default for IkSettings {
+    fn default() -> Self {
+        Self {
+            iterations: 16,
+            tolerance: 0.001,
+        }
+    }
+}
+
+/// Solves a chain of three joints analytically, moving the middle and end joints so the end reaches `target`.
+///
+/// The chain bends towards `pole` if there is one, or keeps bending the way it already bends if not.
+/// The bend at the middle joint is limited to `max_bend` radians, so a target that is too close may not be reached.
+pub fn solve_two_bone(positions: &mut [Vec3; 3], target: Vec3, pole: Option<Vec3>, max_bend: f32) {
+    let [root, mid, end] = *positions;
+    let upper = root.distance(mid);
+    let lower = mid.distance(end);
+    if upper <= 0. || lower <= 0. {
+        return;
+    }
+
+    let Some(direction) = (target - root)
+        .try_normalize()
+        .or_else(|| (end - root).try_normalize())
+    else {
+        return;
+    };
+
+    // the reach from the root to the end, limited by the bone lengths and the bend limit
+    let max_bend = max_bend.clamp(0., PI);
+    let min_reach = (upper * upper + lower * lower + 2. * upper * lower * max_bend.cos())
+        .max(0.)
+        .sqrt();
+    let reach = root
+        .distance(target)
+        .clamp(min_reach.max((upper - lower).abs()), upper + lower);
+
+    let bend_hint = pole.unwrap_or(mid) - root;
+    let bend_direction = (bend_hint - direction * bend_hint.dot(direction))
+        .try_normalize()
+        .unwrap_or_else(|| direction.any_orthonormal_vector());
+
+    // law of cosines for the angle at the root
+    let cos_root = match reach > 0. {
+        true => {
+            ((upper * upper + reach * reach - lower * lower) / (2. * upper * reach)).clamp(-1., 1.)
+        }
+        false => 1.,
+    };
+    let sin_root = (1. - cos_root * cos_root).sqrt();
+
+    positions[1] = root + (direction * cos_root + bend_direction * sin_root) * upper;
+    positions[2] = root + direction * reach;
+}
+
+/// Solves a chain of joints with forward and backward reaching inverse kinematics.
+///
+/// `max_bends` holds the most each joint can bend in radians, relative to the bone that leads into it.
+/// Joints without a limit, and the root joint, can bend freely.
+/// Returns `true` if the end of the chain reached `target`.
+pub fn solve_fabrik(
+    positions: &mut [Vec3],
+    target: Vec3,
+    pole: Option<Vec3>,
+    max_bends: &[f32],
+    settings: IkSettings,
+) -> bool {
+    let Some(end) = positions.len().checked_sub(1).filter(|end| *end > 0) else {
+        return false;
+    };
+
+    let lengths = bone_lengths(positions);
+    let root = positions[0];
+    if root.distance(target) >= lengths.iter().sum::<f32>() {
+        // stretch towards the unreachable target
+        let direction = (target - root).normalize_or_zero();
+        for i in 1..positions.len() {
+            positions[i] = positions[i - 1] + direction * lengths[i - 1];
+        }
+        return positions[end].distance(target) <= settings.tolerance;
+    }
+
+    for _ in 0..settings.iterations {
+        if positions[end].distance(target) <= settings.tolerance {
+            return true;
+        }
+
+        // backward pass from the target to the root
+        positions[end] = target;
+        for i in (0..end).rev() {
+            let direction = (positions[i] - positions[i + 1]).normalize_or_zero();
+            positions[i] = positions[i + 1] + direction * lengths[i];
+        }
+
+        // forward pass from the root to the end, applying limits along the way
+        positions[0] = root;
+        for i in 1..positions.len() {
+            let mut direction = (positions[i] - positions[i - 1]).normalize_or_zero();
+            if i >= 2 {
+                let previous = (positions[i - 1] - positions[i - 2]).normalize_or_zero();
+                direction = limit_direction(direction, previous, joint_limit(max_bends, i - 1));
+            }
+            positions[i] = positions[i - 1] + direction * lengths[i - 1];
+        }
+
+        if let Some(pole) = pole {
+            bend_towards_pole(positions, pole);
+        }
+    }
+
+    positions[end].distance(target) <= settings.tolerance
+}
+
+/// Solves a chain of joints with cyclic coordinate descent.
+///
+/// Uses the same limits as [`solve_fabrik`]. Returns `true` if the end of the chain reached `target`.
+pub fn solve_ccd(
+    positions: &mut [Vec3],
+    target: Vec3,
+    pole: Option<Vec3>,
+    max_bends: &[f32],
+    settings: IkSettings,
+) -> bool {
+    let Some(end) = positions.len().checked_sub(1).filter(|end| *end > 0) else {
+        return false;
+    };
+
+    for _ in 0..settings.iterations {
+        if positions[end].distance(target) <= settings.tolerance {
+            return true;
+        }
+
+        for i in (0..end).rev() {
+            let joint = positions[i];
+            let (Some(to_end), Some(to_target)) = (
+                (positions[end] - joint).try_normalize(),
+                (target - joint).try_normalize(),
+            ) else {
+                continue;
+            };
+
+            let mut rotation = rotation_arc(to_end, to_target);
+            if i >= 1 {
+                // limit the new direction of the bone against the bone that leads into the joint
+                let bone = (positions[i + 1] - joint).normalize_or_zero();
+                let previous = (joint - positions[i - 1]).normalize_or_zero();
+                let rotated = (rotation * bone).normalize_or_zero();
+                let limited = limit_direction(rotated, previous, joint_limit(max_bends, i));
+                if limited != rotated {
+                    rotation = rotation_arc(rotated, limited) * rotation;
+                }
+            }
+
+            for position in &mut positions[i + 1..] {
+                *position = joint + rotation * (*position - joint);
+            }
+        }
+
+        if let Some(pole) = pole {
+            bend_towards_pole(positions, pole);
+        }
+    }
+
+    positions[end].distance(target) <= settings.tolerance
+}
+
+/// Gets the shortest rotation from the normalized `from` to the normalized `to`.
+///
+/// Unlike [`Quat::from_rotation_arc`], this keeps tiny rotations instead of snapping them to identity,
+/// which would stop iterative solvers just short of their target.
+pub(crate) fn rotation_arc(from: Vec3, to: Vec3) -> Quat {
+    let dot = from.dot(to);
+    if dot < -1. + 0.0001 {
+        return Quat::from_rotation_arc(from, to);
+    }
+
+    let axis = from.cross(to);
+    Quat::from_xyzw(axis.x, axis.y, axis.z, 1. + dot).normalize()
+}
+
+fn bone_lengths(positions: &[Vec3]) -> Vec<f32> {
+    positions
+        .windows(2)
+        .map(|bone| bone[0].distance(bone[1]))
+        .collect()
+}
+
+fn joint_limit(max_bends: &[f32], joint: usize) -> f32 {
+    max_bends.get(joint).copied().unwrap_or(PI)
+}
+
+/// Rotates `direction` towards `reference` until the angle between them is at most `max_angle`
+fn limit_direction(direction: Vec3, reference: Vec3, max_angle: f32) -> Vec3 {
+    if max_angle >= PI || direction == Vec3::ZERO || reference == Vec3::ZERO {
+        return direction;
+    }
+
+    let max_angle = max_angle.max(0.);
+    if direction.angle_between(reference) <= max_angle {
+        return direction;
+    }
+
+    let axis = reference
+        .cross(direction)
+        .try_normalize()
+        .unwrap_or_else(|| reference.any_orthonormal_vector());
+    Quat::from_axis_angle(axis, max_angle) * reference
+}
+
+/// Rotates each inner joint around the line between its neighbours, so that it bends towards `pole`
+fn bend_towards_pole(positions: &mut [Vec3], pole: Vec3) {
+    for i in 1..positions.len().saturating_sub(1) {
+        let (start, end) = (positions[i - 1], positions[i + 1]);
+        let Some(axis) = (end - start).try_normalize() else {
+            continue;
+        };
+
+        let project = |point: Vec3| {
+            let offset = point - start;
+            offset - axis * offset.dot(axis)
+        };
+
+        let (Some(joint), Some(pole)) = (
+            project(positions[i]).try_normalize(),
+            project(pole).try_normalize(),
+        ) else {
+            continue;
+        };
+
+        let angle = joint.cross(pole).dot(axis).atan2(joint.dot(pole));
+        positions[i] = start + Quat::from_axis_angle(axis, angle) * (positions[i] - start);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 0.001;
+
+    fn arm() -> [Vec3; 3] {
+        [Vec3::ZERO, Vec3::new(0., 1., 0.), Vec3::new(0., 2., 0.)]
+    }
+
+    fn assert_lengths(positions: &[Vec3], lengths: &[f32]) {
+        for (bone, length) in positions.windows(2).zip(lengths) {
+            assert!((bone[0].distance(bone[1]) - length).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn two_bone_reaches_with_pole() {
+        let mut positions = arm();
+        let target = Vec3::new(1., 1., 0.);
+        solve_two_bone(&mut positions, target, Some(Vec3::new(0., 0., 5.)), PI);
+
+        assert!(positions[2].distance(target) < EPSILON);
+        assert_lengths(&positions, &[1., 1.]);
+        // the middle joint bends towards the pole
+        assert!(positions[1].z > 0.5);
+        assert_eq!(positions[0], Vec3::ZERO);
+    }
+
+    #[test]
+    fn two_bone_limits() {
+        // unreachable targets stretch the chain towards them
+        let mut positions = arm();
+        solve_two_bone(&mut positions, Vec3::X * 5., None, PI);
+        assert!(positions[2].distance(Vec3::X * 2.) < EPSILON);
+
+        // a bend limit of 90 degrees keeps the end at least sqrt(2) away from the root
+        let mut positions = [Vec3::ZERO, Vec3::X, Vec3::new(1., 1., 0.)];
+        solve_two_bone(&mut positions, Vec3::new(0.1, 0.1, 0.), None, PI * 0.5);
+        assert!((positions[2].length() - 2f32.sqrt()).abs() < EPSILON);
+        assert_lengths(&positions, &[1., 1.]);
+    }
+
+    #[test]
+    fn iterative_solvers_reach() {
+        let chain: Vec<Vec3> = (0..5).map(|i| Vec3::Y * i as f32).collect();
+        let targets = [
+            Vec3::new(2., 2., 0.),
+            Vec3::new(-1., 1., 2.),
+            Vec3::new(0., -3., 0.5),
+        ];
+
+        for target in targets {
+            let mut positions = chain.clone();
+            assert!(solve_fabrik(
+                &mut positions,
+                target,
+                None,
+                &[],
+                IkSettings::default()
+            ));
+            assert!(positions[4].distance(target) < EPSILON);
+            assert_lengths(&positions, &[1.; 4]);
+
+            let settings = IkSettings {
+                iterations: 64,
+                ..Default::default()
+            };
+            let mut positions = chain.clone();
+            assert!(solve_ccd(&mut positions, target, None, &[], settings));
+            assert!(positions[4].distance(target) < EPSILON);
+            assert_lengths(&positions, &[1.; 4]);
+        }
+    }
+
+    #[test]
+    fn iterative_solvers_respect_limits() {
+        let chain: Vec<Vec3> = (0..4).map(|i| Vec3::Y * i as f32).collect();
+        let max_bends = [PI, 0.6, 0.6, PI];
+        let target = Vec3::new(1.2, 2.6, 0.);
+
+        let fabrik = |positions: &mut [Vec3]| {
+            solve_fabrik(positions, target, None, &max_bends, IkSettings::default())
+        };
+        let ccd = |positions: &mut [Vec3]| {
+            let settings = IkSettings {
+                iterations: 64,
+                ..Default::default()
+            };
+            solve_ccd(positions, target, None, &max_bends, settings)
+        };
+
+        for solve in [&fabrik as &dyn Fn(&mut [Vec3]) -> bool, &ccd] {
+            let mut positions = chain.clone();
+            assert!(solve(&mut positions));
+            assert_lengths(&positions, &[1.; 3]);
+            for joint in 1..3 {
+                let incoming = positions[joint] - positions[joint - 1];
+                let outgoing = positions[joint + 1] - positions[joint];
+                assert!(incoming.angle_between(outgoing) <= 0.6 + EPSILON);
+            }
+        }
+    }
+
+    #[test]
+    fn fabrik_pole() {
+        // a target straight along the chain has no bend direction, so it starts slightly off the line
+        let chain = [Vec3::ZERO, Vec3::Y, Vec3::Y * 2.];
+        let target = Vec3::new(0.05, 1.5, 0.);
+        for pole in [Vec3::X * 5., Vec3::Z * -5.] {
+            let mut positions = chain;
+            assert!(solve_fabrik(
+                &mut positions,
+                target,
+                Some(pole),
+                &[],
+                IkSettings::default()
+            ));
+            assert!(positions[1].normalize().dot(pole.normalize()) > 0.5);
+        }
+    }
+}
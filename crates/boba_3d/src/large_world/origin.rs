@@ -0,0 +1,274 @@
+use std::cell::Cell;
+
+use boba_core::{
+    register_pearl_stages, stages::BobaPreRender, BobaResources, BobaResult, Pearl, PearlStage,
+    WeakPearl,
+};
+use glam::{DVec3, Vec3};
+use log::error;
+
+use crate::pearls::{BobaTransform, ChangeStamps};
+
+type RebaseCallback = Box<dyn FnMut(Vec3, &mut BobaResources) -> BobaResult>;
+
+thread_local! {
+    /// The absolute position of the floating origin, which root transforms on this thread are relative to.
+    /// This is `None` until a [`FloatingOrigin`] is created on the thread.
+    static WORLD_ORIGIN: Cell<Option<DVec3>> = const { Cell::new(None) };
+}
+
+/// Gets the absolute position of the floating origin, or `None` if large world mode is off on this thread
+pub(crate) fn world_origin() -> Option<DVec3> {
+    WORLD_ORIGIN.with(Cell::get)
+}
+
+/// A pearl that keeps transforms close to the origin in large worlds, where `f32` positions lose precision.
+///
+/// Transform positions are relative to a floating origin, which is stored as an `f64` absolute position.
+/// When the focus transform (usually the camera) drifts further than the threshold from the origin,
+/// the origin moves to the focus and every root transform is shifted back by the same amount.
+/// This is checked every [`BobaPreRender`], after gameplay and physics have moved things for the frame.
+///
+/// Root transforms are shifted lazily the next time they are read, so children move along with their parents
+/// and nothing has to be registered. Path followers and position tweens are shifted the same way.
+/// Anything else that keeps its own world positions, like a physics world or a [`SpatialIndex`](crate::spatial::SpatialIndex),
+/// should be shifted in an [`on_rebase`](Self::on_rebase) callback.
+///
+/// Large world mode is off until the first floating origin is created, and transforms ignore the origin until then.
+/// The origin is shared by every transform on the thread, so there should only be one floating origin per thread.
+pub struct FloatingOrigin {
+    pub threshold: f32,
+    focus: Option<WeakPearl<BobaTransform>>,
+    callbacks: Vec<RebaseCallback>,
+}
+
+register_pearl_stages!(FloatingOrigin: BobaPreRender);
+
+impl PearlStage<BobaPreRender> for FloatingOrigin {
+    fn update(pearl: &Pearl<Self>, _: &(), resources: &mut BobaResources) -> BobaResult {
+        let mut origin = pearl.borrow_mut()?;
+        let Some(shift) = origin.rebase_shift() else {
+            return Ok(());
+        };
+
+        origin.rebase(shift, resources);
+        Ok(())
+    }
+}
+
+impl Default for FloatingOrigin {
+    fn default() -> Self {
+        Self::new(1000.)
+    }
+}
+
+impl FloatingOrigin {
+    /// Creates an origin that rebases when the focus is further than `threshold` units away.
+    ///
+    /// This turns on large world mode for the current thread. If there already was an origin on the thread, it is shared.
+    pub fn new(threshold: f32) -> Self {
+        WORLD_ORIGIN.with(|origin| origin.set(Some(origin.get().unwrap_or_default())));
+        Self {
+            threshold,
+            focus: None,
+            callbacks: Vec::new(),
+        }
+    }
+
+    /// The absolute position of the origin
+    pub fn origin(&self) -> DVec3 {
+        world_origin().unwrap_or_default()
+    }
+
+    /// Sets the transform that the origin follows
+    pub fn set_focus(&mut self, focus: &Pearl<BobaTransform>) {
+        self.focus = Some(focus.downgrade());
+    }
+
+    /// Calls `callback` with the shift every time the origin moves.
+    ///
+    /// Positions that are relative to the origin should be moved by the negative of the shift.
+    pub fn on_rebase(
+        &mut self,
+        callback: impl FnMut(Vec3, &mut BobaResources) -> BobaResult + 'static,
+    ) {
+        self.callbacks.push(Box::new(callback));
+    }
+
+    /// Converts a position relative to the origin into an absolute position
+    pub fn to_absolute(&self, position: Vec3) -> DVec3 {
+        self.origin() + position.as_dvec3()
+    }
+
+    /// Converts an absolute position into a position relative to the origin
+    pub fn to_relative(&self, position: DVec3) -> Vec3 {
+        (position - self.origin()).as_vec3()
+    }
+
+    /// Gets the absolute world position of `transform`
+    pub fn absolute_position(&self, transform: &BobaTransform) -> DVec3 {
+        self.to_absolute(transform.world_position())
+    }
+
+    /// Moves `transform` to an absolute world position
+    pub fn set_absolute_position(&self, transform: &mut BobaTransform, position: DVec3) {
+        transform.set_world_position(self.to_relative(position));
+    }
+
+    /// Moves the origin by `shift`, moving every root transform by the opposite amount and calling the rebase callbacks
+    pub fn rebase(&mut self, shift: Vec3, resources: &mut BobaResources) {
+        if shift == Vec3::ZERO {
+            return;
+        }
+
+        let origin = self.origin() + shift.as_dvec3();
+        WORLD_ORIGIN.with(|world_origin| world_origin.set(Some(origin)));
+        ChangeStamps::mark_all_changed();
+
+        for callback in &mut self.callbacks {
+            if let Err(e) = callback(shift, resources) {
+                error!("There was an error in a floating origin rebase callback. Error: {e}");
+            }
+        }
+    }
+
+    /// Gets the shift needed to move the origin onto the focus, if the focus is past the threshold
+    fn rebase_shift(&self) -> Option<Vec3> {
+        let focus = self.focus.as_ref()?.upgrade()?;
+        let position = focus.borrow().ok()?.world_position();
+        (position.length() > self.threshold).then_some(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use boba_core::{BobaStage, PearlRegistry};
+    use glam::Quat;
+
+    use crate::pearls::SetTransformParent;
+
+    use super::*;
+
+    const EPSILON: f32 = 0.0001;
+
+    #[test]
+    fn rebases_past_threshold() {
+        let camera = Pearl::wrap(BobaTransform::from_position(Vec3::new(90., 0., 0.)));
+        let light = Pearl::wrap(BobaTransform::from_position(Vec3::new(100., 5., 0.)));
+        let mut child = Pearl::wrap(BobaTransform::from_position(Vec3::Y));
+        child.set_parent(light.clone()).unwrap();
+
+        let mut origin = FloatingOrigin::new(100.);
+        origin.set_focus(&camera);
+
+        let shifts = Rc::new(Cell::new(Vec3::ZERO));
+        let recorded = shifts.clone();
+        origin.on_rebase(move |shift, _| {
+            recorded.set(recorded.get() + shift);
+            Ok(())
+        });
+
+        let origin = Pearl::wrap(origin);
+        let mut registry = PearlRegistry::default();
+        registry.add(origin.clone());
+        let mut resources = BobaResources::default();
+
+        // still inside the threshold
+        BobaPreRender.run(&mut registry, &mut resources).unwrap();
+        assert_eq!(shifts.get(), Vec3::ZERO);
+
+        camera
+            .borrow_mut()
+            .unwrap()
+            .translate(Vec3::new(20., 0., 0.));
+        BobaPreRender.run(&mut registry, &mut resources).unwrap();
+        assert_eq!(shifts.get(), Vec3::new(110., 0., 0.));
+        assert_eq!(camera.borrow().unwrap().world_position(), Vec3::ZERO);
+
+        // every transform keeps its absolute position, and children are only shifted through their parent
+        let origin = origin.borrow().unwrap();
+        assert_eq!(origin.origin(), DVec3::new(110., 0., 0.));
+        let light_position = origin.absolute_position(&light.borrow().unwrap());
+        assert_eq!(light_position, DVec3::new(100., 5., 0.));
+        let child_position = origin.absolute_position(&child.borrow().unwrap());
+        assert_eq!(child_position, DVec3::new(100., 6., 0.));
+        assert_eq!(child.borrow().unwrap().local_position(), Vec3::Y);
+    }
+
+    #[test]
+    fn relative_positions_unchanged() {
+        let a = Pearl::wrap(BobaTransform::from_position(Vec3::new(3., 0., 0.)));
+        let b = Pearl::wrap(BobaTransform::from_position(Vec3::new(-2., 4., 1.)));
+        let mut child = Pearl::wrap(BobaTransform::from_position(Vec3::Z));
+        child.set_parent(b.clone()).unwrap();
+        let positions =
+            || [&a, &b, &child].map(|transform| transform.borrow().unwrap().world_position());
+        let before = positions();
+
+        let mut origin = FloatingOrigin::default();
+        origin.rebase(Vec3::new(500., -20., 10.), &mut BobaResources::default());
+
+        // the whole scene moves together, so nothing moves relative to anything else
+        let after = positions();
+        for (before, after) in before.iter().zip(after.iter()) {
+            assert!((*after - *before).abs_diff_eq(Vec3::new(-500., 20., -10.), EPSILON));
+        }
+        assert!((after[2] - after[1]).abs_diff_eq(Vec3::Z, EPSILON));
+
+        // editing a root after the rebase keeps the shift
+        a.borrow_mut()
+            .unwrap()
+            .set_local_rotation(Quat::from_rotation_y(1.));
+        let a_position = a.borrow().unwrap().world_position();
+        assert!(a_position.abs_diff_eq(after[0], EPSILON));
+        assert!(a
+            .borrow()
+            .unwrap()
+            .local_position()
+            .abs_diff_eq(after[0], EPSILON));
+    }
+
+    #[test]
+    fn dropped_parent_then_rebase() {
+        let parent = Pearl::wrap(BobaTransform::from_position(Vec3::new(10., 0., 0.)));
+        let mut child = Pearl::wrap(BobaTransform::from_position(Vec3::Y));
+        child.set_parent(parent.clone()).unwrap();
+        drop(parent);
+
+        // transforms ignore the origin until one is created
+        assert!(world_origin().is_none());
+        let mut origin = FloatingOrigin::default();
+        assert_eq!(world_origin(), Some(DVec3::ZERO));
+
+        // the child is a root now, so it is shifted with the matrix its parent left behind
+        let mut resources = BobaResources::default();
+        origin.rebase(Vec3::X * 100., &mut resources);
+        let position = child.borrow().unwrap().world_position();
+        assert!(position.abs_diff_eq(Vec3::new(-90., 1., 0.), EPSILON));
+        assert_eq!(child.borrow().unwrap().local_position(), Vec3::Y);
+
+        child.borrow_mut().unwrap().set_local_position(Vec3::Z);
+        origin.rebase(Vec3::X * 10., &mut resources);
+        let position = child.borrow().unwrap().world_position();
+        assert!(position.abs_diff_eq(Vec3::new(-100., 0., 1.), EPSILON));
+    }
+
+    #[test]
+    fn precision_far_from_the_start() {
+        let mut origin = FloatingOrigin::new(1000.);
+        let mut resources = BobaResources::default();
+        origin.rebase(Vec3::new(5_000_000., 0., 0.), &mut resources);
+        origin.rebase(Vec3::new(0., 0., 3_000_000.), &mut resources);
+
+        // a small step is still exact, even though the absolute position is far beyond f32 precision
+        let mut transform = BobaTransform::default();
+        let target = DVec3::new(5_000_000.25, 1.5, 3_000_000.125);
+        origin.set_absolute_position(&mut transform, target);
+        assert!(transform
+            .world_position()
+            .abs_diff_eq(Vec3::new(0.25, 1.5, 0.125), EPSILON));
+        assert_eq!(origin.absolute_position(&transform), target);
+    }
+}
@@ -1,3 +1,12 @@
+pub mod animation;
+pub mod commands;
+pub mod constraints;
+pub mod geometry;
+pub mod ik;
+pub mod large_world;
 pub mod pearls;
+pub mod spatial;
+pub mod spline;
+pub mod tween;
 
 pub use glam;
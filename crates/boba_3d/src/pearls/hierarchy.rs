@@ -0,0 +1,331 @@
+use boba_core::{Pearl, PearlError, PearlId, PearlMutError, WeakPearl};
+use indexmap::IndexSet;
+use log::error;
+use thiserror::Error;
+
+use super::{ChangeStamps, StampLineage};
+
+#[derive(Debug, Error)]
+pub enum SetParentError {
+    #[error("A parent child relationship was recursive")]
+    RecursionError,
+    #[error("There was an error accessing one a pearl. Error: {0}")]
+    PearlError(#[from] PearlMutError),
+    #[error("There was an error reading a pearl. Error: {0}")]
+    PearlReadError(#[from] PearlError),
+}
+
+/// The parent and children of a transform.
+///
+/// Parents hold their children, and children only hold a weak link to their parent.
+/// The links can only be changed through [`SetTransformParent`].
+pub struct TransformLinks<T> {
+    parent: Option<WeakPearl<T>>,
+    children: IndexSet<Pearl<T>>,
+}
+
+impl<T> Default for TransformLinks<T> {
+    fn default() -> Self {
+        Self {
+            parent: None,
+            children: Default::default(),
+        }
+    }
+}
+
+impl<T> TransformLinks<T> {
+    /// Gets the parent, if there is one that is still alive
+    pub fn parent(&self) -> Option<Pearl<T>> {
+        self.parent.as_ref()?.upgrade()
+    }
+
+    /// Gets the link to the parent, even if the parent was dropped
+    pub fn parent_link(&self) -> Option<&WeakPearl<T>> {
+        self.parent.as_ref()
+    }
+
+    /// Iterates over the children in sibling order
+    pub fn children(&self) -> impl Iterator<Item = &Pearl<T>> {
+        self.children.iter()
+    }
+
+    /// Gets the child at `index` in sibling order
+    pub fn child(&self, index: usize) -> Option<&Pearl<T>> {
+        self.children.get_index(index)
+    }
+
+    pub fn child_count(&self) -> usize {
+        self.children.len()
+    }
+}
+
+/// A transform that can be arranged in a hierarchy with [`SetTransformParent`].
+///
+/// This is implemented by [`BobaTransform`](super::BobaTransform), and by other transforms like the 2D transform,
+/// so that every kind of transform follows the same parent and child rules.
+pub trait HierarchyTransform: Sized + 'static {
+    /// The world values that a parent hands down to its children, like its world matrix
+    type ParentWorld: Copy;
+
+    /// The parent world values of a transform without a parent
+    const ROOT: Self::ParentWorld;
+
+    fn links(&self) -> &TransformLinks<Self>;
+    fn links_mut(&mut self) -> &mut TransformLinks<Self>;
+    fn stamps(&self) -> &ChangeStamps;
+    fn stamps_mut(&mut self) -> &mut ChangeStamps;
+
+    /// The world values this transform hands down to its children
+    fn parent_world(&self) -> Self::ParentWorld;
+
+    /// Prepares the transform to be moved under a parent with `parent_world`.
+    ///
+    /// This is called right before the parent link is replaced. If `keep_world` is true,
+    /// the local values are recalculated so that the world values stay the same under the new parent.
+    fn prepare_relink(&mut self, parent_world: Self::ParentWorld, keep_world: bool);
+}
+
+/// Methods to manage the parent child hierarchy of transform pearls.
+///
+/// Parents hold their children, and children only hold a weak link to their parent.
+/// A child keeps its parent alive only as long as something else holds the parent pearl.
+pub trait SetTransformParent {
+    type Transform;
+
+    /// Sets the parent of this transform, keeping its local transform.
+    ///
+    /// The transform is moved to the end of the new parent's children.
+    fn set_parent(&mut self, parent: Pearl<Self::Transform>) -> Result<(), SetParentError>;
+
+    /// Sets the parent of this transform, keeping its world transform.
+    fn set_parent_keep_world(
+        &mut self,
+        parent: Pearl<Self::Transform>,
+    ) -> Result<(), SetParentError>;
+
+    /// Removes this transform from its parent, keeping its local transform.
+    fn remove_parent(&mut self) -> Result<(), SetParentError>;
+
+    /// Removes this transform from its parent, keeping its world transform.
+    fn remove_parent_keep_world(&mut self) -> Result<(), SetParentError>;
+
+    /// Gets the index of this transform among its siblings, or `None` if it has no parent.
+    fn sibling_index(&self) -> Result<Option<usize>, SetParentError>;
+
+    /// Moves this transform to `index` among its siblings.
+    ///
+    /// Indices past the last sibling move it to the end. Does nothing if there is no parent.
+    fn set_sibling_index(&mut self, index: usize) -> Result<(), SetParentError>;
+
+    /// Destroys this transform and all of its descendants
+    fn destroy_recursive(&self) -> Result<(), SetParentError>;
+
+    /// Destroys this transform, moving its children to its parent while keeping their world transforms
+    fn destroy_keep_children(&self) -> Result<(), SetParentError>;
+}
+
+impl<T: HierarchyTransform> SetTransformParent for Pearl<T> {
+    type Transform = T;
+
+    fn set_parent(&mut self, parent: Pearl<T>) -> Result<(), SetParentError> {
+        attach(self, &parent, false)
+    }
+
+    fn set_parent_keep_world(&mut self, parent: Pearl<T>) -> Result<(), SetParentError> {
+        attach(self, &parent, true)
+    }
+
+    fn remove_parent(&mut self) -> Result<(), SetParentError> {
+        detach(self, false)
+    }
+
+    fn remove_parent_keep_world(&mut self) -> Result<(), SetParentError> {
+        detach(self, true)
+    }
+
+    fn sibling_index(&self) -> Result<Option<usize>, SetParentError> {
+        let Some(parent) = self.borrow()?.links().parent() else {
+            return Ok(None);
+        };
+
+        let parent_data = parent.borrow()?;
+        Ok(parent_data.links().children.get_index_of(self))
+    }
+
+    fn set_sibling_index(&mut self, index: usize) -> Result<(), SetParentError> {
+        let Some(parent) = self.borrow_mut()?.links().parent() else {
+            return Ok(());
+        };
+
+        let mut parent_data = parent.borrow_mut()?;
+        let children = &mut parent_data.links_mut().children;
+        let Some(current) = children.get_index_of(self) else {
+            return Ok(());
+        };
+
+        let index = index.min(children.len() - 1);
+        children.move_index(current, index);
+        Ok(())
+    }
+
+    fn destroy_recursive(&self) -> Result<(), SetParentError> {
+        detach(self, false)?;
+        destroy_descendants(self)
+    }
+
+    fn destroy_keep_children(&self) -> Result<(), SetParentError> {
+        let mut data = self.borrow_mut()?;
+        let parent = data.links().parent();
+        let children = std::mem::take(&mut data.links_mut().children);
+        drop(data);
+
+        for child in children {
+            match &parent {
+                Some(parent) => attach(&child, parent, true)?,
+                None => set_parent_link(
+                    &mut *child.borrow_mut()?,
+                    None,
+                    T::ROOT,
+                    StampLineage::default(),
+                    true,
+                ),
+            }
+        }
+
+        detach(self, false)?;
+        self.destroy().map_err(PearlMutError::Borrowed)?;
+        Ok(())
+    }
+}
+
+/// Replaces the parent link, recalculating the local transform if the world transform should be kept
+fn set_parent_link<T: HierarchyTransform>(
+    transform: &mut T,
+    parent: Option<WeakPearl<T>>,
+    parent_world: T::ParentWorld,
+    ancestors: StampLineage,
+    keep_world: bool,
+) {
+    transform.prepare_relink(parent_world, keep_world);
+    transform.links_mut().parent = parent;
+    set_ancestors(transform, ancestors);
+    transform.stamps().mark_changed();
+}
+
+/// Replaces the ancestor stamps of `transform` and all of its descendants
+fn set_ancestors<T: HierarchyTransform>(transform: &mut T, ancestors: StampLineage) {
+    transform.stamps_mut().set_ancestors(ancestors);
+    let lineage = transform.stamps().lineage();
+    for child in transform.links().children.iter() {
+        match child.borrow_mut() {
+            Ok(mut child) => set_ancestors(&mut *child, lineage.clone()),
+            Err(PearlMutError::Destroyed) => (),
+            Err(e) => error!("Could not sync child transform due to: {e}"),
+        }
+    }
+}
+
+fn attach<T: HierarchyTransform>(
+    pearl: &Pearl<T>,
+    parent: &Pearl<T>,
+    keep_world: bool,
+) -> Result<(), SetParentError> {
+    if pearl.id() == parent.id() {
+        return Err(SetParentError::RecursionError);
+    }
+
+    let parent_data = parent.borrow_mut()?;
+    validate_parent_recursive(pearl.id(), &*parent_data)?;
+    drop(parent_data);
+
+    let current = pearl.borrow_mut()?.links().parent();
+    if current.as_ref() == Some(parent) {
+        return Ok(());
+    }
+
+    let parent_data = parent.borrow_mut()?;
+    let (world, lineage) = (parent_data.parent_world(), parent_data.stamps().lineage());
+    drop(parent_data);
+
+    set_parent_link(
+        &mut *pearl.borrow_mut()?,
+        Some(parent.downgrade()),
+        world,
+        lineage,
+        keep_world,
+    );
+
+    remove_from_parent(pearl, current)?;
+    parent
+        .borrow_mut()?
+        .links_mut()
+        .children
+        .insert(pearl.clone());
+    Ok(())
+}
+
+fn detach<T: HierarchyTransform>(pearl: &Pearl<T>, keep_world: bool) -> Result<(), SetParentError> {
+    let mut data = pearl.borrow_mut()?;
+    if data.links().parent.is_none() {
+        return Ok(());
+    }
+
+    let current = data.links().parent();
+    set_parent_link(
+        &mut *data,
+        None,
+        T::ROOT,
+        StampLineage::default(),
+        keep_world,
+    );
+    drop(data);
+
+    remove_from_parent(pearl, current)
+}
+
+fn remove_from_parent<T: HierarchyTransform>(
+    pearl: &Pearl<T>,
+    parent: Option<Pearl<T>>,
+) -> Result<(), SetParentError> {
+    let Some(parent) = parent else {
+        return Ok(());
+    };
+
+    let mut parent_data = match parent.borrow_mut() {
+        Ok(parent_data) => parent_data,
+        Err(PearlMutError::Destroyed) => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+
+    parent_data.links_mut().children.shift_remove(pearl);
+    Ok(())
+}
+
+fn destroy_descendants<T: HierarchyTransform>(pearl: &Pearl<T>) -> Result<(), SetParentError> {
+    let children = std::mem::take(&mut pearl.borrow_mut()?.links_mut().children);
+    for child in children.iter() {
+        match destroy_descendants(child) {
+            Err(SetParentError::PearlError(PearlMutError::Destroyed)) => (),
+            result => result?,
+        }
+    }
+
+    pearl.destroy().map_err(PearlMutError::Borrowed)?;
+    Ok(())
+}
+
+fn validate_parent_recursive<T: HierarchyTransform>(
+    id: &PearlId,
+    target: &T,
+) -> Result<(), SetParentError> {
+    let Some(parent) = target.links().parent() else {
+        return Ok(());
+    };
+
+    if id == parent.id() {
+        return Err(SetParentError::RecursionError);
+    };
+
+    let parent_data = parent.borrow_mut()?;
+    validate_parent_recursive(id, &*parent_data)
+}
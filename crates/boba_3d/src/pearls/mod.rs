@@ -1,3 +1,9 @@
+mod hierarchy;
+mod observers;
+mod stamps;
 mod transform;
 
+pub use hierarchy::*;
+pub use observers::*;
+pub use stamps::*;
 pub use transform::*;
@@ -0,0 +1,273 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use boba_core::{
+    register_pearl_stages, stages::BobaPreRender, BobaResources, BobaResult, Pearl, PearlError,
+    PearlId, PearlStage, WeakPearl,
+};
+use glam::Mat4;
+use indexmap::{map::Entry, IndexMap};
+use log::warn;
+
+use super::BobaTransform;
+
+/// The id of an observer added to [`TransformObservers`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObserverId(u64);
+
+/// A change to the world transform of an observed transform.
+#[derive(Clone)]
+pub struct TransformChange {
+    pub transform: Pearl<BobaTransform>,
+    /// The world matrix when observers were last notified
+    pub old: Mat4,
+    /// The current world matrix
+    pub new: Mat4,
+}
+
+type ChangeCallback = Box<dyn FnMut(&TransformChange)>;
+
+enum Observer {
+    Callback(ChangeCallback),
+    Channel(Sender<TransformChange>),
+}
+
+struct ObservedTransform {
+    transform: WeakPearl<BobaTransform>,
+    stamp: u64,
+    matrix: Mat4,
+    observers: IndexMap<ObserverId, Observer>,
+}
+
+/// A pearl that notifies observers when the world transform of a [`BobaTransform`] changes.
+///
+/// Transforms are checked every [`BobaPreRender`], after gameplay, physics and constraints have moved them.
+/// Checking only compares change stamps, so transforms that did not move or inherit a move from a parent
+/// are not recalculated. Several moves between checks are reported as a single change.
+#[derive(Default)]
+pub struct TransformObservers {
+    entries: IndexMap<PearlId, ObservedTransform>,
+    next_id: u64,
+}
+
+register_pearl_stages!(TransformObservers: BobaPreRender);
+
+impl PearlStage<BobaPreRender> for TransformObservers {
+    fn update(pearl: &Pearl<Self>, _: &(), _: &mut BobaResources) -> BobaResult {
+        pearl.borrow_mut()?.notify();
+        Ok(())
+    }
+}
+
+impl TransformObservers {
+    /// Calls `callback` whenever the world transform of `transform` changes.
+    ///
+    /// The callback runs while this pearl is borrowed, so it cannot access it.
+    pub fn observe(
+        &mut self,
+        transform: &Pearl<BobaTransform>,
+        callback: impl FnMut(&TransformChange) + 'static,
+    ) -> Result<ObserverId, PearlError> {
+        self.add(transform, Observer::Callback(Box::new(callback)))
+    }
+
+    /// Creates a channel that receives a [`TransformChange`] whenever the world transform of `transform` changes.
+    ///
+    /// The observer is removed when the receiver is dropped.
+    pub fn subscribe(
+        &mut self,
+        transform: &Pearl<BobaTransform>,
+    ) -> Result<(ObserverId, Receiver<TransformChange>), PearlError> {
+        let (sender, receiver) = channel();
+        let id = self.add(transform, Observer::Channel(sender))?;
+        Ok((id, receiver))
+    }
+
+    /// Removes an observer, returning `false` if it did not exist
+    pub fn unobserve(&mut self, id: ObserverId) -> bool {
+        let Some(index) = self
+            .entries
+            .values()
+            .position(|entry| entry.observers.contains_key(&id))
+        else {
+            return false;
+        };
+
+        let entry = &mut self.entries[index];
+        entry.observers.shift_remove(&id);
+        if entry.observers.is_empty() {
+            self.entries.shift_remove_index(index);
+        }
+        true
+    }
+
+    /// Returns `true` if `transform` has any observers
+    pub fn is_observed(&self, transform: &Pearl<BobaTransform>) -> bool {
+        self.entries.contains_key(transform.id())
+    }
+
+    /// The number of observed transforms
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Notifies observers of every transform whose world matrix changed since the last call.
+    ///
+    /// Destroyed transforms and dropped channels are removed.
+    pub fn notify(&mut self) {
+        self.entries.retain(|_, entry| {
+            let Some(transform) = entry.transform.upgrade() else {
+                return false;
+            };
+
+            let (stamp, matrix) = match transform.borrow() {
+                Ok(data) => match data.world_change_stamp() {
+                    stamp if stamp == entry.stamp => return true,
+                    stamp => (stamp, data.world_matrix()),
+                },
+                Err(PearlError::Destroyed) => return false,
+                Err(e) => {
+                    warn!("Skipping transform observers, because the transform could not be borrowed. Error: {e}");
+                    return true;
+                }
+            };
+
+            entry.stamp = stamp;
+            if matrix == entry.matrix {
+                return true;
+            }
+
+            let change = TransformChange {
+                transform,
+                old: entry.matrix,
+                new: matrix,
+            };
+            entry.matrix = matrix;
+            entry.observers.retain(|_, observer| match observer {
+                Observer::Callback(callback) => {
+                    callback(&change);
+                    true
+                }
+                Observer::Channel(sender) => sender.send(change.clone()).is_ok(),
+            });
+
+            !entry.observers.is_empty()
+        });
+    }
+
+    fn add(
+        &mut self,
+        transform: &Pearl<BobaTransform>,
+        observer: Observer,
+    ) -> Result<ObserverId, PearlError> {
+        let id = ObserverId(self.next_id);
+        self.next_id += 1;
+
+        let entry = match self.entries.entry(*transform.id()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let data = transform.borrow()?;
+                entry.insert(ObservedTransform {
+                    transform: transform.downgrade(),
+                    stamp: data.world_change_stamp(),
+                    matrix: data.world_matrix(),
+                    observers: IndexMap::new(),
+                })
+            }
+        };
+
+        entry.observers.insert(id, observer);
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use boba_core::{BobaStage, PearlRegistry};
+    use glam::Vec3;
+
+    use crate::pearls::SetTransformParent;
+
+    use super::*;
+
+    #[test]
+    fn callbacks_and_channels() {
+        let transform = Pearl::wrap(BobaTransform::default());
+        let mut observers = TransformObservers::default();
+
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let recorded = changes.clone();
+        let callback = observers
+            .observe(&transform, move |change| {
+                recorded.borrow_mut().push((change.old, change.new))
+            })
+            .unwrap();
+        let (_, receiver) = observers.subscribe(&transform).unwrap();
+        assert_eq!(observers.len(), 1);
+
+        // nothing moved yet
+        observers.notify();
+        assert!(changes.borrow().is_empty());
+
+        // several moves between checks are reported once
+        transform.borrow_mut().unwrap().translate(Vec3::X);
+        transform.borrow_mut().unwrap().translate(Vec3::X);
+        observers.notify();
+        let expected = (Mat4::IDENTITY, Mat4::from_translation(Vec3::X * 2.));
+        assert_eq!(changes.borrow().as_slice(), &[expected]);
+        let change = receiver.try_recv().unwrap();
+        assert_eq!((change.old, change.new), expected);
+        assert!(change.transform == transform);
+
+        // setting the same value again is not a change
+        transform
+            .borrow_mut()
+            .unwrap()
+            .set_local_position(Vec3::X * 2.);
+        observers.notify();
+        assert_eq!(changes.borrow().len(), 1);
+
+        // dropped receivers and removed callbacks stop observing
+        assert!(observers.unobserve(callback));
+        assert!(!observers.unobserve(callback));
+        drop(receiver);
+        transform.borrow_mut().unwrap().translate(Vec3::Y);
+        observers.notify();
+        assert!(observers.is_empty());
+    }
+
+    #[test]
+    fn inherited_changes() {
+        let parent = Pearl::wrap(BobaTransform::default());
+        let mut child = Pearl::wrap(BobaTransform::from_position(Vec3::X));
+        child.set_parent(parent.clone()).unwrap();
+
+        let mut observers = TransformObservers::default();
+        let (_, receiver) = observers.subscribe(&child).unwrap();
+        let mut registry = PearlRegistry::default();
+        let observers = Pearl::wrap(observers);
+        registry.add(observers.clone());
+
+        let mut resources = BobaResources::default();
+        parent
+            .borrow_mut()
+            .unwrap()
+            .set_local_position(Vec3::Y * 3.);
+        BobaPreRender.run(&mut registry, &mut resources).unwrap();
+
+        let change = receiver.try_recv().unwrap();
+        assert_eq!(change.old, Mat4::from_translation(Vec3::X));
+        assert_eq!(change.new, Mat4::from_translation(Vec3::new(1., 3., 0.)));
+        assert!(receiver.try_recv().is_err());
+
+        // destroyed transforms are forgotten
+        child.destroy().unwrap();
+        BobaPreRender.run(&mut registry, &mut resources).unwrap();
+        assert!(observers.borrow().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,110 @@
+use std::{
+    cell::Cell,
+    rc::Rc,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// The most recent change stamp handed out to any transform
+static CHANGE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// The stamp given to every transform on this thread by [`ChangeStamps::mark_all_changed`]
+    static ALL_CHANGED: Cell<u64> = const { Cell::new(0) };
+}
+
+/// The change stamps of a transform and all of its ancestors.
+///
+/// Changing a transform only gives it a new stamp, and its descendants are never visited.
+/// A world value calculated at [`ChangeStamps::current`] is dirty once any stamp in the lineage is newer.
+#[derive(Default)]
+pub struct ChangeStamps {
+    changed: Rc<Cell<u64>>,
+    ancestors: StampLineage,
+}
+
+/// The change stamps of a transform and all of its ancestors, nearest first.
+///
+/// This is handed down to the children of the transform with [`ChangeStamps::set_ancestors`].
+#[derive(Default, Clone)]
+pub struct StampLineage(Vec<Rc<Cell<u64>>>);
+
+impl ChangeStamps {
+    /// The most recent stamp handed out to any transform.
+    ///
+    /// A value calculated now is up to date until a stamp newer than this one is handed out.
+    pub fn current() -> u64 {
+        CHANGE_COUNTER.load(Ordering::Relaxed)
+    }
+
+    /// Gives every transform on the current thread a new stamp, which makes all of them dirty
+    pub fn mark_all_changed() {
+        ALL_CHANGED.with(|changed| changed.set(CHANGE_COUNTER.fetch_add(1, Ordering::Relaxed) + 1));
+    }
+
+    /// Gives the transform a new stamp, which makes it and all of its descendants dirty
+    pub fn mark_changed(&self) {
+        self.changed
+            .set(CHANGE_COUNTER.fetch_add(1, Ordering::Relaxed) + 1);
+    }
+
+    /// The newest stamp of the transform and all of its ancestors
+    pub fn newest(&self) -> u64 {
+        self.ancestors
+            .0
+            .iter()
+            .map(|changed| changed.get())
+            .fold(self.changed.get(), u64::max)
+            .max(ALL_CHANGED.with(Cell::get))
+    }
+
+    /// Returns true if the transform or any of its ancestors changed after `stamp`
+    pub fn changed_since(&self, stamp: u64) -> bool {
+        self.changed.get() > stamp
+            || self.ancestors.0.iter().any(|changed| changed.get() > stamp)
+            || ALL_CHANGED.with(Cell::get) > stamp
+    }
+
+    /// The lineage to hand down to the children of the transform
+    pub fn lineage(&self) -> StampLineage {
+        let mut lineage = Vec::with_capacity(self.ancestors.0.len() + 1);
+        lineage.push(self.changed.clone());
+        lineage.extend(self.ancestors.0.iter().cloned());
+        StampLineage(lineage)
+    }
+
+    /// Replaces the ancestors with the lineage of a new parent, or an empty lineage when there is no parent.
+    ///
+    /// The lineage of the children has to be replaced as well, since it contains these ancestors.
+    pub fn set_ancestors(&mut self, ancestors: StampLineage) {
+        self.ancestors = ancestors;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lineage_changes() {
+        let mut parent = ChangeStamps::default();
+        let mut child = ChangeStamps::default();
+        child.set_ancestors(parent.lineage());
+
+        let calculated = ChangeStamps::current();
+        assert!(!child.changed_since(calculated));
+
+        parent.mark_changed();
+        assert!(child.changed_since(calculated));
+        assert!(child.newest() > calculated);
+
+        let calculated = ChangeStamps::current();
+        parent.set_ancestors(StampLineage::default());
+        child.set_ancestors(StampLineage::default());
+        parent.mark_changed();
+        assert!(!child.changed_since(calculated));
+
+        ChangeStamps::mark_all_changed();
+        assert!(child.changed_since(calculated));
+        assert!(parent.newest() > calculated);
+    }
+}
@@ -1,23 +1,65 @@
-use boba_core::{Pearl, PearlId, PearlMutError};
-use glam::{Mat4, Quat, Vec3, Vec4};
-use indexmap::IndexSet;
+use std::cell::Cell;
+
+use boba_core::{Pearl, PearlError, PearlMutError};
+use glam::{DVec3, Mat3, Mat4, Quat, Vec3, Vec4};
 use log::error;
-use thiserror::Error;
 
-pub struct BobaTransform {
-    world_position: Vec3,
-    world_rotation: Quat,
+use crate::{
+    geometry::{Aabb, BoundingSphere},
+    large_world::world_origin,
+};
+
+use super::{ChangeStamps, HierarchyTransform, TransformLinks};
+
+/// The world space values of a transform, cached until the transform or one of its parents changes
+#[derive(Clone, Copy)]
+struct WorldCache {
+    calculated: u64,
+    parent_matrix: Mat4,
+    world_matrix: Mat4,
+    position: Vec3,
+    rotation: Quat,
     lossy_scale: Vec3,
+}
 
+impl WorldCache {
+    fn new(parent_matrix: Mat4, local_matrix: Mat4) -> Self {
+        let world_matrix = parent_matrix * local_matrix;
+        let (lossy_scale, rotation, position) = world_matrix.to_scale_rotation_translation();
+        Self {
+            calculated: ChangeStamps::current(),
+            parent_matrix,
+            world_matrix,
+            position,
+            rotation,
+            lossy_scale,
+        }
+    }
+}
+
+/// A position, rotation and scale that can be arranged in a hierarchy.
+///
+/// Changing a transform only stamps it as changed, which marks it and all of its descendants as dirty.
+/// World space values are recalculated the next time they are read.
+///
+/// The position of a root transform is relative to the [`FloatingOrigin`](crate::large_world::FloatingOrigin),
+/// so every root is shifted back whenever the origin moves.
+pub struct BobaTransform {
     local_position: Vec3,
     local_rotation: Quat,
     local_scale: Vec3,
-
-    parent_matrix: Mat4,
     local_matrix: Mat4,
 
-    parent: Option<Pearl<BobaTransform>>,
-    children: IndexSet<Pearl<BobaTransform>>,
+    /// The world origin that the local position was set relative to.
+    /// This only matters for roots, and for transforms whose parent was dropped.
+    origin: DVec3,
+    /// The final world matrix of a dropped parent, relative to `origin`
+    last_parent_matrix: Mat4,
+
+    world: Cell<WorldCache>,
+    stamps: ChangeStamps,
+
+    links: TransformLinks<BobaTransform>,
 }
 
 impl Default for BobaTransform {
@@ -41,7 +83,7 @@ impl BobaTransform {
 
     pub fn from_position_look_at(position: Vec3, look: Vec3) -> Self {
         let mut new = Self::new(position, Quat::IDENTITY, Vec3::ONE);
-        new.look_at(look);
+        new.look_at(look, Vec3::Y);
         new
     }
 
@@ -49,36 +91,35 @@ impl BobaTransform {
         let matrix = Mat4::from_scale_rotation_translation(scale, rotation, position);
 
         Self {
-            world_position: position,
-            world_rotation: rotation,
-            lossy_scale: scale,
-
             local_position: position,
             local_rotation: rotation,
             local_scale: scale,
-
-            parent_matrix: Mat4::IDENTITY,
             local_matrix: matrix,
 
-            parent: None,
-            children: Default::default(),
+            origin: world_origin().unwrap_or_default(),
+            last_parent_matrix: Mat4::IDENTITY,
+
+            world: Cell::new(WorldCache::new(Mat4::IDENTITY, matrix)),
+            stamps: Default::default(),
+
+            links: Default::default(),
         }
     }
 
     pub fn world_position(&self) -> Vec3 {
-        self.world_position
+        self.world().position
     }
 
     pub fn world_rotation(&self) -> Quat {
-        self.world_rotation
+        self.world().rotation
     }
 
     pub fn lossy_scale(&self) -> Vec3 {
-        self.lossy_scale
+        self.world().lossy_scale
     }
 
     pub fn local_position(&self) -> Vec3 {
-        self.local_position
+        self.local_position + self.root_offset()
     }
 
     pub fn local_rotation(&self) -> Quat {
@@ -90,145 +131,691 @@ impl BobaTransform {
     }
 
     pub fn world_matrix(&self) -> Mat4 {
-        self.parent_matrix * self.local_matrix
+        self.world().world_matrix
     }
 
     pub fn local_matrix(&self) -> Mat4 {
-        self.local_matrix
+        let mut matrix = self.local_matrix;
+        matrix.w_axis += self.root_offset().extend(0.);
+        matrix
+    }
+
+    /// Gets the parent of this transform, if it has one that is still alive
+    pub fn parent(&self) -> Option<Pearl<BobaTransform>> {
+        self.links.parent()
+    }
+
+    /// Returns `true` if the transform was never given a parent, or was removed from its parent.
+    ///
+    /// The local position of a root is relative to the world origin.
+    pub(crate) fn is_root(&self) -> bool {
+        self.links.parent_link().is_none()
+    }
+
+    /// Iterates over the children of this transform in sibling order.
+    ///
+    /// Children that were destroyed without using [`SetTransformParent`] are only removed when the hierarchy changes.
+    pub fn children(&self) -> impl Iterator<Item = &Pearl<BobaTransform>> {
+        self.links.children()
+    }
+
+    /// Gets the child at `index` in sibling order
+    pub fn child(&self, index: usize) -> Option<&Pearl<BobaTransform>> {
+        self.links.child(index)
+    }
+
+    pub fn child_count(&self) -> usize {
+        self.links.child_count()
+    }
+
+    /// The direction the transform is facing in world space (its local `+Z` axis)
+    pub fn forward(&self) -> Vec3 {
+        self.world_rotation() * Vec3::Z
+    }
+
+    /// The right direction when looking along [`forward`](Self::forward) in world space (its local `-X` axis)
+    pub fn right(&self) -> Vec3 {
+        self.world_rotation() * Vec3::NEG_X
+    }
+
+    /// The up direction of the transform in world space (its local `+Y` axis)
+    pub fn up(&self) -> Vec3 {
+        self.world_rotation() * Vec3::Y
+    }
+
+    /// The newest change stamp of this transform and all of its ancestors.
+    ///
+    /// The stamp grows whenever the world transform may have changed, including changes inherited from a parent,
+    /// so comparing it with an earlier stamp is a cheap way to detect movement without recalculating anything.
+    pub fn world_change_stamp(&self) -> u64 {
+        self.stamps.newest()
+    }
+
+    /// Transforms `point` from the local space of this transform into world space
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        self.world_matrix().transform_point3(point)
+    }
+
+    /// Transforms `point` from world space into the local space of this transform
+    pub fn inverse_transform_point(&self, point: Vec3) -> Vec3 {
+        self.world_matrix().inverse().transform_point3(point)
+    }
+
+    /// Transforms `bounds` from the local space of this transform into world space
+    pub fn world_bounds(&self, bounds: &Aabb) -> Aabb {
+        bounds.transformed(&self.world_matrix())
+    }
+
+    /// Transforms `sphere` from the local space of this transform into world space
+    pub fn world_bounding_sphere(&self, sphere: &BoundingSphere) -> BoundingSphere {
+        sphere.transformed(&self.world_matrix())
     }
 
     /// Sets the local position of the transform.
     ///
-    /// Also recalculates the world position, and distributes the changes to
-    /// all available children.
+    /// Marks the world position of this transform and all of its descendants as dirty.
     pub fn set_local_position(&mut self, position: Vec3) {
+        self.settle_origin();
         self.local_position = position;
         self.local_matrix.w_axis = Vec4::from((self.local_position, 1.0));
-        self.calculate_world_transforms();
-        self.apply_matrix_to_children();
+        self.stamps.mark_changed();
     }
 
     /// Sets the local rotation of the transform.
     ///
-    /// Also recalculates the world rotation, and distributes the changes to
-    /// all available children.
+    /// Marks the world rotation of this transform and all of its descendants as dirty.
     pub fn set_local_rotation(&mut self, rotation: Quat) {
+        self.settle_origin();
         self.local_rotation = rotation;
+        self.calculate_local_matrix();
+    }
+
+    /// Sets the local scale of the transform.
+    ///
+    /// Marks the lossy scale of this transform and all of its descendants as dirty.
+    pub fn set_local_scale(&mut self, scale: Vec3) {
+        self.settle_origin();
+        self.local_scale = scale;
+        self.calculate_local_matrix();
+    }
+
+    /// Sets the local position, rotation and scale of the transform at once.
+    pub fn set_local(&mut self, position: Vec3, rotation: Quat, scale: Vec3) {
+        self.settle_origin();
+        self.local_position = position;
+        self.local_rotation = rotation;
+        self.local_scale = scale;
+        self.calculate_local_matrix();
+    }
+
+    /// Sets the world position of the transform.
+    ///
+    /// The local position is calculated relative to the parent.
+    pub fn set_world_position(&mut self, position: Vec3) {
+        let local = self
+            .world()
+            .parent_matrix
+            .inverse()
+            .transform_point3(position);
+        self.set_local_position(local);
+    }
+
+    /// Sets the world rotation of the transform.
+    ///
+    /// The local rotation is calculated relative to the parent.
+    pub fn set_world_rotation(&mut self, rotation: Quat) {
+        let (_, parent_rotation, _) = self.world().parent_matrix.to_scale_rotation_translation();
+        self.set_local_rotation((parent_rotation.inverse() * rotation).normalize());
+    }
+
+    /// Sets the world scale of the transform.
+    ///
+    /// The local scale is calculated by dividing out the scale of the parent.
+    /// Like [`lossy_scale`](Self::lossy_scale), this is only exact when no parent is both rotated and non uniformly scaled.
+    pub fn set_world_scale(&mut self, scale: Vec3) {
+        let (parent_scale, _, _) = self.world().parent_matrix.to_scale_rotation_translation();
+        self.set_local_scale(scale / parent_scale);
+    }
+
+    /// Moves the transform by `offset` in world space.
+    pub fn translate(&mut self, offset: Vec3) {
+        self.set_world_position(self.world_position() + offset);
+    }
+
+    /// Rotates the transform by `rotation` in world space.
+    pub fn rotate(&mut self, rotation: Quat) {
+        self.set_world_rotation(rotation * self.world_rotation());
+    }
+
+    /// Rotates the transform by `rotation` around the world space `point`.
+    ///
+    /// Both the position and the rotation of the transform are changed.
+    pub fn rotate_around(&mut self, point: Vec3, rotation: Quat) {
+        let position = point + rotation * (self.world_position() - point);
+        self.set_world_position(position);
+        self.rotate(rotation);
+    }
+
+    /// Rotates the transform so that [`forward`](Self::forward) points at the world space `target`,
+    /// and [`up`](Self::up) points as close to `up` as possible.
+    ///
+    /// Does nothing if `target` is at the world position of the transform.
+    pub fn look_at(&mut self, target: Vec3, up: Vec3) {
+        if let Some(rotation) = look_rotation(target - self.world_position(), up) {
+            self.set_world_rotation(rotation);
+        }
+    }
+
+    /// Returns `true` if the transform has a parent that was not dropped or destroyed
+    fn has_live_parent(&self) -> bool {
+        match self.links.parent() {
+            Some(parent) => !matches!(parent.is_destroyed(), Ok(true)),
+            None => false,
+        }
+    }
+
+    /// The offset from `origin` to the current world origin.
+    ///
+    /// This is zero when there is no [`FloatingOrigin`](crate::large_world::FloatingOrigin),
+    /// or when the transform is moved by a live parent instead.
+    fn origin_offset(&self) -> Vec3 {
+        match world_origin() {
+            Some(world_origin) if !self.has_live_parent() => (self.origin - world_origin).as_vec3(),
+            _ => Vec3::ZERO,
+        }
+    }
+
+    /// The origin offset of a root, which is added to its local position
+    fn root_offset(&self) -> Vec3 {
+        match self.is_root() {
+            true => self.origin_offset(),
+            false => Vec3::ZERO,
+        }
+    }
+
+    /// Makes the local position, or the matrix of a dropped parent, relative to the current world origin
+    /// without moving the transform
+    fn settle_origin(&mut self) {
+        let offset = self.origin_offset();
+        self.origin = world_origin().unwrap_or_default();
+        if offset == Vec3::ZERO {
+            return;
+        }
+
+        match self.links.parent_link() {
+            None => {
+                self.local_position += offset;
+                self.local_matrix.w_axis = Vec4::from((self.local_position, 1.0));
+            }
+            Some(_) => {
+                self.last_parent_matrix = Mat4::from_translation(offset) * self.last_parent_matrix
+            }
+        }
+    }
+
+    fn calculate_local_matrix(&mut self) {
         self.local_matrix = Mat4::from_scale_rotation_translation(
             self.local_scale,
             self.local_rotation,
             self.local_position,
         );
-        self.calculate_world_transforms();
-        self.apply_matrix_to_children();
+        self.stamps.mark_changed();
     }
 
-    pub fn look_at(&mut self, point: Vec3) {
-        let Some(look_vector) = (point - self.local_position).try_normalize() else {
-            return;
-        };
+    fn set_local_matrix(&mut self, matrix: Mat4) {
+        self.settle_origin();
+        (self.local_scale, self.local_rotation, self.local_position) =
+            matrix.to_scale_rotation_translation();
+        self.calculate_local_matrix();
+    }
 
-        let dot = Vec3::Z.dot(look_vector);
-        if (dot + 1.).abs() < 0.000001 {
-            self.local_rotation = Quat::from_axis_angle(Vec3::Y, 0.);
-            return;
-        }
-        if (dot - 1.).abs() < 0.000001 {
-            self.local_rotation = Quat::from_axis_angle(Vec3::Y, 180.);
-            return;
+    /// Gets the cached world values, recalculating them if the transform is dirty
+    fn world(&self) -> WorldCache {
+        let world = self.world.get();
+        if !self.stamps.changed_since(world.calculated) {
+            return world;
         }
 
-        let angle = dot.acos();
-        let axis = Vec3::Z.cross(look_vector).normalize();
-        self.set_local_rotation(Quat::from_axis_angle(axis, angle));
+        // a parent that was dropped left its final matrix when it was dropped, which moves with the world origin
+        let last_parent = || Mat4::from_translation(self.origin_offset()) * self.last_parent_matrix;
+        let parent = self.links.parent_link().map(|parent| parent.upgrade());
+        let parent_matrix = match parent {
+            None => Mat4::IDENTITY,
+            Some(None) => last_parent(),
+            Some(Some(parent)) => match parent.borrow() {
+                Ok(parent) => parent.world_matrix(),
+                Err(PearlError::Destroyed) => last_parent(),
+                Err(e) => {
+                    error!("Could not sync with parent transform due to: {e}");
+                    return WorldCache::new(world.parent_matrix, self.local_matrix());
+                }
+            },
+        };
+
+        // roots are moved by the world origin instead of a parent
+        let world = WorldCache::new(parent_matrix, self.local_matrix());
+        self.world.set(world);
+        world
+    }
+}
+
+impl HierarchyTransform for BobaTransform {
+    type ParentWorld = Mat4;
+    const ROOT: Mat4 = Mat4::IDENTITY;
+
+    fn links(&self) -> &TransformLinks<Self> {
+        &self.links
+    }
+
+    fn links_mut(&mut self) -> &mut TransformLinks<Self> {
+        &mut self.links
+    }
+
+    fn stamps(&self) -> &ChangeStamps {
+        &self.stamps
     }
 
-    fn calculate_world_transforms(&mut self) {
-        (self.lossy_scale, self.world_rotation, self.world_position) =
-            self.world_matrix().to_scale_rotation_translation();
+    fn stamps_mut(&mut self) -> &mut ChangeStamps {
+        &mut self.stamps
     }
 
-    fn apply_matrix_to_children(&mut self) {
-        for child in self.children.iter() {
+    fn parent_world(&self) -> Mat4 {
+        self.world_matrix()
+    }
+
+    fn prepare_relink(&mut self, parent_matrix: Mat4, keep_world: bool) {
+        let world_matrix = self.world_matrix();
+        self.settle_origin();
+        if keep_world {
+            self.set_local_matrix(parent_matrix.inverse() * world_matrix);
+        }
+    }
+}
+
+impl Drop for BobaTransform {
+    fn drop(&mut self) {
+        if self.links.child_count() == 0 {
+            return;
+        }
+
+        // children only hold a weak link, so they have to be given the final matrix before it is gone
+        let matrix = self.world_matrix();
+        for child in self.links.children() {
             match child.borrow_mut() {
-                Err(e) => {
-                    error!("Could not sync child transform due to: {e}");
-                    continue;
-                }
-                Ok(mut transform) => {
-                    transform.parent_matrix = self.world_matrix();
-                    transform.calculate_world_transforms();
-                    transform.apply_matrix_to_children();
+                Ok(mut child) => {
+                    child.origin = world_origin().unwrap_or_default();
+                    child.last_parent_matrix = matrix;
+                    child.world.set(WorldCache::new(matrix, child.local_matrix));
                 }
+                Err(PearlMutError::Destroyed) => (),
+                Err(e) => error!("Could not sync child transform due to: {e}"),
             }
         }
     }
 }
 
-#[derive(Debug, Error)]
-pub enum SetParentError {
-    #[error("A parent child relationship was recursive")]
-    RecursionError,
-    #[error("There was an error accessing one a pearl. Error: {0}")]
-    PearlError(PearlMutError),
-}
+/// Creates a rotation that points the forward axis along `direction`, keeping the up axis close to `up`.
+///
+/// Returns `None` if `direction` is zero. If `up` is parallel to `direction`, any perpendicular up axis is used.
+pub(crate) fn look_rotation(direction: Vec3, up: Vec3) -> Option<Quat> {
+    let forward = direction.try_normalize()?;
+    let x_axis = match up.cross(forward).try_normalize() {
+        Some(x_axis) => x_axis,
+        None => forward.any_orthonormal_vector(),
+    };
 
-pub trait SetTransformParent {
-    fn set_parent(&mut self, parent: Pearl<BobaTransform>) -> Result<(), SetParentError>;
+    let y_axis = forward.cross(x_axis);
+    Some(Quat::from_mat3(&Mat3::from_cols(x_axis, y_axis, forward)).normalize())
 }
 
-impl SetTransformParent for Pearl<BobaTransform> {
-    fn set_parent(&mut self, parent: Pearl<BobaTransform>) -> Result<(), SetParentError> {
-        if self.id() == parent.id() {
-            return Err(SetParentError::RecursionError);
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use boba_core::Pearl;
+    use glam::{Quat, Vec3};
+
+    use crate::pearls::{SetParentError, SetTransformParent};
+
+    use super::BobaTransform;
+
+    const EPSILON: f32 = 0.0001;
+
+    fn assert_vec(a: Vec3, b: Vec3) {
+        assert!(a.abs_diff_eq(b, EPSILON), "{a} != {b}");
+    }
+
+    fn assert_quat(a: Quat, b: Quat) {
+        assert!(
+            a.abs_diff_eq(b, EPSILON) || a.abs_diff_eq(-b, EPSILON),
+            "{a} != {b}"
+        );
+    }
+
+    /// Creates a parent at `(0, 0, 10)` rotated a quarter turn around `Y` and scaled by 2, with a child at `(1, 0, 0)`
+    fn hierarchy() -> (Pearl<BobaTransform>, Pearl<BobaTransform>) {
+        let parent = Pearl::wrap(BobaTransform::default());
+        let mut child = Pearl::wrap(BobaTransform::from_position(Vec3::X));
+        child.set_parent(parent.clone()).unwrap();
+        parent.borrow_mut().unwrap().set_local(
+            Vec3::Z * 10.,
+            Quat::from_rotation_y(FRAC_PI_2),
+            Vec3::splat(2.),
+        );
+
+        (parent, child)
+    }
+
+    #[test]
+    fn local_setters() {
+        let mut transform = BobaTransform::default();
+        transform.set_local_position(Vec3::ONE);
+        transform.set_local_rotation(Quat::from_rotation_x(1.));
+        transform.set_local_scale(Vec3::splat(3.));
+
+        assert_vec(transform.world_position(), Vec3::ONE);
+        assert_quat(transform.world_rotation(), Quat::from_rotation_x(1.));
+        assert_vec(transform.lossy_scale(), Vec3::splat(3.));
+        assert_vec(transform.transform_point(Vec3::ZERO), Vec3::ONE);
+    }
+
+    #[test]
+    fn child_world() {
+        let (_, child) = hierarchy();
+        let child = child.borrow().unwrap();
+
+        assert_vec(child.world_position(), Vec3::new(0., 0., 8.));
+        assert_quat(child.world_rotation(), Quat::from_rotation_y(FRAC_PI_2));
+        assert_vec(child.lossy_scale(), Vec3::splat(2.));
+    }
+
+    #[test]
+    fn world_setters() {
+        let (_, child) = hierarchy();
+        let mut child = child.borrow_mut().unwrap();
+
+        child.set_world_position(Vec3::new(2., 0., 10.));
+        assert_vec(child.world_position(), Vec3::new(2., 0., 10.));
+        assert_vec(child.local_position(), Vec3::new(0., 0., 1.));
+
+        child.set_world_rotation(Quat::IDENTITY);
+        assert_quat(child.world_rotation(), Quat::IDENTITY);
+        assert_quat(child.local_rotation(), Quat::from_rotation_y(-FRAC_PI_2));
+
+        child.set_world_scale(Vec3::ONE);
+        assert_vec(child.lossy_scale(), Vec3::ONE);
+        assert_vec(child.local_scale(), Vec3::splat(0.5));
+        assert_vec(child.world_position(), Vec3::new(2., 0., 10.));
+    }
+
+    #[test]
+    fn translate_and_rotate() {
+        let (_, child) = hierarchy();
+        let mut child = child.borrow_mut().unwrap();
+
+        child.translate(Vec3::Y);
+        assert_vec(child.world_position(), Vec3::new(0., 1., 8.));
+
+        child.rotate(Quat::from_rotation_y(-FRAC_PI_2));
+        assert_quat(child.world_rotation(), Quat::IDENTITY);
+
+        child.rotate_around(Vec3::new(0., 1., 10.), Quat::from_rotation_y(FRAC_PI_2));
+        assert_vec(child.world_position(), Vec3::new(-2., 1., 10.));
+        assert_quat(child.world_rotation(), Quat::from_rotation_y(FRAC_PI_2));
+    }
+
+    #[test]
+    fn directions() {
+        let mut transform = BobaTransform::default();
+        assert_vec(transform.forward(), Vec3::Z);
+        assert_vec(transform.up(), Vec3::Y);
+        assert_vec(transform.right(), Vec3::NEG_X);
+
+        transform.set_local_rotation(Quat::from_rotation_y(FRAC_PI_2));
+        assert_vec(transform.forward(), Vec3::X);
+        assert_vec(transform.up(), Vec3::Y);
+        assert_vec(transform.right(), Vec3::Z);
+    }
+
+    #[test]
+    fn point_transforms() {
+        let (_, child) = hierarchy();
+        let child = child.borrow().unwrap();
+
+        let world = child.transform_point(Vec3::X);
+        assert_vec(world, Vec3::new(0., 0., 6.));
+        assert_vec(child.inverse_transform_point(world), Vec3::X);
+    }
+
+    #[test]
+    fn look_at() {
+        let mut transform = BobaTransform::from_position(Vec3::ONE);
+        transform.look_at(Vec3::new(1., 1., -5.), Vec3::Y);
+        assert_vec(transform.forward(), Vec3::NEG_Z);
+        assert_vec(transform.up(), Vec3::Y);
+
+        transform.look_at(Vec3::new(1., 10., 1.), Vec3::Y);
+        assert_vec(transform.forward(), Vec3::Y);
+
+        let look = BobaTransform::from_position_look_at(Vec3::ZERO, Vec3::new(1., 1., 0.));
+        assert_vec(look.forward(), Vec3::new(1., 1., 0.).normalize());
+        assert!(look.right().y.abs() < EPSILON);
+    }
+
+    #[test]
+    fn look_at_in_hierarchy() {
+        let (_, child) = hierarchy();
+        let mut child = child.borrow_mut().unwrap();
+
+        child.look_at(Vec3::new(0., 0., 20.), Vec3::Y);
+        assert_vec(child.forward(), Vec3::Z);
+        assert_vec(child.up(), Vec3::Y);
+    }
+
+    #[test]
+    fn parent_matrix_on_attach() {
+        let parent = Pearl::wrap(BobaTransform::from_position(Vec3::Y));
+        let mut child = Pearl::wrap(BobaTransform::from_position(Vec3::X));
+        child.set_parent(parent.clone()).unwrap();
+
+        let child_data = child.borrow().unwrap();
+        assert_vec(child_data.world_position(), Vec3::X + Vec3::Y);
+        assert!(child_data.parent() == Some(parent.clone()));
+        assert!(parent.borrow().unwrap().child(0) == Some(&child));
+    }
+
+    #[test]
+    fn keep_world() {
+        let (parent, mut child) = hierarchy();
+        let world = child.borrow().unwrap().world_matrix();
+
+        child.remove_parent_keep_world().unwrap();
+        assert!(child
+            .borrow()
+            .unwrap()
+            .world_matrix()
+            .abs_diff_eq(world, EPSILON));
+        assert!(child.borrow().unwrap().parent().is_none());
+        assert!(parent.borrow().unwrap().child_count() == 0);
+
+        child.set_parent_keep_world(parent.clone()).unwrap();
+        assert!(child
+            .borrow()
+            .unwrap()
+            .world_matrix()
+            .abs_diff_eq(world, EPSILON));
+        assert_vec(child.borrow().unwrap().local_position(), Vec3::X);
+
+        child.remove_parent().unwrap();
+        assert_vec(child.borrow().unwrap().world_position(), Vec3::X);
+    }
+
+    #[test]
+    fn reparent() {
+        let (parent, mut child) = hierarchy();
+        let other = Pearl::wrap(BobaTransform::from_position(Vec3::Y));
+
+        child.set_parent(other.clone()).unwrap();
+        assert!(parent.borrow().unwrap().child_count() == 0);
+        assert!(other.borrow().unwrap().child_count() == 1);
+        assert_vec(child.borrow().unwrap().world_position(), Vec3::X + Vec3::Y);
+
+        let mut other = other;
+        assert!(matches!(
+            other.set_parent(child.clone()),
+            Err(SetParentError::RecursionError)
+        ));
+    }
+
+    #[test]
+    fn sibling_order() {
+        let parent = Pearl::wrap(BobaTransform::default());
+        let mut children = (0..4)
+            .map(|_| Pearl::wrap(BobaTransform::default()))
+            .collect::<Vec<_>>();
+        for child in children.iter_mut() {
+            child.set_parent(parent.clone()).unwrap();
         }
 
-        let mut self_data = match self.borrow_mut() {
-            Ok(d) => d,
-            Err(e) => return Err(SetParentError::PearlError(e)),
-        };
+        assert!(children[2].sibling_index().unwrap() == Some(2));
+        children[3].set_sibling_index(0).unwrap();
+        children[0].set_sibling_index(10).unwrap();
+        children[1].remove_parent().unwrap();
+
+        let order = parent
+            .borrow()
+            .unwrap()
+            .children()
+            .cloned()
+            .collect::<Vec<_>>();
+        assert!(
+            order
+                == vec![
+                    children[3].clone(),
+                    children[2].clone(),
+                    children[0].clone()
+                ]
+        );
+    }
 
-        let mut parent_data = match parent.borrow_mut() {
-            Ok(d) => d,
-            Err(e) => return Err(SetParentError::PearlError(e)),
-        };
+    #[test]
+    fn destroy_cascade() {
+        let root = Pearl::wrap(BobaTransform::default());
+        let (parent, child) = hierarchy();
+        parent.clone().set_parent(root.clone()).unwrap();
 
-        validate_parent_recursive(self.id(), &parent_data)?;
+        parent.destroy_recursive().unwrap();
+        assert!(parent.is_destroyed().unwrap());
+        assert!(child.is_destroyed().unwrap());
+        assert!(root.borrow().unwrap().child_count() == 0);
+    }
 
-        let Some(self_parent) = &self_data.parent else {
-            parent_data.children.insert(self.clone());
-            self_data.parent = Some(parent.clone());
-            return Ok(());
-        };
+    #[test]
+    fn destroy_keep_children() {
+        let root = Pearl::wrap(BobaTransform::from_position(Vec3::Y));
+        let (parent, child) = hierarchy();
+        parent.clone().set_parent(root.clone()).unwrap();
+        let world = child.borrow().unwrap().world_matrix();
+
+        parent.destroy_keep_children().unwrap();
+        assert!(parent.is_destroyed().unwrap());
+        assert!(child.borrow().unwrap().parent() == Some(root.clone()));
+        assert!(child
+            .borrow()
+            .unwrap()
+            .world_matrix()
+            .abs_diff_eq(world, EPSILON));
+        assert!(root.borrow().unwrap().child(0) == Some(&child));
+    }
 
-        let mut self_parent_data = match self_parent.borrow_mut() {
-            Ok(d) => d,
-            Err(e) => return Err(SetParentError::PearlError(e)),
-        };
+    #[test]
+    fn weak_parent() {
+        let (parent, child) = hierarchy();
+        let weak = parent.downgrade();
+        drop(parent);
 
-        self_parent_data.children.remove(self);
+        assert!(weak.upgrade().is_none());
+        assert!(child.borrow().unwrap().parent().is_none());
+    }
+
+    #[test]
+    fn parent_changed_then_dropped() {
+        let root = Pearl::wrap(BobaTransform::default());
+        let mut parent = Pearl::wrap(BobaTransform::default());
+        let mut child = Pearl::wrap(BobaTransform::from_position(Vec3::X));
+        parent.set_parent(root.clone()).unwrap();
+        child.set_parent(parent.clone()).unwrap();
+        assert_vec(child.borrow().unwrap().world_position(), Vec3::X);
+
+        parent
+            .borrow_mut()
+            .unwrap()
+            .set_local_position(Vec3::Z * 10.);
+        drop(parent);
+        assert_vec(
+            child.borrow().unwrap().world_position(),
+            Vec3::new(1., 0., 10.),
+        );
 
-        drop(self_parent_data);
-        parent_data.children.insert(self.clone());
-        self_data.parent = Some(parent.clone());
+        // the root is the last owner of the parent, so both are dropped together
+        root.borrow_mut().unwrap().set_local_position(Vec3::Y);
+        drop(root);
+        let mut child = child.borrow_mut().unwrap();
+        assert_vec(child.world_position(), Vec3::new(1., 1., 10.));
 
-        Ok(())
+        child.set_local_position(Vec3::NEG_X);
+        assert_vec(child.world_position(), Vec3::new(-1., 1., 10.));
     }
-}
 
-fn validate_parent_recursive(id: &PearlId, target: &BobaTransform) -> Result<(), SetParentError> {
-    let Some(parent) = &target.parent else {
-        return Ok(());
-    };
+    #[test]
+    fn lazy_propagation() {
+        let root = Pearl::wrap(BobaTransform::default());
+        let mut chain = vec![root.clone()];
+        for _ in 0..10 {
+            let mut child = Pearl::wrap(BobaTransform::from_position(Vec3::X));
+            child.set_parent(chain.last().unwrap().clone()).unwrap();
+            chain.push(child);
+        }
 
-    if id == parent.id() {
-        return Err(SetParentError::RecursionError);
-    };
+        let leaf = chain.last().unwrap().clone();
+        assert_vec(leaf.borrow().unwrap().world_position(), Vec3::X * 10.);
 
-    let parent_data = match parent.borrow_mut() {
-        Ok(d) => d,
-        Err(e) => return Err(SetParentError::PearlError(e)),
-    };
+        for i in 1..=5 {
+            root.borrow_mut()
+                .unwrap()
+                .set_local_position(Vec3::Y * i as f32);
+        }
+        assert_vec(
+            leaf.borrow().unwrap().world_position(),
+            Vec3::X * 10. + Vec3::Y * 5.,
+        );
+        assert_vec(
+            chain[5].borrow().unwrap().world_position(),
+            Vec3::new(5., 5., 0.),
+        );
+    }
 
-    validate_parent_recursive(id, &parent_data)
+    #[test]
+    fn move_parent_while_child_borrowed() {
+        let (parent, child) = hierarchy();
+        let child_data = child.borrow_mut().unwrap();
+        parent.borrow_mut().unwrap().set_local_position(Vec3::ZERO);
+        assert_vec(child_data.world_position(), Vec3::new(0., 0., -2.));
+    }
+
+    #[test]
+    fn world_bounds() {
+        use crate::geometry::{Aabb, BoundingSphere};
+
+        let (_parent, child) = hierarchy();
+        let child = child.borrow().unwrap();
+
+        let bounds = child.world_bounds(&Aabb::new(-Vec3::ONE, Vec3::ONE));
+        assert_vec(bounds.min, Vec3::new(-2., -2., 6.));
+        assert_vec(bounds.max, Vec3::new(2., 2., 10.));
+
+        let sphere = child.world_bounding_sphere(&BoundingSphere::new(Vec3::ZERO, 1.));
+        assert_vec(sphere.center, Vec3::new(0., 0., 8.));
+        assert!((sphere.radius - 2.).abs() < EPSILON);
+    }
 }
@@ -0,0 +1,419 @@
+use boba_core::{
+    register_pearl_stages, stages::BobaLateUpdate, BobaResources, BobaResult, Pearl, PearlError,
+    PearlId, PearlStage,
+};
+use glam::Vec3;
+use indexmap::IndexMap;
+use log::warn;
+
+use crate::{
+    geometry::{Aabb, BoundingSphere, Frustum, Ray},
+    pearls::BobaTransform,
+};
+
+use super::tree::AabbTree;
+
+struct SpatialEntry {
+    transform: Pearl<BobaTransform>,
+    local_bounds: Aabb,
+    world_bounds: Aabb,
+    leaf: usize,
+}
+
+/// A transform hit by [`SpatialIndex::query_ray`]
+#[derive(Clone)]
+pub struct SpatialRayHit {
+    pub transform: Pearl<BobaTransform>,
+    pub distance: f32,
+}
+
+/// A bounding volume hierarchy over [`BobaTransform`] pearls and their bounds.
+///
+/// Each transform is stored with bounds in its local space. The tree keeps a box around the world bounds
+/// that is grown by a margin, so transforms that move a little do not have to be moved in the tree.
+///
+/// Moved transforms are picked up by [`SpatialIndex::update`], which runs in [`BobaLateUpdate`]
+/// when the index is registered as a pearl. Destroyed transforms are removed during the update.
+pub struct SpatialIndex {
+    margin: f32,
+    tree: AabbTree<PearlId>,
+    entries: IndexMap<PearlId, SpatialEntry>,
+}
+
+register_pearl_stages!(SpatialIndex: BobaLateUpdate);
+
+impl PearlStage<BobaLateUpdate> for SpatialIndex {
+    fn update(pearl: &Pearl<Self>, _: &f32, _: &mut BobaResources) -> BobaResult {
+        pearl.borrow_mut()?.update();
+        Ok(())
+    }
+}
+
+impl Default for SpatialIndex {
+    fn default() -> Self {
+        Self::new(0.1)
+    }
+}
+
+impl SpatialIndex {
+    /// Creates a new index, where bounds in the tree are grown by `margin` on every side
+    pub fn new(margin: f32) -> Self {
+        Self {
+            margin: margin.max(0.),
+            tree: Default::default(),
+            entries: Default::default(),
+        }
+    }
+
+    pub fn margin(&self) -> f32 {
+        self.margin
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, transform: &Pearl<BobaTransform>) -> bool {
+        self.entries.contains_key(transform.id())
+    }
+
+    /// Gets the world bounds of `transform` as of the last update, if it is in the index
+    pub fn world_bounds(&self, transform: &Pearl<BobaTransform>) -> Option<&Aabb> {
+        Some(&self.entries.get(transform.id())?.world_bounds)
+    }
+
+    /// Iterates over every transform in the index
+    pub fn transforms(&self) -> impl Iterator<Item = &Pearl<BobaTransform>> {
+        self.entries.values().map(|entry| &entry.transform)
+    }
+
+    /// Adds `transform` to the index with `local_bounds`, replacing its bounds if it was already added.
+    ///
+    /// Fails if the transform cannot be read.
+    pub fn insert(
+        &mut self,
+        transform: Pearl<BobaTransform>,
+        local_bounds: Aabb,
+    ) -> Result<(), PearlError> {
+        let world_bounds = transform.borrow()?.world_bounds(&local_bounds);
+        if let Some(entry) = self.entries.get_mut(transform.id()) {
+            entry.local_bounds = local_bounds;
+            Self::move_entry(&mut self.tree, self.margin, entry, world_bounds);
+            return Ok(());
+        }
+
+        let id = *transform.id();
+        let leaf = self.tree.insert(world_bounds.grow(self.margin), id);
+        self.entries.insert(
+            id,
+            SpatialEntry {
+                transform,
+                local_bounds,
+                world_bounds,
+                leaf,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Removes `transform` from the index, returning `true` if it was in the index
+    pub fn remove(&mut self, transform: &Pearl<BobaTransform>) -> bool {
+        let Some(entry) = self.entries.swap_remove(transform.id()) else {
+            return false;
+        };
+
+        self.tree.remove(entry.leaf);
+        true
+    }
+
+    /// Reads the world matrix of every transform, and moves the ones that left their box in the tree.
+    ///
+    /// Destroyed transforms are removed. Transforms that are currently borrowed keep their old bounds.
+    pub fn update(&mut self) {
+        let mut destroyed = Vec::new();
+        for (id, entry) in self.entries.iter_mut() {
+            let world_bounds = match entry.transform.borrow() {
+                Ok(transform) => transform.world_bounds(&entry.local_bounds),
+                Err(PearlError::Destroyed) => {
+                    destroyed.push(*id);
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Could not update transform in spatial index. Old bounds will be used. Error: {e}");
+                    continue;
+                }
+            };
+
+            Self::move_entry(&mut self.tree, self.margin, entry, world_bounds);
+        }
+
+        for id in destroyed {
+            if let Some(entry) = self.entries.swap_remove(&id) {
+                self.tree.remove(entry.leaf);
+            }
+        }
+    }
+
+    /// Moves every stored bound by the opposite of `shift`, to keep up with a moved floating origin.
+    ///
+    /// This is usually called from [`FloatingOrigin::on_rebase`](crate::large_world::FloatingOrigin::on_rebase),
+    /// so that queries are right before the next update.
+    pub fn shift_origin(&mut self, shift: Vec3) {
+        self.tree.translate(-shift);
+        for entry in self.entries.values_mut() {
+            entry.world_bounds.min -= shift;
+            entry.world_bounds.max -= shift;
+        }
+    }
+
+    /// Gets every transform whose bounds overlap `aabb`
+    pub fn query_aabb(&self, aabb: &Aabb) -> Vec<Pearl<BobaTransform>> {
+        self.query(|bounds| bounds.intersects(aabb))
+    }
+
+    /// Gets every transform whose bounds are within `radius` of `center`
+    pub fn query_radius(&self, center: Vec3, radius: f32) -> Vec<Pearl<BobaTransform>> {
+        let sphere = BoundingSphere::new(center, radius);
+        self.query(|bounds| bounds.intersects_sphere(&sphere))
+    }
+
+    /// Gets every transform whose bounds are at least partly inside `frustum`
+    pub fn query_frustum(&self, frustum: &Frustum) -> Vec<Pearl<BobaTransform>> {
+        self.query(|bounds| frustum.intersects_aabb(bounds))
+    }
+
+    /// Gets every transform whose bounds are hit by `ray` within `max_distance`, sorted by distance
+    pub fn query_ray(&self, ray: &Ray, max_distance: f32) -> Vec<SpatialRayHit> {
+        let hits_bounds = |bounds: &Aabb| {
+            ray.intersect_aabb(bounds)
+                .filter(|distance| *distance <= max_distance)
+        };
+
+        let mut hits = Vec::new();
+        self.tree.query(
+            |bounds| hits_bounds(bounds).is_some(),
+            |id| {
+                let entry = &self.entries[id];
+                if let Some(distance) = hits_bounds(&entry.world_bounds) {
+                    hits.push(SpatialRayHit {
+                        transform: entry.transform.clone(),
+                        distance,
+                    });
+                }
+            },
+        );
+
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+
+    /// Visits the tree with `overlaps`, then checks the exact world bounds of the leaves
+    fn query(&self, overlaps: impl Fn(&Aabb) -> bool) -> Vec<Pearl<BobaTransform>> {
+        let mut found = Vec::new();
+        self.tree.query(&overlaps, |id| {
+            let entry = &self.entries[id];
+            if overlaps(&entry.world_bounds) {
+                found.push(entry.transform.clone());
+            }
+        });
+        found
+    }
+
+    fn move_entry(
+        tree: &mut AabbTree<PearlId>,
+        margin: f32,
+        entry: &mut SpatialEntry,
+        world_bounds: Aabb,
+    ) {
+        entry.world_bounds = world_bounds;
+        if tree.aabb(entry.leaf).contains(&world_bounds) {
+            return;
+        }
+
+        let id = tree.remove(entry.leaf);
+        entry.leaf = tree.insert(world_bounds.grow(margin), id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use glam::{Mat4, Vec3};
+    use proptest::prelude::*;
+
+    use crate::large_world::FloatingOrigin;
+
+    use super::*;
+
+    fn ids<'a>(pearls: impl IntoIterator<Item = &'a Pearl<BobaTransform>>) -> HashSet<PearlId> {
+        pearls.into_iter().map(|p| *p.id()).collect()
+    }
+
+    fn brute_force(
+        transforms: &[(Pearl<BobaTransform>, Aabb)],
+        overlaps: impl Fn(&Aabb) -> bool,
+    ) -> HashSet<PearlId> {
+        transforms
+            .iter()
+            .filter(|(t, local)| overlaps(&t.borrow().unwrap().world_bounds(local)))
+            .map(|(t, _)| *t.id())
+            .collect()
+    }
+
+    fn vec3(range: f32) -> impl Strategy<Value = Vec3> {
+        (-range..range, -range..range, -range..range).prop_map(|(x, y, z)| Vec3::new(x, y, z))
+    }
+
+    fn bounds() -> impl Strategy<Value = Aabb> {
+        (vec3(1.), (0.1f32..3., 0.1f32..3., 0.1f32..3.))
+            .prop_map(|(c, (x, y, z))| Aabb::from_center_half_extents(c, Vec3::new(x, y, z)))
+    }
+
+    /// Positions and local bounds of transforms, and offsets to move some of them by
+    type Scene = (Vec<(Vec3, Aabb)>, Vec<(usize, Vec3)>);
+
+    fn scene() -> impl Strategy<Value = Scene> {
+        prop::collection::vec((vec3(50.), bounds()), 1..80).prop_flat_map(|items| {
+            let len = items.len();
+            let moves = prop::collection::vec((0..len, vec3(50.)), 0..40);
+            (Just(items), moves)
+        })
+    }
+
+    /// Builds an index from `items`, then moves some of the transforms and updates the index
+    fn build(
+        items: &[(Vec3, Aabb)],
+        moves: &[(usize, Vec3)],
+    ) -> (SpatialIndex, Vec<(Pearl<BobaTransform>, Aabb)>) {
+        let mut index = SpatialIndex::new(0.5);
+        let transforms: Vec<_> = items
+            .iter()
+            .map(|(position, local)| {
+                let transform = Pearl::wrap(BobaTransform::from_position(*position));
+                index.insert(transform.clone(), *local).unwrap();
+                (transform, *local)
+            })
+            .collect();
+
+        for (i, offset) in moves {
+            let mut transform = transforms[*i].0.borrow_mut().unwrap();
+            transform.translate(*offset * 0.1);
+            transform.rotate(glam::Quat::from_rotation_y(offset.x));
+        }
+        index.update();
+
+        (index, transforms)
+    }
+
+    proptest! {
+        #[test]
+        fn aabb_matches_brute_force((items, moves) in scene(), query in vec3(50.), size in vec3(20.)) {
+            let (index, transforms) = build(&items, &moves);
+            let aabb = Aabb::from_center_half_extents(query, size);
+
+            let expected = brute_force(&transforms, |b| b.intersects(&aabb));
+            prop_assert_eq!(ids(&index.query_aabb(&aabb)), expected);
+        }
+
+        #[test]
+        fn shifted_matches_brute_force((items, moves) in scene(), shift in vec3(500.), center in vec3(50.), radius in 0f32..30.) {
+            let (mut index, transforms) = build(&items, &moves);
+            let mut origin = FloatingOrigin::default();
+            origin.rebase(shift, &mut BobaResources::default());
+            index.shift_origin(shift);
+
+            // the index is queried before the next update
+            let center = center - shift;
+            let sphere = BoundingSphere::new(center, radius);
+            let expected = brute_force(&transforms, |b| b.grow(0.01).intersects_sphere(&sphere));
+            let found = ids(&index.query_radius(center, radius));
+            let strict = brute_force(&transforms, |b| b.intersects_sphere(&sphere));
+            prop_assert!(found.is_subset(&expected) && strict.is_subset(&found));
+        }
+
+        #[test]
+        fn radius_matches_brute_force((items, moves) in scene(), center in vec3(50.), radius in 0f32..30.) {
+            let (index, transforms) = build(&items, &moves);
+            let sphere = BoundingSphere::new(center, radius);
+
+            let expected = brute_force(&transforms, |b| b.intersects_sphere(&sphere));
+            prop_assert_eq!(ids(&index.query_radius(center, radius)), expected);
+        }
+
+        #[test]
+        fn ray_matches_brute_force((items, moves) in scene(), origin in vec3(60.), direction in vec3(1.), max in 0f32..200.) {
+            prop_assume!(direction.length() > 0.01);
+            let (index, transforms) = build(&items, &moves);
+            let ray = Ray::new(origin, direction);
+
+            let hits = index.query_ray(&ray, max);
+            let expected = brute_force(&transforms, |b| ray.intersect_aabb(b).is_some_and(|d| d <= max));
+            prop_assert_eq!(ids(hits.iter().map(|h| &h.transform)), expected);
+            prop_assert!(hits.windows(2).all(|w| w[0].distance <= w[1].distance));
+        }
+
+        #[test]
+        fn frustum_matches_brute_force((items, moves) in scene(), eye in vec3(60.), target in vec3(60.)) {
+            prop_assume!(eye.distance(target) > 0.1);
+            let (index, transforms) = build(&items, &moves);
+            let view = Mat4::look_at_rh(eye, target, Vec3::Y);
+            let proj = Mat4::perspective_rh(60f32.to_radians(), 16. / 9., 0.1, 80.);
+            let frustum = Frustum::from_matrix(&(proj * view));
+
+            let expected = brute_force(&transforms, |b| frustum.intersects_aabb(b));
+            prop_assert_eq!(ids(&index.query_frustum(&frustum)), expected);
+        }
+    }
+
+    #[test]
+    fn incremental_update() {
+        let mut index = SpatialIndex::default();
+        let near = Pearl::wrap(BobaTransform::from_position(Vec3::ZERO));
+        let far = Pearl::wrap(BobaTransform::from_position(Vec3::X * 100.));
+        let unit = Aabb::from_center_half_extents(Vec3::ZERO, Vec3::splat(0.5));
+        index.insert(near.clone(), unit).unwrap();
+        index.insert(far.clone(), unit).unwrap();
+
+        assert_eq!(ids(&index.query_radius(Vec3::ZERO, 5.)), ids([&near]));
+
+        // the index does not see moves until it is updated
+        far.borrow_mut().unwrap().set_local_position(Vec3::X * 2.);
+        assert_eq!(index.query_radius(Vec3::ZERO, 5.).len(), 1);
+        index.update();
+        assert_eq!(ids(&index.query_radius(Vec3::ZERO, 5.)), ids([&near, &far]));
+
+        // destroyed transforms are removed
+        near.destroy().unwrap();
+        index.update();
+        assert_eq!(index.len(), 1);
+        assert_eq!(ids(&index.query_radius(Vec3::ZERO, 5.)), ids([&far]));
+
+        assert!(index.remove(&far));
+        assert!(!index.remove(&far));
+        assert!(index.is_empty());
+    }
+
+    #[test]
+    fn ray_hits_sorted() {
+        let mut index = SpatialIndex::default();
+        let unit = Aabb::from_center_half_extents(Vec3::ZERO, Vec3::splat(0.5));
+        let transforms: Vec<_> = [5., 1., 3.]
+            .into_iter()
+            .map(|z| Pearl::wrap(BobaTransform::from_position(Vec3::Z * z)))
+            .collect();
+        for transform in &transforms {
+            index.insert(transform.clone(), unit).unwrap();
+        }
+
+        let hits = index.query_ray(&Ray::new(Vec3::ZERO, Vec3::Z), 4.);
+        let distances: Vec<_> = hits.iter().map(|h| h.distance).collect();
+        assert_eq!(distances, vec![0.5, 2.5]);
+        assert!(hits[0].transform == transforms[1]);
+    }
+}
@@ -0,0 +1,4 @@
+mod index;
+mod tree;
+
+pub use index::*;
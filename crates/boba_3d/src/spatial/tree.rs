@@ -0,0 +1,276 @@
+use glam::Vec3;
+
+use crate::geometry::Aabb;
+
+enum NodeKind<T> {
+    Leaf(T),
+    Branch(usize, usize),
+    Free,
+}
+
+struct Node<T> {
+    aabb: Aabb,
+    parent: Option<usize>,
+    kind: NodeKind<T>,
+}
+
+/// A dynamic bounding volume hierarchy.
+///
+/// Leaves are inserted next to the sibling that grows the surface area of the tree the least,
+/// and removed leaves are replaced by their sibling. Node indices stay valid until the node is removed.
+pub(crate) struct AabbTree<T> {
+    nodes: Vec<Node<T>>,
+    free: Vec<usize>,
+    root: Option<usize>,
+}
+
+impl<T> Default for AabbTree<T> {
+    fn default() -> Self {
+        Self {
+            nodes: Default::default(),
+            free: Default::default(),
+            root: None,
+        }
+    }
+}
+
+impl<T> AabbTree<T> {
+    /// Inserts a new leaf, returning its index
+    pub fn insert(&mut self, aabb: Aabb, data: T) -> usize {
+        let leaf = self.allocate(aabb, NodeKind::Leaf(data));
+        let Some(root) = self.root else {
+            self.root = Some(leaf);
+            return leaf;
+        };
+
+        // walk down the tree towards the cheapest sibling
+        let mut index = root;
+        while let NodeKind::Branch(left, right) = self.nodes[index].kind {
+            let area = self.nodes[index].aabb.surface_area();
+            let combined = self.nodes[index].aabb.union(&aabb).surface_area();
+
+            // the cost of making a new parent for the leaf and this node
+            let cost = 2. * combined;
+            // the cost that any ancestors of the leaf will have to grow by
+            let inherited = 2. * (combined - area);
+
+            let child_cost = |child: usize| {
+                let node = &self.nodes[child];
+                let grown = node.aabb.union(&aabb).surface_area();
+                match node.kind {
+                    NodeKind::Leaf(_) => grown + inherited,
+                    _ => grown - node.aabb.surface_area() + inherited,
+                }
+            };
+
+            let left_cost = child_cost(left);
+            let right_cost = child_cost(right);
+            if cost < left_cost && cost < right_cost {
+                break;
+            }
+
+            index = match left_cost < right_cost {
+                true => left,
+                false => right,
+            };
+        }
+
+        // create a new parent for the sibling and the leaf
+        let sibling = index;
+        let old_parent = self.nodes[sibling].parent;
+        let parent_aabb = self.nodes[sibling].aabb.union(&aabb);
+        let parent = self.allocate(parent_aabb, NodeKind::Branch(sibling, leaf));
+        self.nodes[parent].parent = old_parent;
+        self.nodes[sibling].parent = Some(parent);
+        self.nodes[leaf].parent = Some(parent);
+
+        match old_parent {
+            Some(old_parent) => {
+                self.replace_child(old_parent, sibling, parent);
+                self.refit(Some(old_parent));
+            }
+            None => self.root = Some(parent),
+        }
+
+        leaf
+    }
+
+    /// Removes a leaf, returning its data.
+    ///
+    /// # Panics
+    /// Panics if `leaf` is not the index of a leaf in the tree
+    pub fn remove(&mut self, leaf: usize) -> T {
+        let parent = self.nodes[leaf].parent;
+        let data = match self.deallocate(leaf) {
+            NodeKind::Leaf(data) => data,
+            _ => panic!("Node {leaf} is not a leaf"),
+        };
+
+        let Some(parent) = parent else {
+            self.root = None;
+            return data;
+        };
+
+        let sibling = match self.nodes[parent].kind {
+            NodeKind::Branch(left, right) if left == leaf => right,
+            NodeKind::Branch(left, _) => left,
+            _ => unreachable!("Parent nodes are always branches"),
+        };
+
+        // replace the parent with the sibling
+        let grandparent = self.nodes[parent].parent;
+        self.deallocate(parent);
+        self.nodes[sibling].parent = grandparent;
+        match grandparent {
+            Some(grandparent) => {
+                self.replace_child(grandparent, parent, sibling);
+                self.refit(Some(grandparent));
+            }
+            None => self.root = Some(sibling),
+        }
+
+        data
+    }
+
+    /// Moves every box in the tree by `offset`, which keeps the shape of the tree
+    pub fn translate(&mut self, offset: Vec3) {
+        for node in &mut self.nodes {
+            node.aabb.min += offset;
+            node.aabb.max += offset;
+        }
+    }
+
+    /// Gets the box of a node
+    pub fn aabb(&self, index: usize) -> &Aabb {
+        &self.nodes[index].aabb
+    }
+
+    /// Visits the data of every leaf whose box passes `overlaps`.
+    ///
+    /// Branches that fail `overlaps` are skipped, so it must also pass for any box that contains a passing box.
+    pub fn query(&self, mut overlaps: impl FnMut(&Aabb) -> bool, mut visit: impl FnMut(&T)) {
+        let mut stack = Vec::from_iter(self.root);
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !overlaps(&node.aabb) {
+                continue;
+            }
+
+            match &node.kind {
+                NodeKind::Leaf(data) => visit(data),
+                NodeKind::Branch(left, right) => {
+                    stack.push(*left);
+                    stack.push(*right);
+                }
+                NodeKind::Free => unreachable!("Free nodes are never linked"),
+            }
+        }
+    }
+
+    fn allocate(&mut self, aabb: Aabb, kind: NodeKind<T>) -> usize {
+        let node = Node {
+            aabb,
+            parent: None,
+            kind,
+        };
+
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn deallocate(&mut self, index: usize) -> NodeKind<T> {
+        self.free.push(index);
+        self.nodes[index].parent = None;
+        std::mem::replace(&mut self.nodes[index].kind, NodeKind::Free)
+    }
+
+    fn replace_child(&mut self, parent: usize, old: usize, new: usize) {
+        if let NodeKind::Branch(left, right) = &mut self.nodes[parent].kind {
+            match *left == old {
+                true => *left = new,
+                false => *right = new,
+            }
+        }
+    }
+
+    /// Recalculates the boxes of `index` and all of its ancestors
+    fn refit(&mut self, mut index: Option<usize>) {
+        while let Some(current) = index {
+            if let NodeKind::Branch(left, right) = self.nodes[current].kind {
+                self.nodes[current].aabb = self.nodes[left].aabb.union(&self.nodes[right].aabb);
+            }
+            index = self.nodes[current].parent;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use super::*;
+
+    impl<T> AabbTree<T> {
+        /// Checks that every branch contains its children, and that parent links are consistent
+        fn validate(&self) -> usize {
+            let Some(root) = self.root else {
+                return 0;
+            };
+
+            assert_eq!(self.nodes[root].parent, None);
+            let mut leaves = 0;
+            let mut stack = vec![root];
+            while let Some(index) = stack.pop() {
+                match self.nodes[index].kind {
+                    NodeKind::Leaf(_) => leaves += 1,
+                    NodeKind::Branch(left, right) => {
+                        for child in [left, right] {
+                            assert_eq!(self.nodes[child].parent, Some(index));
+                            assert!(self.nodes[index].aabb.contains(&self.nodes[child].aabb));
+                            stack.push(child);
+                        }
+                    }
+                    NodeKind::Free => panic!("Free node {index} is linked in the tree"),
+                }
+            }
+            leaves
+        }
+    }
+
+    fn cube(x: f32) -> Aabb {
+        Aabb::from_center_half_extents(Vec3::new(x, 0., 0.), Vec3::splat(0.5))
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut tree = AabbTree::default();
+        let leaves: Vec<_> = (0..20)
+            .map(|i| tree.insert(cube(i as f32 * 2.), i))
+            .collect();
+        assert_eq!(tree.validate(), 20);
+
+        for (i, leaf) in leaves.iter().enumerate().filter(|(i, _)| i % 3 == 0) {
+            assert_eq!(tree.remove(*leaf), i);
+        }
+        assert_eq!(tree.validate(), 13);
+
+        // freed nodes are reused
+        let node_count = tree.nodes.len();
+        tree.insert(cube(100.), 100);
+        assert_eq!(tree.nodes.len(), node_count);
+        assert_eq!(tree.validate(), 14);
+
+        let mut found = Vec::new();
+        tree.query(|aabb| aabb.intersects(&cube(4.5)), |i| found.push(*i));
+        found.sort();
+        assert_eq!(found, vec![2]);
+    }
+}
@@ -0,0 +1,496 @@
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The number of samples per segment used to build the arc length table
+const SAMPLES_PER_SEGMENT: usize = 16;
+
+/// The number of iterations used to refine [`Spline::closest_point`]
+const CLOSEST_POINT_ITERATIONS: usize = 24;
+
+/// An error returned when creating a [`Spline`].
+#[derive(Debug, Error)]
+pub enum SplineError {
+    #[error("Spline needs at least {required} points, but only {found} were given")]
+    NotEnoughPoints { required: usize, found: usize },
+    #[error("Bezier spline has {0} points, which does not form a whole number of cubic segments")]
+    IncompleteBezier(usize),
+}
+
+/// How the points of a [`Spline`] are connected.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SplineKind {
+    /// Straight lines between each point
+    Linear,
+    /// A smooth curve that passes through every point
+    #[default]
+    CatmullRom,
+    /// Cubic bezier segments.
+    ///
+    /// Each segment uses an anchor point, two control points and the anchor point of the next segment.
+    /// Open splines have `3n + 1` points, and closed splines have `3n` points where the last segment ends at the first point.
+    Bezier,
+}
+
+/// A point on a [`Spline`] returned by [`Spline::closest_point`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SplinePoint {
+    /// The curve parameter of the point, where each segment spans a range of `1`
+    pub parameter: f32,
+    /// The arc length from the start of the spline to the point
+    pub distance: f32,
+    pub position: Vec3,
+    /// The normalized direction of the spline at the point
+    pub tangent: Vec3,
+}
+
+/// A curve through a list of points, with an arc length table for moving along it at a constant speed.
+///
+/// Curve parameters run from `0` to [`segment_count`](Spline::segment_count), with each segment spanning a range of `1`.
+/// Parameters and distances past the ends are clamped on open splines and wrapped on closed splines.
+#[derive(Debug, Clone)]
+pub struct Spline {
+    kind: SplineKind,
+    points: Vec<Vec3>,
+    closed: bool,
+    /// The cumulative arc length at every sample of the curve
+    lengths: Vec<f32>,
+}
+
+impl Spline {
+    /// Creates a new spline of `kind` through `points`
+    pub fn new(
+        kind: SplineKind,
+        points: impl Into<Vec<Vec3>>,
+        closed: bool,
+    ) -> Result<Self, SplineError> {
+        let points = points.into();
+        let found = points.len();
+        match kind {
+            SplineKind::Linear | SplineKind::CatmullRom if found < 2 => {
+                return Err(SplineError::NotEnoughPoints { required: 2, found })
+            }
+            SplineKind::Bezier => {
+                let required = match closed {
+                    true => 3,
+                    false => 4,
+                };
+                if found < required {
+                    return Err(SplineError::NotEnoughPoints { required, found });
+                }
+
+                let remainder = match closed {
+                    true => found % 3,
+                    false => (found - 1) % 3,
+                };
+                if remainder != 0 {
+                    return Err(SplineError::IncompleteBezier(found));
+                }
+            }
+            _ => (),
+        }
+
+        let mut spline = Self {
+            kind,
+            points,
+            closed,
+            lengths: Vec::new(),
+        };
+        spline.calculate_lengths();
+        Ok(spline)
+    }
+
+    /// Creates an open spline of straight lines through `points`
+    pub fn linear(points: impl Into<Vec<Vec3>>) -> Result<Self, SplineError> {
+        Self::new(SplineKind::Linear, points, false)
+    }
+
+    /// Creates an open catmull-rom spline through `points`
+    pub fn catmull_rom(points: impl Into<Vec<Vec3>>) -> Result<Self, SplineError> {
+        Self::new(SplineKind::CatmullRom, points, false)
+    }
+
+    /// Creates an open spline of cubic bezier segments from `points`
+    pub fn bezier(points: impl Into<Vec<Vec3>>) -> Result<Self, SplineError> {
+        Self::new(SplineKind::Bezier, points, false)
+    }
+
+    pub fn kind(&self) -> SplineKind {
+        self.kind
+    }
+
+    pub fn points(&self) -> &[Vec3] {
+        &self.points
+    }
+
+    /// Moves every point of the spline by `offset`.
+    ///
+    /// The shape of the curve is unchanged, so the arc length table is kept.
+    pub fn translate(&mut self, offset: Vec3) {
+        for point in &mut self.points {
+            *point += offset;
+        }
+    }
+
+    /// Returns `true` if the end of the spline connects back to its start
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    pub fn segment_count(&self) -> usize {
+        let count = self.points.len();
+        match (self.kind, self.closed) {
+            (SplineKind::Bezier, true) => count / 3,
+            (SplineKind::Bezier, false) => (count - 1) / 3,
+            (_, true) => count,
+            (_, false) => count - 1,
+        }
+    }
+
+    /// The total arc length of the spline
+    pub fn length(&self) -> f32 {
+        self.lengths.last().copied().unwrap_or(0.)
+    }
+
+    /// Gets the position at the curve `parameter`
+    pub fn position_at_parameter(&self, parameter: f32) -> Vec3 {
+        let (segment, t) = self.segment_at(parameter);
+        self.segment_position(segment, t)
+    }
+
+    /// Gets the normalized direction of the spline at the curve `parameter`.
+    ///
+    /// Returns zero if the curve does not move at the parameter, such as between two equal points.
+    pub fn tangent_at_parameter(&self, parameter: f32) -> Vec3 {
+        let (segment, t) = self.segment_at(parameter);
+        self.segment_derivative(segment, t).normalize_or_zero()
+    }
+
+    /// Gets the position that is `distance` along the spline
+    pub fn position_at_distance(&self, distance: f32) -> Vec3 {
+        self.position_at_parameter(self.parameter_at_distance(distance))
+    }
+
+    /// Gets the normalized direction of the spline that is `distance` along it
+    pub fn tangent_at_distance(&self, distance: f32) -> Vec3 {
+        self.tangent_at_parameter(self.parameter_at_distance(distance))
+    }
+
+    /// Converts an arc length along the spline to a curve parameter
+    pub fn parameter_at_distance(&self, distance: f32) -> f32 {
+        let distance = self.wrap_distance(distance);
+        let sample = self
+            .lengths
+            .partition_point(|length| *length <= distance)
+            .clamp(1, self.lengths.len() - 1)
+            - 1;
+
+        let (start, end) = (self.lengths[sample], self.lengths[sample + 1]);
+        let fraction = match end > start {
+            true => (distance - start) / (end - start),
+            false => 0.,
+        };
+        (sample as f32 + fraction) / SAMPLES_PER_SEGMENT as f32
+    }
+
+    /// Converts a curve parameter to the arc length from the start of the spline
+    pub fn distance_at_parameter(&self, parameter: f32) -> f32 {
+        let sample = self.wrap_parameter(parameter) * SAMPLES_PER_SEGMENT as f32;
+        let index = (sample.floor() as usize).min(self.lengths.len() - 2);
+        let fraction = sample - index as f32;
+        let (start, end) = (self.lengths[index], self.lengths[index + 1]);
+        start + (end - start) * fraction
+    }
+
+    /// Finds the point on the spline that is closest to `point`
+    pub fn closest_point(&self, point: Vec3) -> SplinePoint {
+        let step = 1. / SAMPLES_PER_SEGMENT as f32;
+        let closest_sample = (0..self.lengths.len())
+            .map(|sample| sample as f32 * step)
+            .map(|parameter| {
+                let distance = self
+                    .position_at_parameter(parameter)
+                    .distance_squared(point);
+                (parameter, distance)
+            })
+            .fold((0., f32::INFINITY), |closest, sample| {
+                match sample.1 < closest.1 {
+                    true => sample,
+                    false => closest,
+                }
+            })
+            .0;
+
+        // refine the sample with a golden section search between its neighbours
+        let distance = |parameter: f32| {
+            self.position_at_parameter(parameter)
+                .distance_squared(point)
+        };
+        let ratio = (5f32.sqrt() - 1.) * 0.5;
+        let (mut low, mut high) = (closest_sample - step, closest_sample + step);
+        if !self.closed {
+            low = low.max(0.);
+            high = high.min(self.segment_count() as f32);
+        }
+        for _ in 0..CLOSEST_POINT_ITERATIONS {
+            let a = high - (high - low) * ratio;
+            let b = low + (high - low) * ratio;
+            match distance(a) < distance(b) {
+                true => high = b,
+                false => low = a,
+            }
+        }
+
+        let parameter = self.wrap_parameter((low + high) * 0.5);
+        SplinePoint {
+            parameter,
+            distance: self.distance_at_parameter(parameter),
+            position: self.position_at_parameter(parameter),
+            tangent: self.tangent_at_parameter(parameter),
+        }
+    }
+
+    /// Clamps `distance` to the spline for open splines, or wraps it for closed splines
+    pub fn wrap_distance(&self, distance: f32) -> f32 {
+        let length = self.length();
+        match self.closed && length > 0. {
+            true => distance.rem_euclid(length),
+            false => distance.clamp(0., length),
+        }
+    }
+
+    /// Clamps `parameter` to the spline for open splines, or wraps it for closed splines
+    pub fn wrap_parameter(&self, parameter: f32) -> f32 {
+        let count = self.segment_count() as f32;
+        match self.closed {
+            true => parameter.rem_euclid(count),
+            false => parameter.clamp(0., count),
+        }
+    }
+
+    /// Splits a curve parameter into a segment index and the progress through that segment
+    fn segment_at(&self, parameter: f32) -> (usize, f32) {
+        let parameter = self.wrap_parameter(parameter);
+        let segment = (parameter.floor() as usize).min(self.segment_count() - 1);
+        (segment, parameter - segment as f32)
+    }
+
+    /// Gets a point by index, wrapping for closed splines and extrapolating past the ends of open splines
+    fn point(&self, index: isize) -> Vec3 {
+        let count = self.points.len() as isize;
+        if self.closed {
+            return self.points[index.rem_euclid(count) as usize];
+        }
+
+        match index {
+            index if index < 0 => 2. * self.points[0] - self.points[1],
+            index if index >= count => {
+                let last = count as usize - 1;
+                2. * self.points[last] - self.points[last - 1]
+            }
+            index => self.points[index as usize],
+        }
+    }
+
+    fn segment_points(&self, segment: usize) -> [Vec3; 4] {
+        let segment = segment as isize;
+        match self.kind {
+            SplineKind::Linear => {
+                let (start, end) = (self.point(segment), self.point(segment + 1));
+                [start, start, end, end]
+            }
+            SplineKind::CatmullRom => [
+                self.point(segment - 1),
+                self.point(segment),
+                self.point(segment + 1),
+                self.point(segment + 2),
+            ],
+            SplineKind::Bezier => {
+                let start = segment * 3;
+                [
+                    self.point(start),
+                    self.point(start + 1),
+                    self.point(start + 2),
+                    self.point(start + 3),
+                ]
+            }
+        }
+    }
+
+    fn segment_position(&self, segment: usize, t: f32) -> Vec3 {
+        let [p0, p1, p2, p3] = self.segment_points(segment);
+        match self.kind {
+            SplineKind::Linear => p1.lerp(p2, t),
+            SplineKind::CatmullRom => {
+                let (t2, t3) = (t * t, t * t * t);
+                0.5 * (2. * p1
+                    + (p2 - p0) * t
+                    + (2. * p0 - 5. * p1 + 4. * p2 - p3) * t2
+                    + (3. * p1 - p0 - 3. * p2 + p3) * t3)
+            }
+            SplineKind::Bezier => {
+                let u = 1. - t;
+                u * u * u * p0 + 3. * u * u * t * p1 + 3. * u * t * t * p2 + t * t * t * p3
+            }
+        }
+    }
+
+    fn segment_derivative(&self, segment: usize, t: f32) -> Vec3 {
+        let [p0, p1, p2, p3] = self.segment_points(segment);
+        match self.kind {
+            SplineKind::Linear => p2 - p1,
+            SplineKind::CatmullRom => {
+                0.5 * ((p2 - p0)
+                    + 2. * (2. * p0 - 5. * p1 + 4. * p2 - p3) * t
+                    + 3. * (3. * p1 - p0 - 3. * p2 + p3) * t * t)
+            }
+            SplineKind::Bezier => {
+                let u = 1. - t;
+                3. * u * u * (p1 - p0) + 6. * u * t * (p2 - p1) + 3. * t * t * (p3 - p2)
+            }
+        }
+    }
+
+    fn calculate_lengths(&mut self) {
+        let samples = self.segment_count() * SAMPLES_PER_SEGMENT;
+        let step = 1. / SAMPLES_PER_SEGMENT as f32;
+
+        let mut length = 0.;
+        let mut previous = self.segment_position(0, 0.);
+        self.lengths = Vec::with_capacity(samples + 1);
+        self.lengths.push(0.);
+        for sample in 1..=samples {
+            let parameter = sample as f32 * step;
+            let segment = ((sample - 1) / SAMPLES_PER_SEGMENT).min(self.segment_count() - 1);
+            let position = self.segment_position(segment, parameter - segment as f32);
+            length += position.distance(previous);
+            self.lengths.push(length);
+            previous = position;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 0.001;
+
+    fn assert_vec(a: Vec3, b: Vec3) {
+        assert!(a.abs_diff_eq(b, EPSILON), "{a} != {b}");
+    }
+
+    #[test]
+    fn invalid_points() {
+        assert!(matches!(
+            Spline::linear([Vec3::ZERO]),
+            Err(SplineError::NotEnoughPoints {
+                required: 2,
+                found: 1
+            })
+        ));
+        assert!(matches!(
+            Spline::bezier([Vec3::ZERO; 5]),
+            Err(SplineError::IncompleteBezier(5))
+        ));
+        assert!(Spline::new(SplineKind::Bezier, [Vec3::ZERO; 6], true).is_ok());
+    }
+
+    #[test]
+    fn linear_arc_length() {
+        let spline = Spline::linear([Vec3::ZERO, Vec3::X * 2., Vec3::new(2., 4., 0.)]).unwrap();
+        assert_eq!(spline.segment_count(), 2);
+        assert!((spline.length() - 6.).abs() < EPSILON);
+
+        // the second segment is twice as long, so distance is not proportional to the parameter
+        assert_vec(spline.position_at_distance(1.), Vec3::X);
+        assert_vec(spline.position_at_distance(4.), Vec3::new(2., 2., 0.));
+        assert!((spline.parameter_at_distance(4.) - 1.5).abs() < EPSILON);
+        assert!((spline.distance_at_parameter(1.5) - 4.).abs() < EPSILON);
+        assert_vec(spline.tangent_at_distance(4.), Vec3::Y);
+
+        // open splines clamp past the ends
+        assert_vec(spline.position_at_distance(-1.), Vec3::ZERO);
+        assert_vec(spline.position_at_distance(10.), Vec3::new(2., 4., 0.));
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_points() {
+        let points = [
+            Vec3::ZERO,
+            Vec3::new(1., 1., 0.),
+            Vec3::new(2., 0., 1.),
+            Vec3::new(3., -1., 0.),
+        ];
+
+        for closed in [false, true] {
+            let spline = Spline::new(SplineKind::CatmullRom, points, closed).unwrap();
+            for (i, point) in points.iter().enumerate() {
+                assert_vec(spline.position_at_parameter(i as f32), *point);
+            }
+        }
+
+        // closed splines wrap around to the first point
+        let spline = Spline::new(SplineKind::CatmullRom, points, true).unwrap();
+        assert_eq!(spline.segment_count(), 4);
+        assert_vec(spline.position_at_distance(spline.length()), points[0]);
+        assert_vec(
+            spline.position_at_distance(spline.length() + 0.5),
+            spline.position_at_distance(0.5),
+        );
+    }
+
+    #[test]
+    fn bezier_evaluation() {
+        // a straight bezier with uneven control points still moves at a constant speed by distance
+        let spline =
+            Spline::bezier([Vec3::ZERO, Vec3::X * 0.1, Vec3::X * 0.2, Vec3::X * 3.]).unwrap();
+        assert!((spline.length() - 3.).abs() < EPSILON);
+        // the arc length table is sampled, so positions between samples are only approximately even
+        for distance in [0.5, 1.5, 2.5] {
+            let position = spline.position_at_distance(distance);
+            assert!((position.x - distance).abs() < 0.01, "{position}");
+        }
+        assert_vec(spline.tangent_at_distance(1.5), Vec3::X);
+
+        // a quarter circle approximation
+        let k = 0.5523;
+        let spline =
+            Spline::bezier([Vec3::X, Vec3::new(1., k, 0.), Vec3::new(k, 1., 0.), Vec3::Y]).unwrap();
+        assert!((spline.length() - std::f32::consts::FRAC_PI_2).abs() < 0.01);
+        assert_vec(spline.tangent_at_parameter(0.), Vec3::Y);
+        assert_vec(spline.tangent_at_parameter(1.), -Vec3::X);
+    }
+
+    #[test]
+    fn closest_point() {
+        let spline = Spline::linear([Vec3::ZERO, Vec3::X * 4., Vec3::new(4., 4., 0.)]).unwrap();
+
+        let closest = spline.closest_point(Vec3::new(1.3, -2., 0.));
+        assert_vec(closest.position, Vec3::X * 1.3);
+        assert!((closest.distance - 1.3).abs() < EPSILON);
+        assert_vec(closest.tangent, Vec3::X);
+
+        let closest = spline.closest_point(Vec3::new(6., 2.5, 1.));
+        assert_vec(closest.position, Vec3::new(4., 2.5, 0.));
+        assert!((closest.distance - 6.5).abs() < EPSILON);
+
+        let closest = spline.closest_point(Vec3::new(-3., -3., 0.));
+        assert_vec(closest.position, Vec3::ZERO);
+
+        // closest points on a curve are perpendicular to the tangent
+        let spline = Spline::new(
+            SplineKind::CatmullRom,
+            [Vec3::X, Vec3::Z, -Vec3::X, -Vec3::Z],
+            true,
+        )
+        .unwrap();
+        let target = Vec3::new(2., 0.5, 1.5);
+        let closest = spline.closest_point(target);
+        assert!((target - closest.position).dot(closest.tangent).abs() < EPSILON);
+        assert_vec(
+            spline.position_at_distance(closest.distance),
+            closest.position,
+        );
+    }
+}
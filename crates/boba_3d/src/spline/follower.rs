@@ -0,0 +1,299 @@
+use boba_core::{
+    register_pearl_stages, stages::BobaUpdate, BobaResources, BobaResult, Pearl, PearlMutError,
+    PearlStage,
+};
+use glam::{DVec3, Vec3};
+use log::warn;
+
+use crate::{large_world::world_origin, pearls::BobaTransform};
+
+use super::Spline;
+
+/// What a [`PathFollower`] does when it reaches the end of its spline.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PathEnd {
+    /// Stops at the end of the spline
+    #[default]
+    Stop,
+    /// Jumps back to the start of the spline. Closed splines continue smoothly around the loop.
+    Loop,
+    /// Reverses direction at each end of the spline
+    PingPong,
+}
+
+/// A pearl that moves a transform along a [`Spline`] at a constant speed every [`BobaUpdate`].
+///
+/// The spline is in world space, so the transform keeps following it when its parent moves.
+/// When a [`FloatingOrigin`](crate::large_world::FloatingOrigin) moves, the spline is moved along with the world.
+pub struct PathFollower {
+    target: Pearl<BobaTransform>,
+    spline: Spline,
+    /// The world origin that the spline points are relative to
+    origin: DVec3,
+    pub speed: f32,
+    pub end: PathEnd,
+    /// The up direction used when orienting the transform, or `None` to leave its rotation alone
+    pub orient_up: Option<Vec3>,
+    distance: f32,
+    reversed: bool,
+}
+
+register_pearl_stages!(PathFollower: BobaUpdate);
+
+impl PearlStage<BobaUpdate> for PathFollower {
+    fn update(pearl: &Pearl<Self>, delta: &f32, _: &mut BobaResources) -> BobaResult {
+        let mut follower = pearl.borrow_mut()?;
+        match follower.advance(*delta) {
+            Ok(()) | Err(PearlMutError::Destroyed) => (),
+            Err(e) => warn!("Skipping path follower frame, because its target could not be borrowed. Error: {e}"),
+        }
+        Ok(())
+    }
+}
+
+impl PathFollower {
+    /// Creates a follower that moves `target` along `spline` at `speed` units per second
+    pub fn new(target: Pearl<BobaTransform>, spline: Spline, speed: f32) -> Self {
+        Self {
+            target,
+            spline,
+            origin: world_origin().unwrap_or_default(),
+            speed,
+            end: PathEnd::default(),
+            orient_up: None,
+            distance: 0.,
+            reversed: false,
+        }
+    }
+
+    /// Sets what happens when the follower reaches the end of the spline
+    pub fn with_end(mut self, end: PathEnd) -> Self {
+        self.end = end;
+        self
+    }
+
+    /// Rotates the transform to face along the spline, keeping its up axis close to `up`
+    pub fn oriented(mut self, up: Vec3) -> Self {
+        self.orient_up = Some(up);
+        self
+    }
+
+    pub fn target(&self) -> &Pearl<BobaTransform> {
+        &self.target
+    }
+
+    /// Gets the spline, which is moved with the world origin the next time the follower advances
+    pub fn spline(&self) -> &Spline {
+        &self.spline
+    }
+
+    /// Replaces the spline, keeping the current distance along it
+    pub fn set_spline(&mut self, spline: Spline) {
+        self.spline = spline;
+        self.origin = world_origin().unwrap_or_default();
+        self.distance = self.spline.wrap_distance(self.distance);
+    }
+
+    /// The arc length from the start of the spline to the follower
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    /// Moves the follower to `distance` along the spline.
+    ///
+    /// The transform is moved on the next call to [`advance`](Self::advance).
+    pub fn set_distance(&mut self, distance: f32) {
+        self.distance = self.spline.wrap_distance(distance);
+    }
+
+    /// Returns `true` if the follower is moving towards the start of the spline
+    pub fn is_reversed(&self) -> bool {
+        self.reversed
+    }
+
+    /// Returns `true` if the follower has stopped at an end of the spline
+    pub fn is_finished(&self) -> bool {
+        let end = match (self.speed < 0.) != self.reversed {
+            true => 0.,
+            false => self.spline.length(),
+        };
+        self.end == PathEnd::Stop && self.distance == end
+    }
+
+    /// Moves the follower along the spline by `delta` seconds, then moves the transform to match
+    pub fn advance(&mut self, delta: f32) -> Result<(), PearlMutError> {
+        let length = self.spline.length();
+        let step = self.speed * delta;
+        match self.end {
+            PathEnd::Stop => self.distance = (self.distance + step).clamp(0., length),
+            PathEnd::Loop if length > 0. => {
+                self.distance = (self.distance + step).rem_euclid(length);
+            }
+            PathEnd::PingPong if length > 0. => {
+                // walk an unfolded path that goes to the end and back again
+                let phase = match self.reversed {
+                    false => self.distance,
+                    true => 2. * length - self.distance,
+                };
+                let phase = (phase + step).rem_euclid(2. * length);
+                self.reversed = phase > length;
+                self.distance = match self.reversed {
+                    false => phase,
+                    true => 2. * length - phase,
+                };
+            }
+            _ => self.distance = 0.,
+        }
+
+        self.apply()
+    }
+
+    /// Moves the spline to stay in place when the world origin moved since it was last used
+    fn settle_origin(&mut self) {
+        let Some(world_origin) = world_origin() else {
+            return;
+        };
+
+        if self.origin != world_origin {
+            self.spline
+                .translate((self.origin - world_origin).as_vec3());
+            self.origin = world_origin;
+        }
+    }
+
+    /// Moves the transform to the current distance along the spline
+    fn apply(&mut self) -> Result<(), PearlMutError> {
+        self.settle_origin();
+        let parameter = self.spline.parameter_at_distance(self.distance);
+        let position = self.spline.position_at_parameter(parameter);
+
+        let mut transform = self.target.borrow_mut()?;
+        transform.set_world_position(position);
+        if let Some(up) = self.orient_up {
+            let tangent = self.spline.tangent_at_parameter(parameter);
+            let direction = match (self.speed < 0.) != self.reversed {
+                true => -tangent,
+                false => tangent,
+            };
+            transform.look_at(position + direction, up);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boba_core::{
+        stages::BobaUpdate, BobaResources, BobaStage, Pearl, PearlRegistry, StageClock, StageDelta,
+    };
+    use glam::Vec3;
+
+    use crate::{large_world::FloatingOrigin, pearls::SetTransformParent};
+
+    use super::*;
+
+    const EPSILON: f32 = 0.001;
+
+    fn assert_vec(a: Vec3, b: Vec3) {
+        assert!(a.abs_diff_eq(b, EPSILON), "{a} != {b}");
+    }
+
+    fn corner() -> Spline {
+        Spline::linear([Vec3::ZERO, Vec3::X * 2., Vec3::new(2., 0., 2.)]).unwrap()
+    }
+
+    #[test]
+    fn follow_in_update() {
+        let mut clock = StageClock::replaying();
+        clock.queue_replay((0..3).map(|_| StageDelta::new::<BobaUpdate>(0.5)));
+        let mut resources = BobaResources::default();
+        resources.add(clock);
+
+        let transform = Pearl::wrap(BobaTransform::default());
+        let follower =
+            Pearl::wrap(PathFollower::new(transform.clone(), corner(), 2.).oriented(Vec3::Y));
+        let mut registry = PearlRegistry::default();
+        registry.add(follower.clone());
+
+        let mut stage = BobaUpdate::default();
+        stage.run(&mut registry, &mut resources).unwrap();
+        assert_vec(transform.borrow().unwrap().world_position(), Vec3::X);
+        assert_vec(transform.borrow().unwrap().forward(), Vec3::X);
+
+        stage.run(&mut registry, &mut resources).unwrap();
+        stage.run(&mut registry, &mut resources).unwrap();
+        assert_vec(
+            transform.borrow().unwrap().world_position(),
+            Vec3::new(2., 0., 1.),
+        );
+        assert_vec(transform.borrow().unwrap().forward(), Vec3::Z);
+        assert!(!follower.borrow().unwrap().is_finished());
+    }
+
+    #[test]
+    fn path_ends() {
+        let transform = Pearl::wrap(BobaTransform::default());
+
+        let mut follower = PathFollower::new(transform.clone(), corner(), 1.);
+        follower.advance(10.).unwrap();
+        assert!(follower.is_finished());
+        assert_vec(
+            transform.borrow().unwrap().world_position(),
+            Vec3::new(2., 0., 2.),
+        );
+
+        let mut follower =
+            PathFollower::new(transform.clone(), corner(), 1.).with_end(PathEnd::Loop);
+        follower.advance(5.).unwrap();
+        assert!((follower.distance() - 1.).abs() < EPSILON);
+        assert_vec(transform.borrow().unwrap().world_position(), Vec3::X);
+
+        let mut follower = PathFollower::new(transform.clone(), corner(), 1.)
+            .with_end(PathEnd::PingPong)
+            .oriented(Vec3::Y);
+        follower.advance(5.).unwrap();
+        assert!(follower.is_reversed());
+        assert!((follower.distance() - 3.).abs() < EPSILON);
+        assert_vec(
+            transform.borrow().unwrap().world_position(),
+            Vec3::new(2., 0., 1.),
+        );
+        assert_vec(transform.borrow().unwrap().forward(), -Vec3::Z);
+
+        follower.advance(4.).unwrap();
+        assert!(!follower.is_reversed());
+        assert!((follower.distance() - 1.).abs() < EPSILON);
+    }
+
+    #[test]
+    fn follows_in_world_space() {
+        let mut parent = BobaTransform::default();
+        parent.set_local_position(Vec3::new(0., 5., 0.));
+        let parent = Pearl::wrap(parent);
+        let mut child = Pearl::wrap(BobaTransform::default());
+        child.set_parent(parent.clone()).unwrap();
+
+        let mut follower = PathFollower::new(child.clone(), corner(), 1.);
+        follower.advance(1.).unwrap();
+        let child = child.borrow().unwrap();
+        assert_vec(child.world_position(), Vec3::X);
+        assert_vec(child.local_position(), Vec3::new(1., -5., 0.));
+    }
+
+    #[test]
+    fn follows_the_floating_origin() {
+        let transform = Pearl::wrap(BobaTransform::default());
+        let mut origin = FloatingOrigin::default();
+        let mut follower = PathFollower::new(transform.clone(), corner(), 1.);
+        follower.advance(1.).unwrap();
+
+        // the spline stays at the same absolute position, so the follower keeps moving along it
+        origin.rebase(Vec3::new(100., 0., -50.), &mut BobaResources::default());
+        follower.advance(2.).unwrap();
+        let transform = transform.borrow().unwrap();
+        assert_vec(transform.world_position(), Vec3::new(-98., 0., 51.));
+        assert_eq!(origin.absolute_position(&transform), DVec3::new(2., 0., 1.));
+        assert_vec(follower.spline().points()[0], Vec3::new(-100., 0., 50.));
+    }
+}
@@ -0,0 +1,5 @@
+mod curve;
+mod follower;
+
+pub use curve::*;
+pub use follower::*;
@@ -0,0 +1,193 @@
+use std::f32::consts::PI;
+
+/// An easing curve that maps linear progress in `0..=1` onto eased progress.
+///
+/// Curves that overshoot, like [`Ease::BackOut`] and [`Ease::ElasticOut`], can return values outside of `0..=1`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Ease {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    QuartIn,
+    QuartOut,
+    QuartInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    CircIn,
+    CircOut,
+    CircInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+}
+
+const BACK: f32 = 1.70158;
+const BACK_IN_OUT: f32 = BACK * 1.525;
+const ELASTIC: f32 = 2. * PI / 3.;
+const ELASTIC_IN_OUT: f32 = 2. * PI / 4.5;
+
+impl Ease {
+    /// Applies the curve to `t`, which is clamped to `0..=1`
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+        match self {
+            Ease::Linear => t,
+            Ease::QuadIn => t * t,
+            Ease::QuadOut => 1. - (1. - t).powi(2),
+            Ease::QuadInOut => in_out(t, |t| t * t),
+            Ease::CubicIn => t.powi(3),
+            Ease::CubicOut => 1. - (1. - t).powi(3),
+            Ease::CubicInOut => in_out(t, |t| t.powi(3)),
+            Ease::QuartIn => t.powi(4),
+            Ease::QuartOut => 1. - (1. - t).powi(4),
+            Ease::QuartInOut => in_out(t, |t| t.powi(4)),
+            Ease::SineIn => 1. - (t * PI / 2.).cos(),
+            Ease::SineOut => (t * PI / 2.).sin(),
+            Ease::SineInOut => -((PI * t).cos() - 1.) / 2.,
+            Ease::ExpoIn => expo_in(t),
+            Ease::ExpoOut => 1. - expo_in(1. - t),
+            Ease::ExpoInOut => in_out(t, expo_in),
+            Ease::CircIn => 1. - (1. - t * t).sqrt(),
+            Ease::CircOut => (1. - (t - 1.).powi(2)).sqrt(),
+            Ease::CircInOut => in_out(t, |t| 1. - (1. - t * t).sqrt()),
+            Ease::BackIn => (BACK + 1.) * t.powi(3) - BACK * t * t,
+            Ease::BackOut => 1. + (BACK + 1.) * (t - 1.).powi(3) + BACK * (t - 1.).powi(2),
+            Ease::BackInOut => in_out(t, |t| (BACK_IN_OUT + 1.) * t.powi(3) - BACK_IN_OUT * t * t),
+            Ease::ElasticIn => elastic_in(t, ELASTIC),
+            Ease::ElasticOut => 1. - elastic_in(1. - t, ELASTIC),
+            Ease::ElasticInOut => in_out(t, |t| elastic_in(t, ELASTIC_IN_OUT)),
+            Ease::BounceIn => 1. - bounce_out(1. - t),
+            Ease::BounceOut => bounce_out(t),
+            Ease::BounceInOut => in_out(t, |t| 1. - bounce_out(1. - t)),
+        }
+    }
+}
+
+/// Builds an in-out curve from an in curve, by mirroring it for the second half
+fn in_out(t: f32, ease_in: impl Fn(f32) -> f32) -> f32 {
+    match t < 0.5 {
+        true => ease_in(t * 2.) / 2.,
+        false => 1. - ease_in((1. - t) * 2.) / 2.,
+    }
+}
+
+fn expo_in(t: f32) -> f32 {
+    match t == 0. {
+        true => 0.,
+        false => 2f32.powf(10. * t - 10.),
+    }
+}
+
+fn elastic_in(t: f32, period: f32) -> f32 {
+    match t {
+        t if t == 0. || t == 1. => t,
+        t => -(2f32.powf(10. * t - 10.)) * ((t * 10. - 10.75) * period).sin(),
+    }
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+
+    if t < 1. / D {
+        N * t * t
+    } else if t < 2. / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ease;
+
+    const ALL: [Ease; 28] = [
+        Ease::Linear,
+        Ease::QuadIn,
+        Ease::QuadOut,
+        Ease::QuadInOut,
+        Ease::CubicIn,
+        Ease::CubicOut,
+        Ease::CubicInOut,
+        Ease::QuartIn,
+        Ease::QuartOut,
+        Ease::QuartInOut,
+        Ease::SineIn,
+        Ease::SineOut,
+        Ease::SineInOut,
+        Ease::ExpoIn,
+        Ease::ExpoOut,
+        Ease::ExpoInOut,
+        Ease::CircIn,
+        Ease::CircOut,
+        Ease::CircInOut,
+        Ease::BackIn,
+        Ease::BackOut,
+        Ease::BackInOut,
+        Ease::ElasticIn,
+        Ease::ElasticOut,
+        Ease::ElasticInOut,
+        Ease::BounceIn,
+        Ease::BounceOut,
+        Ease::BounceInOut,
+    ];
+
+    #[test]
+    fn endpoints() {
+        for ease in ALL {
+            assert!(ease.apply(0.).abs() < 0.001, "{ease:?} does not start at 0");
+            assert!(
+                (ease.apply(1.) - 1.).abs() < 0.001,
+                "{ease:?} does not end at 1"
+            );
+        }
+    }
+
+    #[test]
+    fn symmetric_in_out() {
+        for ease in [
+            Ease::QuadInOut,
+            Ease::CubicInOut,
+            Ease::SineInOut,
+            Ease::CircInOut,
+        ] {
+            assert!(
+                (ease.apply(0.5) - 0.5).abs() < 0.001,
+                "{ease:?} is not symmetric"
+            );
+            let a = ease.apply(0.25);
+            let b = 1. - ease.apply(0.75);
+            assert!((a - b).abs() < 0.001, "{ease:?} is not symmetric");
+        }
+    }
+
+    #[test]
+    fn shapes() {
+        assert!(Ease::QuadIn.apply(0.5) < 0.5);
+        assert!(Ease::QuadOut.apply(0.5) > 0.5);
+        assert!(Ease::BackIn.apply(0.2) < 0.);
+        assert!(Ease::BackOut.apply(0.8) > 1.);
+        assert!(Ease::Linear.apply(2.) == 1.);
+    }
+}
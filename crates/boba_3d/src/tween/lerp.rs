@@ -0,0 +1,38 @@
+use glam::{Quat, Vec2, Vec3, Vec4};
+
+/// A value that can be linearly interpolated, and therefore tweened
+pub trait Lerp: Clone + 'static {
+    /// Interpolates between `self` and `to`, where `t = 0` is `self` and `t = 1` is `to`
+    fn lerp(&self, to: &Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Lerp for Vec2 {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        Vec2::lerp(*self, *to, t)
+    }
+}
+
+impl Lerp for Vec3 {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        Vec3::lerp(*self, *to, t)
+    }
+}
+
+impl Lerp for Vec4 {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        Vec4::lerp(*self, *to, t)
+    }
+}
+
+/// Rotations use spherical interpolation
+impl Lerp for Quat {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        self.slerp(*to, t)
+    }
+}
@@ -0,0 +1,11 @@
+mod easing;
+mod lerp;
+mod player;
+mod sequence;
+mod tweening;
+
+pub use easing::*;
+pub use lerp::*;
+pub use player::*;
+pub use sequence::*;
+pub use tweening::*;
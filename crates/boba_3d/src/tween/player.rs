@@ -0,0 +1,115 @@
+use boba_core::{
+    register_pearl_stages, stages::BobaUpdate, BobaResources, BobaResult, Pearl, PearlStage,
+};
+use indexmap::IndexMap;
+
+use super::{TweenProgress, Tweenable};
+
+/// The id of a tween playing in a [`TweenPlayer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TweenId(u64);
+
+/// A pearl that advances all of its tweens every [`BobaUpdate`].
+///
+/// Completion callbacks run while the player is borrowed, so they cannot access the player pearl itself.
+#[derive(Default)]
+pub struct TweenPlayer {
+    tweens: IndexMap<TweenId, Box<dyn Tweenable>>,
+    next_id: u64,
+}
+
+register_pearl_stages!(TweenPlayer: BobaUpdate);
+
+impl PearlStage<BobaUpdate> for TweenPlayer {
+    fn update(pearl: &Pearl<Self>, delta: &f32, resources: &mut BobaResources) -> BobaResult {
+        pearl.borrow_mut()?.advance(*delta, resources);
+        Ok(())
+    }
+}
+
+impl TweenPlayer {
+    /// Starts playing `tween` and returns its id
+    pub fn play(&mut self, tween: impl Tweenable) -> TweenId {
+        let id = TweenId(self.next_id);
+        self.next_id += 1;
+        self.tweens.insert(id, Box::new(tween));
+        id
+    }
+
+    /// Stops the tween with `id` without completing it.
+    ///
+    /// Returns `false` if it was not playing.
+    pub fn stop(&mut self, id: TweenId) -> bool {
+        self.tweens.shift_remove(&id).is_some()
+    }
+
+    /// Stops all tweens without completing them
+    pub fn clear(&mut self) {
+        self.tweens.clear();
+    }
+
+    pub fn is_playing(&self, id: TweenId) -> bool {
+        self.tweens.contains_key(&id)
+    }
+
+    /// The number of tweens that are playing
+    pub fn len(&self) -> usize {
+        self.tweens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tweens.is_empty()
+    }
+
+    /// Advances every tween by `delta` seconds, removing tweens that finished or were stopped
+    pub fn advance(&mut self, delta: f32, resources: &mut BobaResources) {
+        self.tweens
+            .retain(|_, tween| matches!(tween.advance(delta, resources), TweenProgress::Running));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boba_core::{
+        stages::BobaUpdate, BobaResources, Pearl, PearlRegistry, StageClock, StageDelta,
+    };
+    use glam::Vec3;
+
+    use crate::{
+        pearls::BobaTransform,
+        tween::{Tween, TweenLoop},
+    };
+
+    use super::TweenPlayer;
+
+    #[test]
+    fn play_in_update() {
+        let mut clock = StageClock::replaying();
+        clock.queue_replay((0..3).map(|_| StageDelta::new::<BobaUpdate>(0.5)));
+        let mut resources = BobaResources::default();
+        resources.add(clock);
+
+        let transform = Pearl::wrap(BobaTransform::default());
+        let player = Pearl::wrap(TweenPlayer::default());
+        let mut registry = PearlRegistry::default();
+        registry.add(player.clone());
+
+        let mut player_data = player.borrow_mut().unwrap();
+        let finite = player_data.play(Tween::local_position(transform.clone(), Vec3::X, 1.));
+        let infinite = player_data.play(Tween::wait(1.).looping(TweenLoop::Forever));
+        drop(player_data);
+
+        let mut stage = BobaUpdate::default();
+        for _ in 0..3 {
+            boba_core::BobaStage::run(&mut stage, &mut registry, &mut resources).unwrap();
+        }
+
+        let mut player_data = player.borrow_mut().unwrap();
+        assert!(!player_data.is_playing(finite));
+        assert!(player_data.is_playing(infinite));
+        assert!(transform.borrow().unwrap().local_position() == Vec3::X);
+
+        assert!(player_data.stop(infinite));
+        assert!(player_data.is_empty());
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::VecDeque;
+
+use boba_core::{BobaResources, BobaResult};
+use log::error;
+
+use super::{tweening::CompleteCallback, Tween, TweenProgress, Tweenable};
+
+/// Plays tweens one after another.
+///
+/// Time left over when one tween finishes is carried into the next, so sequences do not drift.
+/// The sequence stops as soon as one of its tweens is stopped.
+#[derive(Default)]
+pub struct TweenSequence {
+    steps: VecDeque<Box<dyn Tweenable>>,
+    on_complete: Option<CompleteCallback>,
+}
+
+impl TweenSequence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `tween` to the end of the sequence
+    pub fn then(mut self, tween: impl Tweenable) -> Self {
+        self.steps.push_back(Box::new(tween));
+        self
+    }
+
+    /// Appends a pause of `duration` seconds to the end of the sequence
+    pub fn wait(self, duration: f32) -> Self {
+        self.then(Tween::wait(duration))
+    }
+
+    /// Runs `callback` when the last tween in the sequence completes
+    pub fn on_complete(
+        mut self,
+        callback: impl FnOnce(&mut BobaResources) -> BobaResult + 'static,
+    ) -> Self {
+        self.on_complete = Some(Box::new(callback));
+        self
+    }
+}
+
+impl Tweenable for TweenSequence {
+    fn advance(&mut self, delta: f32, resources: &mut BobaResources) -> TweenProgress {
+        let mut delta = delta;
+        while let Some(step) = self.steps.front_mut() {
+            match step.advance(delta, resources) {
+                TweenProgress::Running => return TweenProgress::Running,
+                TweenProgress::Stopped => return TweenProgress::Stopped,
+                TweenProgress::Finished { leftover } => {
+                    self.steps.pop_front();
+                    delta = leftover;
+                }
+            }
+        }
+
+        if let Some(callback) = self.on_complete.take() {
+            if let Err(e) = callback(resources) {
+                error!("There was an error in a tween sequence completion callback. Error: {e}");
+            }
+        }
+
+        TweenProgress::Finished { leftover: delta }
+    }
+}
@@ -0,0 +1,536 @@
+use boba_core::{BobaResources, BobaResult, Pearl, PearlMutError};
+use glam::{DVec3, Quat, Vec3};
+use log::{error, warn};
+
+use crate::{large_world::world_origin, pearls::BobaTransform};
+
+use super::{Ease, Lerp};
+
+/// The result of advancing something that is tweening.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TweenProgress {
+    /// Still running
+    Running,
+    /// Completed this frame. `leftover` is the part of the delta time that was not needed.
+    Finished { leftover: f32 },
+    /// Stopped early, because its target was destroyed
+    Stopped,
+}
+
+/// Anything that can be advanced over time by a [`TweenPlayer`](super::TweenPlayer).
+pub trait Tweenable: 'static {
+    fn advance(&mut self, delta: f32, resources: &mut BobaResources) -> TweenProgress;
+}
+
+/// How many times a tween plays.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TweenLoop {
+    #[default]
+    Once,
+    Times(u32),
+    Forever,
+}
+
+impl TweenLoop {
+    fn has_next(&self, completed: u32) -> bool {
+        match self {
+            TweenLoop::Once => false,
+            TweenLoop::Times(count) => completed < *count,
+            TweenLoop::Forever => true,
+        }
+    }
+}
+
+/// A callback that runs when a tween or sequence finishes.
+pub(crate) type CompleteCallback = Box<dyn FnOnce(&mut BobaResources) -> BobaResult>;
+
+type PropertySetter<T, V> = Box<dyn FnMut(&mut T, V)>;
+
+/// Samples a property of a pearl at a given eased progress.
+trait TweenTrack {
+    /// Called once, right before the first sample
+    fn start(&mut self) -> Result<(), PearlMutError>;
+    fn sample(&mut self, t: f32) -> Result<(), PearlMutError>;
+}
+
+struct PropertyTrack<T, V> {
+    pearl: Pearl<T>,
+    from: Option<V>,
+    to: V,
+    get: Option<fn(&T) -> V>,
+    set: PropertySetter<T, V>,
+}
+
+impl<T, V: Lerp> TweenTrack for PropertyTrack<T, V> {
+    fn start(&mut self) -> Result<(), PearlMutError> {
+        if let (None, Some(get)) = (&self.from, self.get) {
+            self.from = Some(get(&*self.pearl.borrow_mut()?));
+        }
+
+        Ok(())
+    }
+
+    fn sample(&mut self, t: f32) -> Result<(), PearlMutError> {
+        let Some(from) = &self.from else {
+            return Ok(());
+        };
+
+        let value = from.lerp(&self.to, t);
+        (self.set)(&mut *self.pearl.borrow_mut()?, value);
+        Ok(())
+    }
+}
+
+/// Moves the local position of a transform.
+///
+/// The local position of a root is relative to the world origin,
+/// so both ends are moved with the origin to keep the same absolute path.
+struct PositionTrack {
+    transform: Pearl<BobaTransform>,
+    from: Option<Vec3>,
+    to: Vec3,
+    /// The world origin that the ends are relative to
+    origin: DVec3,
+}
+
+impl PositionTrack {
+    fn settle_origin(&mut self, transform: &BobaTransform) {
+        let Some(world_origin) = world_origin() else {
+            return;
+        };
+
+        if self.origin != world_origin && transform.is_root() {
+            let offset = (self.origin - world_origin).as_vec3();
+            self.to += offset;
+            if let Some(from) = &mut self.from {
+                *from += offset;
+            }
+        }
+
+        self.origin = world_origin;
+    }
+}
+
+impl TweenTrack for PositionTrack {
+    fn start(&mut self) -> Result<(), PearlMutError> {
+        let transform = self.transform.clone();
+        let transform = transform.borrow_mut()?;
+        self.settle_origin(&transform);
+        self.from.get_or_insert(transform.local_position());
+        Ok(())
+    }
+
+    fn sample(&mut self, t: f32) -> Result<(), PearlMutError> {
+        let transform = self.transform.clone();
+        let mut transform = transform.borrow_mut()?;
+        self.settle_origin(&transform);
+        if let Some(from) = self.from {
+            transform.set_local_position(from.lerp(self.to, t));
+        }
+
+        Ok(())
+    }
+}
+
+/// Interpolates a single value over time.
+///
+/// Tweens are created with one of the constructors and configured with the builder methods:
+/// ```ignore
+/// let tween = Tween::local_position(door, Vec3::Y * 3., 2.)
+///     .ease(Ease::CubicInOut)
+///     .delay(0.5)
+///     .on_complete(|_| {
+///         println!("door opened");
+///         Ok(())
+///     });
+/// ```
+/// A tween stops without completing if its target pearl is destroyed.
+pub struct Tween {
+    track: Option<Box<dyn TweenTrack>>,
+    duration: f32,
+    delay: f32,
+    ease: Ease,
+    looping: TweenLoop,
+    ping_pong: bool,
+    on_complete: Option<CompleteCallback>,
+
+    started: bool,
+    elapsed: f32,
+    completed_loops: u32,
+}
+
+impl Tween {
+    fn new(track: Option<Box<dyn TweenTrack>>, duration: f32) -> Self {
+        Self {
+            track,
+            duration: duration.max(0.),
+            delay: 0.,
+            ease: Ease::Linear,
+            looping: TweenLoop::Once,
+            ping_pong: false,
+            on_complete: None,
+
+            started: false,
+            elapsed: 0.,
+            completed_loops: 0,
+        }
+    }
+
+    /// Creates a tween that changes nothing, which is useful as a pause in a [`TweenSequence`](super::TweenSequence)
+    pub fn wait(duration: f32) -> Self {
+        Self::new(None, duration)
+    }
+
+    /// Creates a tween that moves the local position of `transform` from its current position to `to`.
+    ///
+    /// If the transform is a root, `to` is moved with the [`FloatingOrigin`](crate::large_world::FloatingOrigin).
+    pub fn local_position(transform: Pearl<BobaTransform>, to: Vec3, duration: f32) -> Self {
+        let track = PositionTrack {
+            transform,
+            from: None,
+            to,
+            origin: world_origin().unwrap_or_default(),
+        };
+
+        Self::new(Some(Box::new(track)), duration)
+    }
+
+    /// Creates a tween that rotates `transform` from its current local rotation to `to`
+    pub fn local_rotation(transform: Pearl<BobaTransform>, to: Quat, duration: f32) -> Self {
+        Self::transform(
+            transform,
+            to,
+            duration,
+            BobaTransform::local_rotation,
+            BobaTransform::set_local_rotation,
+        )
+    }
+
+    /// Creates a tween that scales `transform` from its current local scale to `to`
+    pub fn local_scale(transform: Pearl<BobaTransform>, to: Vec3, duration: f32) -> Self {
+        Self::transform(
+            transform,
+            to,
+            duration,
+            BobaTransform::local_scale,
+            BobaTransform::set_local_scale,
+        )
+    }
+
+    /// Creates a tween that interpolates any value in `pearl` from `from` to `to`.
+    ///
+    /// `set` is called with the interpolated value every frame.
+    pub fn value<T, V>(
+        pearl: Pearl<T>,
+        from: V,
+        to: V,
+        duration: f32,
+        set: impl FnMut(&mut T, V) + 'static,
+    ) -> Self
+    where
+        T: 'static,
+        V: Lerp,
+    {
+        let track = PropertyTrack {
+            pearl,
+            from: Some(from.clone()),
+            to,
+            get: None,
+            set: Box::new(set),
+        };
+
+        Self::new(Some(Box::new(track)), duration)
+    }
+
+    fn transform<V: Lerp>(
+        transform: Pearl<BobaTransform>,
+        to: V,
+        duration: f32,
+        get: fn(&BobaTransform) -> V,
+        set: fn(&mut BobaTransform, V),
+    ) -> Self {
+        let track = PropertyTrack {
+            pearl: transform,
+            from: None,
+            to,
+            get: Some(get),
+            set: Box::new(set),
+        };
+
+        Self::new(Some(Box::new(track)), duration)
+    }
+
+    /// Sets the easing curve. Defaults to [`Ease::Linear`].
+    pub fn ease(mut self, ease: Ease) -> Self {
+        self.ease = ease;
+        self
+    }
+
+    /// Waits `delay` seconds before the tween starts. The delay is only applied once, not every loop.
+    pub fn delay(mut self, delay: f32) -> Self {
+        self.delay = delay.max(0.);
+        self
+    }
+
+    /// Sets how many times the tween plays. Defaults to [`TweenLoop::Once`].
+    pub fn looping(mut self, looping: TweenLoop) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Makes every other loop play backwards.
+    ///
+    /// Combined with [`TweenLoop::Times(2)`](TweenLoop::Times), the tween goes to its target and back.
+    pub fn ping_pong(mut self) -> Self {
+        self.ping_pong = true;
+        self
+    }
+
+    /// Runs `callback` when the tween completes. It is not called if the tween is stopped.
+    pub fn on_complete(
+        mut self,
+        callback: impl FnOnce(&mut BobaResources) -> BobaResult + 'static,
+    ) -> Self {
+        self.on_complete = Some(Box::new(callback));
+        self
+    }
+
+    /// Samples the track at the linear progress `t` of the current loop
+    fn sample(&mut self, t: f32) -> Result<(), PearlMutError> {
+        let Some(track) = &mut self.track else {
+            return Ok(());
+        };
+
+        let t = match self.ping_pong && self.completed_loops % 2 == 1 {
+            true => 1. - t,
+            false => t,
+        };
+
+        track.sample(self.ease.apply(t))
+    }
+
+    fn step(&mut self, delta: f32) -> Result<TweenProgress, PearlMutError> {
+        let mut delta = delta;
+        if self.delay > 0. {
+            let consumed = delta.min(self.delay);
+            self.delay -= consumed;
+            delta -= consumed;
+            if self.delay > 0. {
+                return Ok(TweenProgress::Running);
+            }
+        }
+
+        if !self.started {
+            if let Some(track) = &mut self.track {
+                track.start()?;
+            }
+            self.started = true;
+        }
+
+        self.elapsed += delta;
+        while self.elapsed >= self.duration {
+            let next_loop = self.completed_loops + 1;
+            if !self.looping.has_next(next_loop) {
+                self.sample(1.)?;
+                self.completed_loops = next_loop;
+                let leftover = self.elapsed - self.duration;
+                return Ok(TweenProgress::Finished { leftover });
+            }
+
+            self.completed_loops = next_loop;
+            self.elapsed -= self.duration;
+
+            // a zero length loop could never finish on its own
+            if self.duration == 0. {
+                self.elapsed = 0.;
+                break;
+            }
+        }
+
+        let t = match self.duration > 0. {
+            true => self.elapsed / self.duration,
+            false => 1.,
+        };
+
+        self.sample(t)?;
+        Ok(TweenProgress::Running)
+    }
+}
+
+impl Tweenable for Tween {
+    fn advance(&mut self, delta: f32, resources: &mut BobaResources) -> TweenProgress {
+        let progress = match self.step(delta) {
+            Ok(progress) => progress,
+            Err(PearlMutError::Destroyed) => return TweenProgress::Stopped,
+            Err(e) => {
+                warn!("Skipping tween frame, because its target could not be borrowed. Error: {e}");
+                return TweenProgress::Running;
+            }
+        };
+
+        if let TweenProgress::Finished { .. } = progress {
+            if let Some(callback) = self.on_complete.take() {
+                if let Err(e) = callback(resources) {
+                    error!("There was an error in a tween completion callback. Error: {e}");
+                }
+            }
+        }
+
+        progress
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boba_core::{BobaResources, Pearl};
+    use glam::Vec3;
+
+    use crate::{
+        large_world::FloatingOrigin,
+        pearls::BobaTransform,
+        tween::{Ease, TweenLoop, TweenProgress, TweenSequence, Tweenable},
+    };
+
+    use super::Tween;
+
+    fn position(transform: &Pearl<BobaTransform>) -> Vec3 {
+        transform.borrow().unwrap().local_position()
+    }
+
+    fn assert_vec(a: Vec3, b: Vec3) {
+        assert!(a.abs_diff_eq(b, 0.0001), "{a} != {b}");
+    }
+
+    #[test]
+    fn move_to_target() {
+        let mut resources = BobaResources::default();
+        let transform = Pearl::wrap(BobaTransform::from_position(Vec3::X));
+        let mut tween = Tween::local_position(transform.clone(), Vec3::X * 3., 1.);
+
+        assert!(tween.advance(0.5, &mut resources) == TweenProgress::Running);
+        assert_vec(position(&transform), Vec3::X * 2.);
+
+        let progress = tween.advance(0.75, &mut resources);
+        assert!(progress == TweenProgress::Finished { leftover: 0.25 });
+        assert_vec(position(&transform), Vec3::X * 3.);
+    }
+
+    #[test]
+    fn move_with_floating_origin() {
+        let mut resources = BobaResources::default();
+        let transform = Pearl::wrap(BobaTransform::from_position(Vec3::X));
+        let mut tween = Tween::local_position(transform.clone(), Vec3::X * 3., 1.);
+        tween.advance(0.5, &mut resources);
+
+        // both ends move with the origin, so the tween still ends at the same absolute position
+        let mut origin = FloatingOrigin::default();
+        origin.rebase(Vec3::X * 10., &mut resources);
+        tween.advance(0.25, &mut resources);
+        assert_vec(position(&transform), Vec3::X * -7.5);
+        tween.advance(0.25, &mut resources);
+        assert_vec(position(&transform), Vec3::X * -7.);
+    }
+
+    #[test]
+    fn delay_and_ease() {
+        let mut resources = BobaResources::default();
+        let transform = Pearl::wrap(BobaTransform::default());
+        let mut tween = Tween::local_scale(transform.clone(), Vec3::splat(2.), 1.)
+            .delay(1.)
+            .ease(Ease::QuadIn);
+
+        tween.advance(0.5, &mut resources);
+        assert_vec(transform.borrow().unwrap().local_scale(), Vec3::ONE);
+
+        tween.advance(1., &mut resources);
+        assert_vec(transform.borrow().unwrap().local_scale(), Vec3::splat(1.25));
+    }
+
+    #[test]
+    fn ping_pong_loops() {
+        let mut resources = BobaResources::default();
+        let transform = Pearl::wrap(BobaTransform::default());
+        let mut tween = Tween::local_position(transform.clone(), Vec3::Y, 1.)
+            .looping(TweenLoop::Times(2))
+            .ping_pong();
+
+        tween.advance(1.25, &mut resources);
+        assert_vec(position(&transform), Vec3::Y * 0.75);
+
+        let progress = tween.advance(1., &mut resources);
+        assert!(matches!(progress, TweenProgress::Finished { .. }));
+        assert_vec(position(&transform), Vec3::ZERO);
+    }
+
+    #[test]
+    fn loop_forever() {
+        let mut resources = BobaResources::default();
+        let transform = Pearl::wrap(BobaTransform::default());
+        let mut tween =
+            Tween::local_position(transform.clone(), Vec3::Z, 1.).looping(TweenLoop::Forever);
+
+        for _ in 0..10 {
+            assert!(tween.advance(0.25, &mut resources) == TweenProgress::Running);
+        }
+        assert_vec(position(&transform), Vec3::Z * 0.5);
+    }
+
+    #[test]
+    fn value_and_completion() {
+        struct Fade {
+            alpha: f32,
+        }
+
+        struct Completed(bool);
+
+        let mut resources = BobaResources::default();
+        resources.add(Completed(false));
+        let fade = Pearl::wrap(Fade { alpha: 1. });
+        let mut tween = Tween::value(fade.clone(), 1., 0., 2., |fade: &mut Fade, alpha| {
+            fade.alpha = alpha
+        })
+        .on_complete(|resources| {
+            resources.get_mut::<Completed>()?.0 = true;
+            Ok(())
+        });
+
+        tween.advance(1., &mut resources);
+        assert!(fade.borrow().unwrap().alpha == 0.5);
+        assert!(!resources.get::<Completed>().unwrap().0);
+
+        tween.advance(1., &mut resources);
+        assert!(fade.borrow().unwrap().alpha == 0.);
+        assert!(resources.get::<Completed>().unwrap().0);
+    }
+
+    #[test]
+    fn stop_on_destroyed_target() {
+        let mut resources = BobaResources::default();
+        let transform = Pearl::wrap(BobaTransform::default());
+        let mut tween = Tween::local_position(transform.clone(), Vec3::X, 1.);
+
+        tween.advance(0.5, &mut resources);
+        transform.destroy().unwrap();
+        assert!(tween.advance(0.1, &mut resources) == TweenProgress::Stopped);
+    }
+
+    #[test]
+    fn sequence() {
+        let mut resources = BobaResources::default();
+        let transform = Pearl::wrap(BobaTransform::default());
+        let mut sequence = TweenSequence::new()
+            .then(Tween::local_position(transform.clone(), Vec3::X, 1.))
+            .wait(1.)
+            .then(Tween::local_position(transform.clone(), Vec3::ZERO, 1.));
+
+        sequence.advance(1.5, &mut resources);
+        assert_vec(position(&transform), Vec3::X);
+
+        sequence.advance(1., &mut resources);
+        assert_vec(position(&transform), Vec3::X * 0.5);
+
+        let progress = sequence.advance(1., &mut resources);
+        assert!(progress == TweenProgress::Finished { leftover: 0.5 });
+        assert_vec(position(&transform), Vec3::ZERO);
+    }
+}
@@ -0,0 +1,171 @@
+use std::{
+    any::{Any, TypeId},
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use hashbrown::HashMap;
+
+use crate::PearlId;
+
+/// The number of pearls stored together in each chunk
+pub(crate) const CHUNK_SLOTS: usize = 64;
+
+thread_local! {
+    /// The arena of every pearl type that was wrapped on this thread
+    static ARENAS: RefCell<HashMap<TypeId, Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+/// A block of pearls of the same type.
+///
+/// The data of every pearl in the chunk is stored next to each other in memory,
+/// apart from the ids and handle counts, so that updating pearls only walks over their data.
+pub(crate) struct PearlChunk<T> {
+    data: [RefCell<Option<T>>; CHUNK_SLOTS],
+    /// The id of the pearl in each slot, so that weak pearls can tell it apart from an older pearl in the same slot
+    ids: [Cell<Option<PearlId>>; CHUNK_SLOTS],
+    /// The number of pearl handles to each slot. A slot is free when there are none.
+    handles: [Cell<u32>; CHUNK_SLOTS],
+}
+
+impl<T> PearlChunk<T> {
+    fn empty() -> Self {
+        Self {
+            data: std::array::from_fn(|_| RefCell::new(None)),
+            ids: std::array::from_fn(|_| Cell::new(None)),
+            handles: std::array::from_fn(|_| Cell::new(0)),
+        }
+    }
+
+    /// Gets the data of the pearl with `id`
+    pub fn data(&self, id: &PearlId) -> &RefCell<Option<T>> {
+        &self.data[id.slot()]
+    }
+
+    /// Adds a handle to the pearl with `id`
+    pub fn retain(&self, id: &PearlId) {
+        let handles = &self.handles[id.slot()];
+        handles.set(handles.get() + 1);
+    }
+
+    /// Adds a handle to the pearl with `id`, if its slot has not been freed or reused since
+    pub fn retain_if(&self, id: &PearlId) -> bool {
+        let slot = id.slot();
+        if self.handles[slot].get() == 0 || self.ids[slot].get() != Some(*id) {
+            return false;
+        }
+
+        self.retain(id);
+        true
+    }
+
+    /// Removes a handle from the pearl with `id`, dropping its data and freeing the slot when it was the last one
+    pub fn release(&self, id: &PearlId) {
+        let slot = id.slot();
+        let handles = self.handles[slot].get() - 1;
+        self.handles[slot].set(handles);
+        if handles == 0 {
+            drop(self.data[slot].take());
+        }
+    }
+}
+
+/// Every chunk of a single pearl type on this thread.
+///
+/// Chunks are kept until the thread ends, and their slots are reused by new pearls of the same type.
+struct PearlArena<T> {
+    chunks: Vec<Rc<PearlChunk<T>>>,
+    /// The chunk and slot index where the search for the next free slot starts
+    cursor: (usize, usize),
+}
+
+impl<T> Default for PearlArena<T> {
+    fn default() -> Self {
+        Self {
+            chunks: Vec::new(),
+            cursor: (0, 0),
+        }
+    }
+}
+
+impl<T> PearlArena<T> {
+    /// Finds a free slot after the cursor, searching at most one chunk worth of slots before adding a new chunk
+    fn free_slot(&mut self) -> (Rc<PearlChunk<T>>, usize) {
+        for _ in 0..CHUNK_SLOTS.min(self.chunks.len() * CHUNK_SLOTS) {
+            let (chunk, slot) = self.cursor;
+            self.cursor = match slot + 1 {
+                CHUNK_SLOTS => ((chunk + 1) % self.chunks.len(), 0),
+                next => (chunk, next),
+            };
+
+            if self.chunks[chunk].handles[slot].get() == 0 {
+                return (self.chunks[chunk].clone(), slot);
+            }
+        }
+
+        let chunk = Rc::new(PearlChunk::empty());
+        self.chunks.push(chunk.clone());
+        self.cursor = (self.chunks.len() - 1, 1);
+        (chunk, 0)
+    }
+}
+
+/// Stores `item` with the other pearls of its type, returning its new id and chunk with one handle
+pub(crate) fn store<T: 'static>(item: T) -> (PearlId, Rc<PearlChunk<T>>) {
+    let (chunk, slot) = ARENAS.with(|arenas| {
+        arenas
+            .borrow_mut()
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::<PearlArena<T>>::default())
+            .downcast_mut::<PearlArena<T>>()
+            .unwrap()
+            .free_slot()
+    });
+
+    let id = PearlId::new(slot);
+    chunk.ids[slot].set(Some(id));
+    chunk.handles[slot].set(1);
+    *chunk.data[slot].borrow_mut() = Some(item);
+    (id, chunk)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::Pearl;
+
+    struct Slotted(u32);
+
+    #[test]
+    fn reuse_freed_slot() {
+        let first = Pearl::wrap(Slotted(1));
+        let weak = first.downgrade();
+        let clone = first.clone();
+        drop(first);
+        assert_eq!(weak.upgrade().unwrap().borrow().unwrap().0, 1);
+
+        drop(clone);
+        assert!(weak.upgrade().is_none());
+
+        let pearls: Vec<_> = (0..super::CHUNK_SLOTS as u32 * 2)
+            .map(|i| Pearl::wrap(Slotted(i)))
+            .collect();
+        assert!(weak.upgrade().is_none());
+        for (i, pearl) in pearls.iter().enumerate() {
+            assert_eq!(pearl.borrow().unwrap().0, i as u32);
+        }
+    }
+
+    #[test]
+    fn drop_data_with_last_handle() {
+        let shared = Rc::new(());
+        let pearl = Pearl::wrap(shared.clone());
+        let clone = pearl.clone();
+        drop(pearl);
+        assert_eq!(Rc::strong_count(&shared), 2);
+
+        drop(clone);
+        assert_eq!(Rc::strong_count(&shared), 1);
+    }
+}
@@ -0,0 +1,217 @@
+use std::{any::type_name, cell::BorrowMutError, collections::VecDeque};
+
+use thiserror::Error;
+
+use crate::{BobaResources, ResourceError};
+
+/// A single delta time that was consumed by a stage.
+///
+/// The stage is identified by its [`stage_name`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageDelta {
+    pub stage: String,
+    pub delta: f32,
+}
+
+impl StageDelta {
+    pub fn new<Stage: 'static>(delta: f32) -> Self {
+        Self {
+            stage: stage_name::<Stage>(),
+            delta,
+        }
+    }
+}
+
+/// Gets the name of `Stage` without module paths, like `BobaUpdate` or `MilkTeaEvent<KeyboardInput>`.
+///
+/// This keeps recorded deltas valid when a stage is moved to another module or crate.
+pub fn stage_name<Stage: 'static>() -> String {
+    let full = type_name::<Stage>();
+    let mut name = String::with_capacity(full.len());
+    let mut segment_start = 0;
+    let mut chars = full.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ':' if chars.peek() == Some(&':') => {
+                chars.next();
+                name.truncate(segment_start);
+            }
+            c if c.is_alphanumeric() || c == '_' => name.push(c),
+            c => {
+                name.push(c);
+                segment_start = name.len();
+            }
+        }
+    }
+    name
+}
+
+/// An error returned by [`StageClock::delta`].
+#[derive(Debug, Error)]
+pub enum StageClockError {
+    #[error("Stage '{0}' requested a delta, but the replay has no deltas left.")]
+    Exhausted(String),
+    #[error("Replay desync. Expected a delta for stage '{expected}', but stage '{found}' requested one.")]
+    Mismatch { expected: String, found: String },
+    #[error("Could not access the stage clock. Error: {0}")]
+    Resource(ResourceError<BorrowMutError>),
+}
+
+/// The way a [`StageClock`] provides its deltas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockMode {
+    Live,
+    Recording,
+    Replaying,
+}
+
+/// Provides delta times for stages that are driven by time, like [`BobaUpdate`](crate::stages::BobaUpdate).
+///
+/// When added as a resource, every delta can be recorded, or played back from a previous recording
+/// in place of the system clock. If no clock resource is present, stages use their own live timing.
+pub struct StageClock {
+    mode: ClockMode,
+    deltas: VecDeque<StageDelta>,
+}
+
+impl Default for StageClock {
+    fn default() -> Self {
+        Self::live()
+    }
+}
+
+impl StageClock {
+    /// Creates a clock that passes all live deltas through unchanged
+    pub fn live() -> Self {
+        Self {
+            mode: ClockMode::Live,
+            deltas: Default::default(),
+        }
+    }
+
+    /// Creates a clock that passes live deltas through and keeps a record of them
+    pub fn recording() -> Self {
+        Self {
+            mode: ClockMode::Recording,
+            deltas: Default::default(),
+        }
+    }
+
+    /// Creates a clock that ignores live deltas and plays back queued deltas instead
+    pub fn replaying() -> Self {
+        Self {
+            mode: ClockMode::Replaying,
+            deltas: Default::default(),
+        }
+    }
+
+    pub fn mode(&self) -> ClockMode {
+        self.mode
+    }
+
+    /// Queues deltas to be played back when the clock is replaying
+    pub fn queue_replay(&mut self, deltas: impl IntoIterator<Item = StageDelta>) {
+        self.deltas.extend(deltas);
+    }
+
+    /// Removes and returns all deltas recorded since the last call
+    pub fn take_recorded(&mut self) -> Vec<StageDelta> {
+        self.deltas.drain(..).collect()
+    }
+
+    /// Gets the delta that `Stage` should use, given the `live` delta it measured itself.
+    pub fn delta<Stage: 'static>(&mut self, live: f32) -> Result<f32, StageClockError> {
+        match self.mode {
+            ClockMode::Live => Ok(live),
+            ClockMode::Recording => {
+                self.deltas.push_back(StageDelta::new::<Stage>(live));
+                Ok(live)
+            }
+            ClockMode::Replaying => {
+                let stage = stage_name::<Stage>();
+                let Some(next) = self.deltas.pop_front() else {
+                    return Err(StageClockError::Exhausted(stage));
+                };
+
+                if next.stage != stage {
+                    return Err(StageClockError::Mismatch {
+                        expected: next.stage,
+                        found: stage,
+                    });
+                }
+
+                Ok(next.delta)
+            }
+        }
+    }
+
+    /// Gets the delta that `Stage` should use from the [`StageClock`] in `resources`.
+    ///
+    /// Falls back to the `live` delta if there is no clock resource.
+    pub fn resolve<Stage: 'static>(
+        resources: &BobaResources,
+        live: f32,
+    ) -> Result<f32, StageClockError> {
+        match resources.get_mut::<StageClock>() {
+            Ok(mut clock) => clock.delta::<Stage>(live),
+            Err(ResourceError::NotFound(_)) => Ok(live),
+            Err(e) => Err(StageClockError::Resource(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{stage_name, BobaResources, StageClock, StageClockError};
+
+    struct TestStage1;
+    struct TestStage2;
+    struct Generic<T>(T);
+
+    #[test]
+    fn stage_names() {
+        assert!(stage_name::<TestStage1>() == "TestStage1");
+        assert!(stage_name::<Generic<TestStage2>>() == "Generic<TestStage2>");
+        assert!(stage_name::<Generic<(u8, Vec<TestStage1>)>>() == "Generic<(u8, Vec<TestStage1>)>");
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let mut recorder = StageClock::recording();
+        assert!(recorder.delta::<TestStage1>(0.5).unwrap() == 0.5);
+        assert!(recorder.delta::<TestStage2>(0.25).unwrap() == 0.25);
+
+        let mut replayer = StageClock::replaying();
+        replayer.queue_replay(recorder.take_recorded());
+        assert!(replayer.delta::<TestStage1>(1.).unwrap() == 0.5);
+        assert!(replayer.delta::<TestStage2>(1.).unwrap() == 0.25);
+        assert!(matches!(
+            replayer.delta::<TestStage1>(1.),
+            Err(StageClockError::Exhausted(_))
+        ));
+    }
+
+    #[test]
+    fn replay_mismatch() {
+        let mut recorder = StageClock::recording();
+        recorder.delta::<TestStage1>(0.5).unwrap();
+
+        let mut replayer = StageClock::replaying();
+        replayer.queue_replay(recorder.take_recorded());
+        assert!(matches!(
+            replayer.delta::<TestStage2>(1.),
+            Err(StageClockError::Mismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn resolve() {
+        let mut resources = BobaResources::default();
+        assert!(StageClock::resolve::<TestStage1>(&resources, 0.5).unwrap() == 0.5);
+
+        let mut clock = StageClock::replaying();
+        clock.queue_replay(vec![crate::StageDelta::new::<TestStage1>(2.)]);
+        resources.add(clock);
+        assert!(StageClock::resolve::<TestStage1>(&resources, 0.5).unwrap() == 2.);
+    }
+}
@@ -0,0 +1,479 @@
+use std::{any::Any, collections::VecDeque};
+
+use log::error;
+
+use crate::{BobaResources, BobaResult};
+
+/// An undoable change to pearls or resources.
+pub trait BobaCommand: 'static {
+    /// Applies the change. This is called when the command is executed, and again on redo.
+    fn apply(&mut self, resources: &BobaResources) -> BobaResult;
+
+    /// Reverts the change made by the last call to `apply`.
+    fn revert(&mut self, resources: &BobaResources) -> BobaResult;
+
+    /// Attempts to merge an already applied `next` command into this one.
+    ///
+    /// Only called for commands executed in the same group, for example every step of a drag.
+    /// Returns `true` if `next` was merged, in which case it will not be stored on its own.
+    fn merge(&mut self, _next: &Self) -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+}
+
+/// An undoable command that replaces the resource of type `T` with a new value.
+///
+/// Consecutive commands for the same resource type merge when executed in a group.
+pub struct SetResource<T> {
+    /// Swapped with the resource, so it holds the previous value while the command is applied
+    value: T,
+}
+
+impl<T> SetResource<T> {
+    pub fn new(value: T) -> Self {
+        Self { value }
+    }
+}
+
+impl<T: 'static> BobaCommand for SetResource<T> {
+    fn apply(&mut self, resources: &BobaResources) -> BobaResult {
+        std::mem::swap(&mut *resources.get_mut::<T>()?, &mut self.value);
+        Ok(())
+    }
+
+    fn revert(&mut self, resources: &BobaResources) -> BobaResult {
+        std::mem::swap(&mut *resources.get_mut::<T>()?, &mut self.value);
+        Ok(())
+    }
+
+    fn merge(&mut self, _: &Self) -> bool {
+        // this command already holds the value from before the group, and the resource holds the newest value
+        true
+    }
+}
+
+/// A history of executed [`BobaCommand`]s that can be undone and redone.
+///
+/// Usually stored as a resource so that any pearl or stage can edit through it.
+pub struct CommandHistory {
+    undo: VecDeque<CommandGroup>,
+    redo: Vec<CommandGroup>,
+    group: Option<CommandGroup>,
+    limit: usize,
+}
+
+impl Default for CommandHistory {
+    fn default() -> Self {
+        Self::new(100)
+    }
+}
+
+impl CommandHistory {
+    /// Creates a new history that keeps at most `limit` undo entries
+    pub fn new(limit: usize) -> Self {
+        Self {
+            undo: Default::default(),
+            redo: Default::default(),
+            group: None,
+            limit,
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Sets the maximum number of undo entries, dropping the oldest entries if necessary
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        self.enforce_limit();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// The number of entries that can be undone
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// The number of entries that can be redone
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    /// Removes all entries from the history
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.group = None;
+    }
+
+    /// Applies `command` and stores it in the history.
+    ///
+    /// Clears all redo entries. If the command fails to apply, it is not stored.
+    pub fn execute<Command>(
+        &mut self,
+        mut command: Command,
+        resources: &BobaResources,
+    ) -> BobaResult
+    where
+        Command: BobaCommand,
+    {
+        command.apply(resources)?;
+        self.redo.clear();
+
+        match &mut self.group {
+            Some(group) => group.push(Box::new(command)),
+            None => {
+                let mut group = CommandGroup::default();
+                group.push(Box::new(command));
+                self.push_undo(group);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Starts a group. All commands executed until [`end_group`](Self::end_group) are undone and redone as one entry.
+    ///
+    /// Does nothing if a group is already open.
+    pub fn begin_group(&mut self) {
+        if self.group.is_none() {
+            self.group = Some(CommandGroup::default());
+        }
+    }
+
+    /// Ends the current group and stores it in the history
+    pub fn end_group(&mut self) {
+        let Some(group) = self.group.take() else {
+            return;
+        };
+
+        if !group.commands.is_empty() {
+            self.push_undo(group);
+        }
+    }
+
+    /// Reverts the most recent entry.
+    ///
+    /// Any open group is ended first. Returns `false` if there was nothing to undo.
+    /// If a command fails to revert, the commands of the entry that were already reverted are applied again,
+    /// the entry stays on the undo stack, and the error is returned.
+    pub fn undo(&mut self, resources: &BobaResources) -> anyhow::Result<bool> {
+        self.end_group();
+        let Some(mut group) = self.undo.pop_back() else {
+            return Ok(false);
+        };
+
+        if let Err(e) = group.revert(resources) {
+            self.undo.push_back(group);
+            return Err(e);
+        }
+
+        self.redo.push(group);
+        Ok(true)
+    }
+
+    /// Applies the most recently undone entry again.
+    ///
+    /// Returns `false` if there was nothing to redo.
+    /// If a command fails to apply, the commands of the entry that were already applied are reverted again,
+    /// the entry stays on the redo stack, and the error is returned.
+    pub fn redo(&mut self, resources: &BobaResources) -> anyhow::Result<bool> {
+        self.end_group();
+        let Some(mut group) = self.redo.pop() else {
+            return Ok(false);
+        };
+
+        if let Err(e) = group.apply(resources) {
+            self.redo.push(group);
+            return Err(e);
+        }
+
+        self.undo.push_back(group);
+        self.enforce_limit();
+        Ok(true)
+    }
+
+    fn push_undo(&mut self, group: CommandGroup) {
+        self.undo.push_back(group);
+        self.enforce_limit();
+    }
+
+    fn enforce_limit(&mut self) {
+        while self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+}
+
+#[derive(Default)]
+struct CommandGroup {
+    commands: Vec<Box<dyn DynamicCommand>>,
+}
+
+impl CommandGroup {
+    fn push(&mut self, command: Box<dyn DynamicCommand>) {
+        if let Some(last) = self.commands.last_mut() {
+            if last.dynamic_merge(command.as_any()) {
+                return;
+            }
+        }
+
+        self.commands.push(command);
+    }
+
+    /// Applies every command in order, or none of them if one fails
+    fn apply(&mut self, resources: &BobaResources) -> BobaResult {
+        for index in 0..self.commands.len() {
+            if let Err(e) = self.commands[index].dynamic_apply(resources) {
+                for command in self.commands[..index].iter_mut().rev() {
+                    if let Err(e) = command.dynamic_revert(resources) {
+                        error!("Could not roll back a partially applied command group. Error: {e}");
+                    }
+                }
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reverts every command in reverse order, or none of them if one fails
+    fn revert(&mut self, resources: &BobaResources) -> BobaResult {
+        for index in (0..self.commands.len()).rev() {
+            if let Err(e) = self.commands[index].dynamic_revert(resources) {
+                for command in self.commands[index + 1..].iter_mut() {
+                    if let Err(e) = command.dynamic_apply(resources) {
+                        error!(
+                            "Could not roll back a partially reverted command group. Error: {e}"
+                        );
+                    }
+                }
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+trait DynamicCommand {
+    fn as_any(&self) -> &dyn Any;
+    fn dynamic_apply(&mut self, resources: &BobaResources) -> BobaResult;
+    fn dynamic_revert(&mut self, resources: &BobaResources) -> BobaResult;
+    fn dynamic_merge(&mut self, next: &dyn Any) -> bool;
+}
+
+impl<Command> DynamicCommand for Command
+where
+    Command: BobaCommand,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn dynamic_apply(&mut self, resources: &BobaResources) -> BobaResult {
+        self.apply(resources)
+    }
+
+    fn dynamic_revert(&mut self, resources: &BobaResources) -> BobaResult {
+        self.revert(resources)
+    }
+
+    fn dynamic_merge(&mut self, next: &dyn Any) -> bool {
+        match next.downcast_ref::<Command>() {
+            Some(next) => self.merge(next),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BobaCommand, BobaResources, BobaResult, CommandHistory, SetResource};
+
+    struct Counter(i32);
+
+    struct AddCommand(i32);
+
+    impl BobaCommand for AddCommand {
+        fn apply(&mut self, resources: &BobaResources) -> BobaResult {
+            resources.get_mut::<Counter>()?.0 += self.0;
+            Ok(())
+        }
+
+        fn revert(&mut self, resources: &BobaResources) -> BobaResult {
+            resources.get_mut::<Counter>()?.0 -= self.0;
+            Ok(())
+        }
+
+        fn merge(&mut self, next: &Self) -> bool {
+            self.0 += next.0;
+            true
+        }
+    }
+
+    struct Broken(bool);
+
+    /// Fails to apply or revert while the [`Broken`] resource is true
+    struct BreakableCommand;
+
+    impl BobaCommand for BreakableCommand {
+        fn apply(&mut self, resources: &BobaResources) -> BobaResult {
+            match resources.get::<Broken>()?.0 {
+                true => Err(anyhow::anyhow!("broken")),
+                false => Ok(()),
+            }
+        }
+
+        fn revert(&mut self, resources: &BobaResources) -> BobaResult {
+            self.apply(resources)
+        }
+    }
+
+    fn counter(resources: &BobaResources) -> i32 {
+        resources.get::<Counter>().unwrap().0
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut resources = BobaResources::default();
+        resources.add(Counter(0));
+        let mut history = CommandHistory::default();
+
+        history.execute(AddCommand(1), &resources).unwrap();
+        history.execute(AddCommand(2), &resources).unwrap();
+        assert!(counter(&resources) == 3);
+
+        assert!(history.undo(&resources).unwrap());
+        assert!(counter(&resources) == 1);
+        assert!(history.redo(&resources).unwrap());
+        assert!(counter(&resources) == 3);
+
+        history.undo(&resources).unwrap();
+        history.undo(&resources).unwrap();
+        assert!(!history.undo(&resources).unwrap());
+        assert!(counter(&resources) == 0);
+        assert!(history.redo_len() == 2);
+
+        history.execute(AddCommand(5), &resources).unwrap();
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn group_merge() {
+        let mut resources = BobaResources::default();
+        resources.add(Counter(0));
+        let mut history = CommandHistory::default();
+
+        history.begin_group();
+        for _ in 0..10 {
+            history.execute(AddCommand(1), &resources).unwrap();
+        }
+        history.end_group();
+
+        assert!(counter(&resources) == 10);
+        assert!(history.undo_len() == 1);
+        assert!(history.undo.back().unwrap().commands.len() == 1);
+
+        history.undo(&resources).unwrap();
+        assert!(counter(&resources) == 0);
+    }
+
+    #[test]
+    fn limit() {
+        let mut resources = BobaResources::default();
+        resources.add(Counter(0));
+        let mut history = CommandHistory::new(3);
+
+        for _ in 0..5 {
+            history.execute(AddCommand(1), &resources).unwrap();
+        }
+
+        assert!(history.undo_len() == 3);
+        while history.undo(&resources).unwrap() {}
+        assert!(counter(&resources) == 2);
+    }
+
+    #[test]
+    fn failed_apply() {
+        let resources = BobaResources::default();
+        let mut history = CommandHistory::default();
+
+        assert!(history.execute(AddCommand(1), &resources).is_err());
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn failed_group_rolls_back() {
+        let mut resources = BobaResources::default();
+        resources.add(Counter(0));
+        resources.add(Broken(false));
+        let mut history = CommandHistory::default();
+
+        history.begin_group();
+        history.execute(AddCommand(1), &resources).unwrap();
+        history.execute(BreakableCommand, &resources).unwrap();
+        history.execute(AddCommand(2), &resources).unwrap();
+        history.end_group();
+
+        // the last command was reverted before the failure, so it is applied again
+        resources.get_mut::<Broken>().unwrap().0 = true;
+        assert!(history.undo(&resources).is_err());
+        assert!(counter(&resources) == 3);
+        assert!(history.undo_len() == 1 && history.redo_len() == 0);
+
+        resources.get_mut::<Broken>().unwrap().0 = false;
+        assert!(history.undo(&resources).unwrap());
+        assert!(counter(&resources) == 0);
+
+        // the first command was applied before the failure, so it is reverted again
+        resources.get_mut::<Broken>().unwrap().0 = true;
+        assert!(history.redo(&resources).is_err());
+        assert!(counter(&resources) == 0);
+        assert!(history.undo_len() == 0 && history.redo_len() == 1);
+
+        resources.get_mut::<Broken>().unwrap().0 = false;
+        assert!(history.redo(&resources).unwrap());
+        assert!(counter(&resources) == 3);
+    }
+
+    #[test]
+    fn resource_edits() {
+        let mut resources = BobaResources::default();
+        resources.add(Counter(0));
+        let mut history = CommandHistory::default();
+
+        history
+            .execute(SetResource::new(Counter(5)), &resources)
+            .unwrap();
+        history.begin_group();
+        history
+            .execute(SetResource::new(Counter(6)), &resources)
+            .unwrap();
+        history
+            .execute(SetResource::new(Counter(7)), &resources)
+            .unwrap();
+        history.end_group();
+        assert!(counter(&resources) == 7);
+        assert!(history.undo_len() == 2);
+
+        history.undo(&resources).unwrap();
+        assert!(counter(&resources) == 5);
+        history.undo(&resources).unwrap();
+        assert!(counter(&resources) == 0);
+        history.redo(&resources).unwrap();
+        history.redo(&resources).unwrap();
+        assert!(counter(&resources) == 7);
+    }
+}
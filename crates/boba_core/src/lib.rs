@@ -1,12 +1,21 @@
+mod arena;
+mod clock;
+mod history;
 mod pearl;
 mod registry;
 mod resources;
+mod snapshot;
 mod stage;
+mod system;
 
+pub use clock::*;
+pub use history::*;
 pub use pearl::*;
 pub use registry::*;
 pub use resources::*;
+pub use snapshot::*;
 pub use stage::*;
+pub use system::*;
 
 pub mod stages;
 
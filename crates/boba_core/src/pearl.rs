@@ -2,13 +2,16 @@ use std::{
     cell::{BorrowError, BorrowMutError, Ref, RefCell, RefMut},
     hash::Hash,
     ops::DerefMut,
-    rc::Rc,
+    rc::{Rc, Weak},
     sync::atomic::AtomicU64,
 };
 
 use thiserror::Error;
 
-use crate::{BobaResources, BobaResult, BobaStage, StageRegistrar};
+use crate::{
+    arena::{self, PearlChunk, CHUNK_SLOTS},
+    BobaResources, BobaResult, BobaStage, StageRegistrar,
+};
 
 /// The Id for a Pearl
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
@@ -17,16 +20,22 @@ pub struct PearlId {
 }
 
 impl PearlId {
-    /// Creates a new PearlId.
+    /// Creates a new PearlId for a pearl stored at `slot` in its chunk.
     ///
-    /// It increments a atomic u64 and uses that as its id value, so each Id will be constructed with a unique value.
+    /// It increments a atomic u64 and combines that with the slot index, so each Id will be constructed with a unique value.
     /// This will never run out because there are more ids than there are atoms in the universe.
-    fn new() -> Self {
+    pub(crate) fn new(slot: usize) -> Self {
         static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let count = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
         Self {
-            _id: COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+            _id: count * CHUNK_SLOTS as u64 + (slot % CHUNK_SLOTS) as u64,
         }
     }
+
+    /// The index of the pearl in its chunk
+    pub(crate) fn slot(&self) -> usize {
+        self._id as usize % CHUNK_SLOTS
+    }
 }
 
 /// An error returned by [`Pearl::is_destroyed`].
@@ -55,9 +64,12 @@ pub enum PearlMutError {
 /// The core data management object in BobaEngine.
 ///
 /// It is useful for multiple objects to hold references to the same struct.
+///
+/// Pearls of the same type are stored next to each other in chunks, and each pearl is a handle into a chunk.
+/// The data of a pearl is dropped when the last handle to it is dropped.
 pub struct Pearl<T> {
     id: PearlId,
-    data: Rc<RefCell<Option<T>>>,
+    chunk: Rc<PearlChunk<T>>,
 }
 
 impl<T> Eq for Pearl<T> {}
@@ -74,35 +86,48 @@ impl<T> Hash for Pearl<T> {
     }
 }
 
-impl<T> Pearl<T> {
+impl<T: 'static> Pearl<T> {
     pub fn wrap(item: T) -> Self {
-        Self {
-            id: PearlId::new(),
-            data: Rc::new(RefCell::new(Some(item))),
-        }
+        let (id, chunk) = arena::store(item);
+        Self { id, chunk }
     }
 }
 
 impl<T> Clone for Pearl<T> {
     fn clone(&self) -> Self {
+        self.chunk.retain(&self.id);
         Self {
             id: self.id,
-            data: self.data.clone(),
+            chunk: self.chunk.clone(),
         }
     }
 }
 
+impl<T> Drop for Pearl<T> {
+    fn drop(&mut self) {
+        self.chunk.release(&self.id);
+    }
+}
+
 impl<T> Pearl<T> {
     /// Gets the unique id of the current pearl
     pub fn id(&self) -> &PearlId {
         &self.id
     }
 
+    /// Creates a [`WeakPearl`] that points to the same data, without keeping it alive
+    pub fn downgrade(&self) -> WeakPearl<T> {
+        WeakPearl {
+            id: self.id,
+            chunk: Rc::downgrade(&self.chunk),
+        }
+    }
+
     /// Destroys the current pearl.
     ///
     /// Can fail if the pearl is currently being borrowed somewhere else.
     pub fn destroy(&self) -> Result<(), BorrowMutError> {
-        let mut borrow = self.data.try_borrow_mut()?;
+        let mut borrow = self.data().try_borrow_mut()?;
         drop(std::mem::replace(borrow.deref_mut(), None));
 
         Ok(())
@@ -112,7 +137,7 @@ impl<T> Pearl<T> {
     ///
     /// Can fail if the pearl is currently being borrowed somewhere else.
     pub fn is_destroyed(&self) -> Result<bool, BorrowError> {
-        let borrow = self.data.try_borrow()?;
+        let borrow = self.data().try_borrow()?;
         Ok(borrow.is_none())
     }
 
@@ -120,7 +145,7 @@ impl<T> Pearl<T> {
     ///
     /// Can fail if the pearl is either already destroyed, or the pearl is already mutably borrowed.
     pub fn borrow(&self) -> Result<Ref<T>, PearlError> {
-        let borrow = match self.data.as_ref().try_borrow() {
+        let borrow = match self.data().try_borrow() {
             Ok(borrow) => borrow,
             Err(e) => return Err(PearlError::Borrowed(e)),
         };
@@ -136,7 +161,7 @@ impl<T> Pearl<T> {
     ///
     /// Can fail if the pearl is either already destroyed, or the pearl is already borrowed.
     pub fn borrow_mut(&self) -> Result<RefMut<T>, PearlMutError> {
-        let borrow = match self.data.as_ref().try_borrow_mut() {
+        let borrow = match self.data().try_borrow_mut() {
             Ok(borrow) => borrow,
             Err(e) => return Err(PearlMutError::Borrowed(e)),
         };
@@ -147,6 +172,60 @@ impl<T> Pearl<T> {
 
         Ok(RefMut::map(borrow, |data| data.as_mut().unwrap()))
     }
+
+    fn data(&self) -> &RefCell<Option<T>> {
+        self.chunk.data(&self.id)
+    }
+}
+
+/// A non owning reference to a [`Pearl`].
+///
+/// Used to break reference cycles, like a child pointing back to its parent.
+pub struct WeakPearl<T> {
+    id: PearlId,
+    chunk: Weak<PearlChunk<T>>,
+}
+
+impl<T> Eq for WeakPearl<T> {}
+
+impl<T> PartialEq for WeakPearl<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Hash for WeakPearl<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<T> Clone for WeakPearl<T> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            chunk: self.chunk.clone(),
+        }
+    }
+}
+
+impl<T> WeakPearl<T> {
+    /// Gets the unique id of the pearl this points to
+    pub fn id(&self) -> &PearlId {
+        &self.id
+    }
+
+    /// Gets the [`Pearl`] this points to.
+    ///
+    /// Returns `None` if every [`Pearl`] that pointed to the data has been dropped.
+    pub fn upgrade(&self) -> Option<Pearl<T>> {
+        let chunk = self.chunk.upgrade()?;
+        if !chunk.retain_if(&self.id) {
+            return None;
+        }
+
+        Some(Pearl { id: self.id, chunk })
+    }
 }
 
 /// Base trait for being able to register stages with the boba system
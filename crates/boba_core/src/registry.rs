@@ -1,21 +1,17 @@
-use std::{
-    any::{Any, TypeId},
-    cell::BorrowError,
-    hash::Hash,
-};
+use std::any::{Any, TypeId};
 
 use hashbrown::HashMap;
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
 use log::{error, info, warn};
 
-use crate::{BobaResources, BobaStage, Pearl, PearlId, PearlStage, RegisterPearlStages};
+use crate::{BobaResources, BobaStage, Pearl, PearlSnapshot, PearlStage, RegisterPearlStages};
 
 /// A collection of pearls, all registered to their respective stages.
 ///
 /// The registry may be told to `run_stage`, and all pearls associated with that stage will be updated.
 #[derive(Default)]
 pub struct PearlRegistry {
-    pearls: HashMap<TypeId, Box<dyn Any>>,
+    pearls: HashMap<TypeId, Box<dyn AnyPearlCollection>>,
 }
 
 impl PearlRegistry {
@@ -26,22 +22,49 @@ impl PearlRegistry {
         T::register(pearl, self);
     }
 
-    /// Updates all pearls associated with a specific stage
+    /// Updates all pearls associated with a specific stage.
+    ///
+    /// Pearls are updated one type at a time, in the order each type was first added to the stage,
+    /// and in insertion order within a type. Pearls of different types are not updated in the
+    /// global order they were added in, so a pearl may be updated before a pearl of another type
+    /// that was added ahead of it. Stages that depend on the order across types should be split
+    /// into separate stages.
     pub fn run_stage<Stage>(&mut self, data: &Stage::Data, resources: &mut BobaResources)
     where
         Stage: BobaStage,
     {
         let stageid = TypeId::of::<Stage>();
         let Some(any_collection) = self.pearls.get_mut(&stageid) else {
-            info!("PearlRegistry ran stage {}, but there were no associated pearls.", std::any::type_name::<Stage>());
+            info!(
+                "PearlRegistry ran stage {}, but there were no associated pearls.",
+                std::any::type_name::<Stage>()
+            );
             return;
         };
 
         any_collection
+            .as_any_mut()
             .downcast_mut::<PearlCollection<Stage>>()
             .unwrap()
             .update(data, resources);
     }
+
+    /// Gets the type names and counts of all pearls registered to the stage with id `stageid`.
+    ///
+    /// Counts may include destroyed pearls that have not been cleaned up by a stage run yet.
+    pub(crate) fn stage_pearls(&self, stageid: &TypeId) -> Vec<PearlSnapshot> {
+        match self.pearls.get(stageid) {
+            Some(collection) => collection.snapshot(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Iterates over the ids and names of every stage that has pearls registered to it
+    pub(crate) fn stages(&self) -> impl Iterator<Item = (TypeId, &'static str)> + '_ {
+        self.pearls
+            .iter()
+            .map(|(id, collection)| (*id, collection.stage_name()))
+    }
 }
 
 pub trait StageRegistrar {
@@ -62,6 +85,7 @@ impl StageRegistrar for PearlRegistry {
         match self.pearls.get_mut(&stageid) {
             Some(any_collection) => {
                 any_collection
+                    .as_any_mut()
                     .downcast_mut::<PearlCollection<Stage>>()
                     .unwrap()
                     .add(pearl);
@@ -75,11 +99,16 @@ impl StageRegistrar for PearlRegistry {
     }
 }
 
+/// All pearls registered to a single stage.
+///
+/// Pearls are grouped into batches by their concrete type, so that each type is updated with a single
+/// monomorphized loop, instead of a virtual call per pearl.
+/// The pearls of each type are themselves stored next to each other in chunks, so a batch walks memory in order.
 struct PearlCollection<Stage>
 where
     Stage: BobaStage,
 {
-    pearls: IndexSet<Box<dyn PearlRunner<Stage>>>,
+    batches: IndexMap<TypeId, Box<dyn PearlBatchRunner<Stage>>>,
 }
 
 impl<Stage> PearlCollection<Stage>
@@ -88,7 +117,7 @@ where
 {
     pub fn new() -> Self {
         Self {
-            pearls: Default::default(),
+            batches: Default::default(),
         }
     }
 
@@ -96,80 +125,198 @@ where
     where
         Update: PearlStage<Stage>,
     {
-        self.pearls.insert(Box::new(pearl));
+        let typeid = TypeId::of::<Update>();
+        match self.batches.get_mut(&typeid) {
+            Some(runner) => {
+                runner
+                    .as_any_mut()
+                    .downcast_mut::<PearlBatch<Update>>()
+                    .unwrap()
+                    .pearls
+                    .insert(pearl);
+            }
+            None => {
+                let mut batch = PearlBatch::<Update>::new();
+                batch.pearls.insert(pearl);
+                self.batches.insert(typeid, Box::new(batch));
+            }
+        }
     }
 
     pub fn update(&mut self, data: &Stage::Data, resources: &mut BobaResources) {
-        self.pearls
-            .retain(|runner| !matches!(runner.dynamic_update(data, resources), PearlStatus::Dead));
+        for batch in self.batches.values_mut() {
+            batch.update(data, resources);
+        }
     }
 }
 
-#[derive(Debug)]
-enum PearlStatus {
-    Dead,
-    Alive,
-    BorrowError(BorrowError),
+trait AnyPearlCollection {
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn stage_name(&self) -> &'static str;
+    fn snapshot(&self) -> Vec<PearlSnapshot>;
 }
 
-trait PearlRunner<Stage>
+impl<Stage> AnyPearlCollection for PearlCollection<Stage>
 where
     Stage: BobaStage,
 {
-    fn id(&self) -> &PearlId;
-    fn dynamic_update(&self, data: &Stage::Data, resources: &mut BobaResources) -> PearlStatus;
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn stage_name(&self) -> &'static str {
+        std::any::type_name::<Stage>()
+    }
+
+    fn snapshot(&self) -> Vec<PearlSnapshot> {
+        self.batches
+            .values()
+            .map(|batch| PearlSnapshot {
+                name: batch.type_name().into(),
+                count: batch.len(),
+            })
+            .collect()
+    }
+}
+
+/// The handles of every pearl in a stage that share the same type.
+struct PearlBatch<Update> {
+    pearls: IndexSet<Pearl<Update>>,
 }
 
-impl<Stage, Update> PearlRunner<Stage> for Pearl<Update>
+impl<Update> PearlBatch<Update> {
+    fn new() -> Self {
+        Self {
+            pearls: Default::default(),
+        }
+    }
+}
+
+trait PearlBatchRunner<Stage>
+where
+    Stage: BobaStage,
+{
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn type_name(&self) -> &'static str;
+    fn len(&self) -> usize;
+    fn update(&mut self, data: &Stage::Data, resources: &mut BobaResources);
+}
+
+impl<Stage, Update> PearlBatchRunner<Stage> for PearlBatch<Update>
 where
     Stage: BobaStage,
     Update: PearlStage<Stage>,
 {
-    fn id(&self) -> &PearlId {
-        self.id()
-    }
-
-    fn dynamic_update(
-        &self,
-        data: &<Stage as BobaStage>::Data,
-        resources: &mut BobaResources,
-    ) -> PearlStatus {
-        match self.is_destroyed() {
-            Ok(false) => (),
-            Ok(true) => return PearlStatus::Dead,
-            Err(e) => {
-                warn!("Could not check status of pearl. Error: {e}");
-                return PearlStatus::BorrowError(e);
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<Update>()
+    }
+
+    fn len(&self) -> usize {
+        self.pearls.len()
+    }
+
+    fn update(&mut self, data: &Stage::Data, resources: &mut BobaResources) {
+        self.pearls.retain(|pearl| {
+            match pearl.is_destroyed() {
+                Ok(false) => (),
+                Ok(true) => return false,
+                Err(e) => {
+                    warn!("Could not check status of pearl. Error: {e}");
+                    return true;
+                }
             }
-        }
 
-        if let Err(e) = Update::update(self, data, resources) {
-            error!(
-                "There was an error while updating Pearl<{}>. Error: {e}",
-                std::any::type_name::<Update>()
-            );
-        };
+            if let Err(e) = Update::update(pearl, data, resources) {
+                error!(
+                    "There was an error while updating Pearl<{}>. Error: {e}",
+                    std::any::type_name::<Update>()
+                );
+            };
 
-        PearlStatus::Alive
+            true
+        });
     }
 }
 
-impl<Stage> Eq for Box<dyn PearlRunner<Stage>> where Stage: BobaStage {}
+#[cfg(test)]
+mod tests {
+    use crate::{
+        register_pearl_stages, BobaResources, BobaResult, BobaStage, Pearl, PearlRegistry,
+        PearlStage,
+    };
 
-impl<Stage> PartialEq for Box<dyn PearlRunner<Stage>>
-where
-    Stage: BobaStage,
-{
-    fn eq(&self, other: &Self) -> bool {
-        self.id() == other.id()
+    struct TestStage;
+
+    impl BobaStage for TestStage {
+        type Data = ();
+
+        fn run(
+            &mut self,
+            registry: &mut PearlRegistry,
+            resources: &mut BobaResources,
+        ) -> BobaResult {
+            registry.run_stage::<TestStage>(&(), resources);
+            Ok(())
+        }
     }
-}
 
-impl<Stage> Hash for Box<dyn PearlRunner<Stage>>
-where
-    Stage: BobaStage,
-{
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.id().hash(state);
+    struct TestPearl1(u32);
+    struct TestPearl2(u32);
+
+    register_pearl_stages!(TestPearl1: TestStage);
+    register_pearl_stages!(TestPearl2: TestStage);
+
+    impl PearlStage<TestStage> for TestPearl1 {
+        fn update(pearl: &Pearl<Self>, _: &(), _: &mut BobaResources) -> BobaResult {
+            pearl.borrow_mut()?.0 += 1;
+            Ok(())
+        }
+    }
+
+    impl PearlStage<TestStage> for TestPearl2 {
+        fn update(pearl: &Pearl<Self>, _: &(), _: &mut BobaResources) -> BobaResult {
+            pearl.borrow_mut()?.0 += 2;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn run_stage() {
+        let mut registry = PearlRegistry::default();
+        let mut resources = BobaResources::default();
+
+        let pearl1 = Pearl::wrap(TestPearl1(0));
+        let pearl2 = Pearl::wrap(TestPearl2(0));
+        let pearl3 = Pearl::wrap(TestPearl1(0));
+        registry.add(pearl1.clone());
+        registry.add(pearl2.clone());
+        registry.add(pearl3.clone());
+
+        TestStage.run(&mut registry, &mut resources).unwrap();
+        TestStage.run(&mut registry, &mut resources).unwrap();
+
+        assert!(pearl1.borrow().unwrap().0 == 2);
+        assert!(pearl2.borrow().unwrap().0 == 4);
+        assert!(pearl3.borrow().unwrap().0 == 2);
+    }
+
+    #[test]
+    fn remove_destroyed() {
+        let mut registry = PearlRegistry::default();
+        let mut resources = BobaResources::default();
+
+        let pearl1 = Pearl::wrap(TestPearl1(0));
+        let pearl2 = Pearl::wrap(TestPearl1(0));
+        registry.add(pearl1.clone());
+        registry.add(pearl2.clone());
+
+        pearl1.destroy().unwrap();
+        TestStage.run(&mut registry, &mut resources).unwrap();
+
+        assert!(pearl2.borrow().unwrap().0 == 1);
     }
 }
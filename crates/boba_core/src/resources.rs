@@ -18,6 +18,7 @@ pub enum ResourceError<E> {
 #[derive(Default)]
 pub struct BobaResources {
     resources: HashMap<TypeId, Box<dyn Any>>,
+    names: HashMap<TypeId, &'static str>,
 }
 
 impl BobaResources {
@@ -59,6 +60,7 @@ impl BobaResources {
     {
         self.resources
             .insert(TypeId::of::<T>(), Box::new(RefCell::new(resource)));
+        self.names.insert(TypeId::of::<T>(), type_name::<T>());
     }
 
     pub fn remove<T>(&mut self) -> Option<T>
@@ -66,8 +68,14 @@ impl BobaResources {
         T: 'static,
     {
         let any = self.resources.remove(&TypeId::of::<T>())?;
+        self.names.remove(&TypeId::of::<T>());
         Some(any.downcast::<RefCell<T>>().unwrap().into_inner())
     }
+
+    /// Gets the type names of all the resources in this collection
+    pub fn type_names(&self) -> Vec<&'static str> {
+        self.names.values().copied().collect()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,223 @@
+use std::fmt::Display;
+
+use hashbrown::HashSet;
+use serde::{Deserialize, Serialize};
+
+use crate::{BobaResources, PearlRegistry, StageCollection};
+
+/// The type name and number of pearls of a single type registered to a stage.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PearlSnapshot {
+    pub name: String,
+    pub count: usize,
+}
+
+/// A stage and all the pearls registered to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageSnapshot {
+    pub name: String,
+    pub pearls: Vec<PearlSnapshot>,
+}
+
+/// A named [`StageCollection`] and its stages in execution order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageCollectionSnapshot {
+    pub name: String,
+    pub stages: Vec<StageSnapshot>,
+}
+
+/// A structured view of which stages run in what order, which pearls they update, and which resources exist.
+///
+/// Stages that have pearls registered to them, but are not part of any captured collection,
+/// are listed in `unscheduled`. These are usually run directly, like events.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BobaSnapshot {
+    pub collections: Vec<StageCollectionSnapshot>,
+    pub unscheduled: Vec<StageSnapshot>,
+    pub resources: Vec<String>,
+}
+
+impl BobaSnapshot {
+    /// Captures a snapshot of `registry` and `resources` with the stages in each named collection
+    pub fn capture(
+        registry: &PearlRegistry,
+        resources: &BobaResources,
+        collections: &[(&str, &StageCollection)],
+    ) -> Self {
+        let mut scheduled = HashSet::new();
+        let collections = collections
+            .iter()
+            .map(|(name, collection)| StageCollectionSnapshot {
+                name: name.to_string(),
+                stages: collection
+                    .stages()
+                    .map(|(id, stage_name)| {
+                        scheduled.insert(id);
+                        StageSnapshot {
+                            name: stage_name.into(),
+                            pearls: registry.stage_pearls(&id),
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let mut unscheduled = registry
+            .stages()
+            .filter(|(id, _)| !scheduled.contains(id))
+            .map(|(id, name)| StageSnapshot {
+                name: name.into(),
+                pearls: registry.stage_pearls(&id),
+            })
+            .collect::<Vec<_>>();
+        unscheduled.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut resources = resources
+            .type_names()
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        resources.sort();
+
+        Self {
+            collections,
+            unscheduled,
+            resources,
+        }
+    }
+
+    /// Serializes the snapshot into pretty printed JSON
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl Display for BobaSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for collection in &self.collections {
+            writeln!(f, "Stages ({})", collection.name)?;
+            write_stages(f, &collection.stages)?;
+        }
+
+        writeln!(f, "Unscheduled Stages")?;
+        write_stages(f, &self.unscheduled)?;
+
+        writeln!(f, "Resources")?;
+        for (index, resource) in self.resources.iter().enumerate() {
+            let branch = branch(index, self.resources.len());
+            writeln!(f, "{branch}{resource}")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn branch(index: usize, len: usize) -> &'static str {
+    match index + 1 == len {
+        true => "└── ",
+        false => "├── ",
+    }
+}
+
+fn write_stages(f: &mut std::fmt::Formatter<'_>, stages: &[StageSnapshot]) -> std::fmt::Result {
+    for (index, stage) in stages.iter().enumerate() {
+        let last = index + 1 == stages.len();
+        writeln!(f, "{}{}", branch(index, stages.len()), stage.name)?;
+
+        let indent = match last {
+            true => "    ",
+            false => "│   ",
+        };
+        for (index, pearl) in stage.pearls.iter().enumerate() {
+            let branch = branch(index, stage.pearls.len());
+            writeln!(f, "{indent}{branch}{} ({})", pearl.name, pearl.count)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        register_pearl_stages, BobaResources, BobaResult, BobaSnapshot, BobaStage, Pearl,
+        PearlRegistry, PearlStage, StageCollection,
+    };
+
+    struct TestStage1;
+    struct TestStage2;
+    struct TestPearl;
+    struct TestResource;
+
+    impl BobaStage for TestStage1 {
+        type Data = ();
+
+        fn run(&mut self, _: &mut PearlRegistry, _: &mut BobaResources) -> BobaResult {
+            Ok(())
+        }
+    }
+
+    impl BobaStage for TestStage2 {
+        type Data = ();
+
+        fn run(&mut self, _: &mut PearlRegistry, _: &mut BobaResources) -> BobaResult {
+            Ok(())
+        }
+    }
+
+    register_pearl_stages!(TestPearl: TestStage1, TestStage2);
+
+    impl PearlStage<TestStage1> for TestPearl {
+        fn update(_: &Pearl<Self>, _: &(), _: &mut BobaResources) -> BobaResult {
+            Ok(())
+        }
+    }
+
+    impl PearlStage<TestStage2> for TestPearl {
+        fn update(_: &Pearl<Self>, _: &(), _: &mut BobaResources) -> BobaResult {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn capture() {
+        let mut registry = PearlRegistry::default();
+        registry.add(Pearl::wrap(TestPearl));
+        registry.add(Pearl::wrap(TestPearl));
+
+        let mut resources = BobaResources::default();
+        resources.add(TestResource);
+
+        let mut stages = StageCollection::default();
+        stages.insert(TestStage1);
+
+        let snapshot = BobaSnapshot::capture(&registry, &resources, &[("main", &stages)]);
+
+        let main = &snapshot.collections[0];
+        assert!(main.stages.len() == 1);
+        assert!(main.stages[0].name.ends_with("TestStage1"));
+        assert!(main.stages[0].pearls[0].name.ends_with("TestPearl"));
+        assert!(main.stages[0].pearls[0].count == 2);
+        assert!(snapshot.unscheduled.len() == 1);
+        assert!(snapshot.unscheduled[0].name.ends_with("TestStage2"));
+        assert!(snapshot.resources[0].ends_with("TestResource"));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut stages = StageCollection::default();
+        stages.insert(TestStage1);
+        stages.insert(TestStage2);
+
+        let snapshot = BobaSnapshot::capture(
+            &PearlRegistry::default(),
+            &BobaResources::default(),
+            &[("main", &stages)],
+        );
+        let json = snapshot.to_json().unwrap();
+        let parsed = serde_json::from_str::<BobaSnapshot>(&json).unwrap();
+
+        assert!(parsed == snapshot);
+        assert!(snapshot.to_string().contains("TestStage2"));
+    }
+}
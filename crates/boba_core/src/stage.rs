@@ -57,6 +57,53 @@ impl StageCollection {
         }
     }
 
+    /// Inserts a stage right before the `Target` stage.
+    ///
+    /// If an instance of this stage already exists in this collection, it will be removed first.
+    /// If `Target` is not in the collection, the stage is appended instead.
+    pub fn insert_before<Target, Stage>(&mut self, stage: Stage)
+    where
+        Target: BobaStage,
+        Stage: BobaStage,
+    {
+        self.insert_relative::<Target, Stage>(stage, 0);
+    }
+
+    /// Inserts a stage right after the `Target` stage.
+    ///
+    /// If an instance of this stage already exists in this collection, it will be removed first.
+    /// If `Target` is not in the collection, the stage is appended instead.
+    pub fn insert_after<Target, Stage>(&mut self, stage: Stage)
+    where
+        Target: BobaStage,
+        Stage: BobaStage,
+    {
+        self.insert_relative::<Target, Stage>(stage, 1);
+    }
+
+    fn insert_relative<Target, Stage>(&mut self, stage: Stage, offset: usize)
+    where
+        Target: BobaStage,
+        Stage: BobaStage,
+    {
+        let stageid = TypeId::of::<Stage>();
+        self.stages.shift_remove(&stageid);
+
+        let target = self.stages.get_index_of(&TypeId::of::<Target>());
+        let (index, _) = self.stages.insert_full(stageid, Box::new(stage));
+        if let Some(target) = target {
+            self.stages.move_index(index, target + offset);
+        }
+    }
+
+    /// Returns true if the collection contains an instance of `Stage`
+    pub fn contains<Stage>(&self) -> bool
+    where
+        Stage: BobaStage,
+    {
+        self.stages.contains_key(&TypeId::of::<Stage>())
+    }
+
     /// Removes a stage from the collection
     pub fn remove<Stage>(&mut self)
     where
@@ -66,6 +113,13 @@ impl StageCollection {
         self.stages.shift_remove(&stageid);
     }
 
+    /// Iterates over the ids and names of every stage in order
+    pub(crate) fn stages(&self) -> impl Iterator<Item = (TypeId, &'static str)> + '_ {
+        self.stages
+            .iter()
+            .map(|(id, runner)| (*id, runner.type_name()))
+    }
+
     /// Runs all the corresponding pearls in a registry with each BobaStage in order
     pub fn run(&mut self, registry: &mut PearlRegistry, resources: &mut BobaResources) {
         for runner in self.stages.values_mut() {
@@ -76,6 +130,7 @@ impl StageCollection {
 
 trait DynamicStageRunner {
     fn type_id(&self) -> TypeId;
+    fn type_name(&self) -> &'static str;
     fn dynamic_run(&mut self, registry: &mut PearlRegistry, resources: &mut BobaResources);
 }
 
@@ -87,6 +142,10 @@ where
         TypeId::of::<Stage>()
     }
 
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<Stage>()
+    }
+
     fn dynamic_run(&mut self, registry: &mut PearlRegistry, resources: &mut BobaResources) {
         if let Err(e) = self.run(registry, resources) {
             error!(
@@ -189,4 +248,24 @@ mod tests {
         assert!(collection.stages.len() == 2);
         assert!(collection.stages[1].type_id() == TypeId::of::<TestStage3>());
     }
+
+    #[test]
+    fn insert_relative() {
+        let mut collection = StageCollection::default();
+
+        collection.insert(TestStage1);
+        collection.insert(TestStage2);
+        collection.insert_before::<TestStage2, _>(TestStage3);
+        assert!(collection.stages[1].type_id() == TypeId::of::<TestStage3>());
+
+        collection.insert_after::<TestStage2, _>(TestStage1);
+        assert!(collection.stages.len() == 3);
+        assert!(collection.stages[0].type_id() == TypeId::of::<TestStage3>());
+        assert!(collection.stages[2].type_id() == TypeId::of::<TestStage1>());
+
+        collection.remove::<TestStage2>();
+        collection.insert_after::<TestStage2, _>(TestStage3);
+        assert!(collection.stages[1].type_id() == TypeId::of::<TestStage3>());
+        assert!(collection.contains::<TestStage1>() && !collection.contains::<TestStage2>());
+    }
 }
@@ -0,0 +1,127 @@
+use crate::{BobaResources, BobaResult, BobaStage, PearlRegistry};
+
+use super::StageTimer;
+
+/// Runs pearls at a fixed timestep, independent of the frame rate.
+///
+/// Elapsed time is accumulated every frame, and the stage runs once for every full timestep that has passed.
+/// Pearls receive the timestep as their delta. Runs after [`BobaUpdate`](super::BobaUpdate).
+pub struct BobaFixedUpdate {
+    timestep: f32,
+    max_steps: u32,
+    accumulator: f32,
+    timer: StageTimer,
+}
+
+impl Default for BobaFixedUpdate {
+    fn default() -> Self {
+        Self::new(1. / 50.)
+    }
+}
+
+impl BobaFixedUpdate {
+    /// Creates a new stage that runs every `timestep` seconds
+    pub fn new(timestep: f32) -> Self {
+        Self {
+            timestep,
+            max_steps: 8,
+            accumulator: 0.,
+            timer: Default::default(),
+        }
+    }
+
+    pub fn timestep(&self) -> f32 {
+        self.timestep
+    }
+
+    /// Sets the maximum number of steps run in a single frame.
+    ///
+    /// Any time beyond that is dropped, so that a slow frame cannot cause an ever growing backlog.
+    pub fn set_max_steps(&mut self, max_steps: u32) {
+        self.max_steps = max_steps;
+    }
+}
+
+impl BobaStage for BobaFixedUpdate {
+    type Data = f32;
+
+    fn run(&mut self, registry: &mut PearlRegistry, resources: &mut BobaResources) -> BobaResult {
+        self.accumulator += self.timer.delta::<BobaFixedUpdate>(resources)?;
+        self.accumulator = self.accumulator.min(self.timestep * self.max_steps as f32);
+
+        while self.accumulator >= self.timestep {
+            registry.run_stage::<BobaFixedUpdate>(&self.timestep, resources);
+            self.accumulator -= self.timestep;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        register_pearl_stages, BobaResources, BobaResult, BobaStage, Pearl, PearlRegistry,
+        PearlStage, StageClock, StageDelta,
+    };
+
+    use super::BobaFixedUpdate;
+
+    #[derive(Default)]
+    struct StepCounter {
+        steps: u32,
+        time: f32,
+    }
+
+    register_pearl_stages!(StepCounter: BobaFixedUpdate);
+
+    impl PearlStage<BobaFixedUpdate> for StepCounter {
+        fn update(pearl: &Pearl<Self>, delta: &f32, _: &mut BobaResources) -> BobaResult {
+            let mut counter = pearl.borrow_mut()?;
+            counter.steps += 1;
+            counter.time += delta;
+            Ok(())
+        }
+    }
+
+    fn run_frames(stage: &mut BobaFixedUpdate, deltas: &[f32]) -> Pearl<StepCounter> {
+        let mut clock = StageClock::replaying();
+        clock.queue_replay(
+            deltas
+                .iter()
+                .map(|d| StageDelta::new::<BobaFixedUpdate>(*d)),
+        );
+
+        let mut resources = BobaResources::default();
+        resources.add(clock);
+
+        let mut registry = PearlRegistry::default();
+        let counter = Pearl::wrap(StepCounter::default());
+        registry.add(counter.clone());
+
+        for _ in deltas {
+            stage.run(&mut registry, &mut resources).unwrap();
+        }
+
+        counter
+    }
+
+    #[test]
+    fn accumulate_steps() {
+        let mut stage = BobaFixedUpdate::new(0.5);
+        let counter = run_frames(&mut stage, &[0.25, 0.25, 0.75, 0.]);
+
+        let counter = counter.borrow().unwrap();
+        assert!(counter.steps == 2);
+        assert!(counter.time == 1.);
+    }
+
+    #[test]
+    fn max_steps() {
+        let mut stage = BobaFixedUpdate::new(0.5);
+        stage.set_max_steps(2);
+        let counter = run_frames(&mut stage, &[10.]);
+
+        assert!(counter.borrow().unwrap().steps == 2);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{BobaResources, BobaResult, BobaStage, PearlRegistry, ResourceError};
+
+use super::{FrameTimer, StageTimer};
+
+/// The first stage of every frame.
+///
+/// Runs before any input handling or updates, and is useful for resetting per frame state.
+/// Starts the [`FrameTimer`] for the frame, adding it to the resources if it is missing.
+#[derive(Default)]
+pub struct BobaFirst;
+
+impl BobaStage for BobaFirst {
+    type Data = ();
+
+    fn run(&mut self, registry: &mut PearlRegistry, resources: &mut BobaResources) -> BobaResult {
+        let ticked = resources
+            .get_mut::<FrameTimer>()
+            .map(|mut timer| timer.tick());
+        match ticked {
+            Ok(()) => (),
+            Err(ResourceError::NotFound(_)) => {
+                let mut timer = FrameTimer::default();
+                timer.tick();
+                resources.add(timer);
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        registry.run_stage::<BobaFirst>(&(), resources);
+        Ok(())
+    }
+}
+
+/// Runs pearls with the frame delta time, before [`BobaUpdate`](super::BobaUpdate).
+#[derive(Default)]
+pub struct BobaPreUpdate {
+    timer: StageTimer,
+}
+
+impl BobaStage for BobaPreUpdate {
+    type Data = f32;
+
+    fn run(&mut self, registry: &mut PearlRegistry, resources: &mut BobaResources) -> BobaResult {
+        let delta = self.timer.delta::<BobaPreUpdate>(resources)?;
+        registry.run_stage::<BobaPreUpdate>(&delta, resources);
+        Ok(())
+    }
+}
+
+/// Runs pearls with the frame delta time, after all updates.
+///
+/// Useful for logic that has to see the final state of the frame, like a camera following a target.
+/// Physics stages should be inserted before this stage, for example with
+/// [`StageCollection::insert_after`](crate::StageCollection::insert_after) and [`BobaFixedUpdate`](super::BobaFixedUpdate),
+/// so that late updates see the bodies where physics left them.
+#[derive(Default)]
+pub struct BobaLateUpdate {
+    timer: StageTimer,
+}
+
+impl BobaStage for BobaLateUpdate {
+    type Data = f32;
+
+    fn run(&mut self, registry: &mut PearlRegistry, resources: &mut BobaResources) -> BobaResult {
+        let delta = self.timer.delta::<BobaLateUpdate>(resources)?;
+        registry.run_stage::<BobaLateUpdate>(&delta, resources);
+        Ok(())
+    }
+}
+
+/// Runs pearls right before the frame is drawn.
+#[derive(Default)]
+pub struct BobaPreRender;
+
+impl BobaStage for BobaPreRender {
+    type Data = ();
+
+    fn run(&mut self, registry: &mut PearlRegistry, resources: &mut BobaResources) -> BobaResult {
+        registry.run_stage::<BobaPreRender>(&(), resources);
+        Ok(())
+    }
+}
+
+/// Runs pearls right after the frame is drawn.
+#[derive(Default)]
+pub struct BobaPostRender;
+
+impl BobaStage for BobaPostRender {
+    type Data = ();
+
+    fn run(&mut self, registry: &mut PearlRegistry, resources: &mut BobaResources) -> BobaResult {
+        registry.run_stage::<BobaPostRender>(&(), resources);
+        Ok(())
+    }
+}
+
+/// The last stage of every frame.
+#[derive(Default)]
+pub struct BobaLast;
+
+impl BobaStage for BobaLast {
+    type Data = ();
+
+    fn run(&mut self, registry: &mut PearlRegistry, resources: &mut BobaResources) -> BobaResult {
+        registry.run_stage::<BobaLast>(&(), resources);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread::sleep, time::Duration};
+
+    use crate::{
+        stages::{BobaFirst, BobaLateUpdate, BobaPreUpdate, BobaUpdate},
+        BobaResources, BobaStage, PearlRegistry, StageClock,
+    };
+
+    use super::FrameTimer;
+
+    #[test]
+    fn shared_frame_delta() {
+        let mut registry = PearlRegistry::default();
+        let mut resources = BobaResources::default();
+        resources.add(StageClock::recording());
+
+        let mut pre_update = BobaPreUpdate::default();
+        let mut update = BobaUpdate::default();
+        let mut late_update = BobaLateUpdate::default();
+        for _ in 0..2 {
+            BobaFirst.run(&mut registry, &mut resources).unwrap();
+            pre_update.run(&mut registry, &mut resources).unwrap();
+            sleep(Duration::from_millis(2));
+            update.run(&mut registry, &mut resources).unwrap();
+            sleep(Duration::from_millis(2));
+            late_update.run(&mut registry, &mut resources).unwrap();
+        }
+
+        // every stage in the frame sees the time since the previous frame started
+        let frame = resources.get::<FrameTimer>().unwrap().delta();
+        assert!(frame >= 0.004);
+        let deltas = resources.get_mut::<StageClock>().unwrap().take_recorded();
+        assert!(deltas.len() == 6);
+        assert!(deltas[3..].iter().all(|delta| delta.delta == frame));
+    }
+}
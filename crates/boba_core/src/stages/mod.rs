@@ -1,3 +1,20 @@
+//! The built in stages, in the order they run each frame:
+//!
+//! [`BobaFirst`], [`BobaPreUpdate`], [`BobaUpdate`], [`BobaFixedUpdate`], [`BobaLateUpdate`],
+//! [`BobaPreRender`], [`BobaPostRender`], [`BobaLast`].
+//!
+//! The frame is drawn between [`BobaPreRender`] and [`BobaPostRender`].
+//! [`BobaFirst`] measures the frame time in a [`FrameTimer`], which every timed stage of the frame shares.
+
+mod fixed_update;
+mod frame;
+mod timer;
 mod update;
 
+pub use fixed_update::*;
+pub use frame::*;
 pub use update::*;
+
+pub use timer::FrameTimer;
+
+pub(crate) use timer::StageTimer;
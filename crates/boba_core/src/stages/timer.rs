@@ -0,0 +1,59 @@
+use std::time::Instant;
+
+use crate::{BobaResources, StageClock, StageClockError};
+
+/// The time between the starts of the last two frames.
+///
+/// Measured once per frame by [`BobaFirst`](super::BobaFirst), which adds it to the resources on its first run.
+/// Every timed stage uses this delta when it is present, so that all stages in a frame see the same time step.
+#[derive(Default)]
+pub struct FrameTimer {
+    instant: Option<Instant>,
+    delta: f32,
+}
+
+impl FrameTimer {
+    /// The time since the start of the previous frame, or `0` in the first frame
+    pub fn delta(&self) -> f32 {
+        self.delta
+    }
+
+    /// Starts a new frame
+    pub(crate) fn tick(&mut self) {
+        self.delta = match self.instant {
+            Some(instant) => instant.elapsed().as_secs_f32(),
+            None => 0.,
+        };
+        self.instant = Some(Instant::now());
+    }
+}
+
+/// Measures the time between runs of a stage.
+#[derive(Default)]
+pub(crate) struct StageTimer {
+    instant: Option<Instant>,
+}
+
+impl StageTimer {
+    /// Gets the frame delta from the [`FrameTimer`] in `resources`, or the time since the last call if there is none.
+    /// The delta is resolved through the [`StageClock`] in `resources`.
+    ///
+    /// Without a frame timer, returns `0` on the first call.
+    pub fn delta<Stage: 'static>(
+        &mut self,
+        resources: &BobaResources,
+    ) -> Result<f32, StageClockError> {
+        let own = match self.instant {
+            Some(instant) => instant.elapsed().as_secs_f32(),
+            None => 0f32,
+        };
+
+        self.instant = Some(Instant::now());
+        let delta = match resources.get::<FrameTimer>() {
+            Ok(frame) => frame.delta(),
+            Err(_) => own,
+        };
+
+        StageClock::resolve::<Stage>(resources, delta)
+    }
+}
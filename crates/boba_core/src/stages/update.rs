@@ -1,23 +1,20 @@
-use std::time::Instant;
-
 use crate::{BobaResources, BobaResult, BobaStage, PearlRegistry};
 
+use super::StageTimer;
+
+/// Runs pearls once per frame with the frame delta time.
+///
+/// Runs after [`BobaPreUpdate`](super::BobaPreUpdate) and before [`BobaFixedUpdate`](super::BobaFixedUpdate).
 #[derive(Default)]
 pub struct BobaUpdate {
-    instant: Option<Instant>,
+    timer: StageTimer,
 }
 
 impl BobaStage for BobaUpdate {
     type Data = f32;
 
     fn run(&mut self, registry: &mut PearlRegistry, resources: &mut BobaResources) -> BobaResult {
-        let delta = match self.instant {
-            Some(instant) => instant.elapsed().as_secs_f32(),
-            None => 0f32,
-        };
-
-        self.instant = Some(Instant::now());
-
+        let delta = self.timer.delta::<BobaUpdate>(resources)?;
         registry.run_stage::<BobaUpdate>(&delta, resources);
 
         Ok(())
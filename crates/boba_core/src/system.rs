@@ -0,0 +1,251 @@
+use std::{any::type_name, fmt::Display, marker::PhantomData};
+
+use thiserror::Error;
+
+use crate::{BobaResources, BobaResult, BobaStage, PearlRegistry, ResourceError};
+
+/// An error returned when a system cannot fetch its resources.
+#[derive(Debug, Error)]
+pub enum SystemError {
+    #[error("System '{system}' requires resource '{resource}', but it does not exist.")]
+    MissingResource {
+        system: &'static str,
+        resource: String,
+    },
+    #[error("System '{system}' could not borrow resource '{resource}'. Error: {error}")]
+    Borrowed {
+        system: &'static str,
+        resource: String,
+        error: String,
+    },
+}
+
+impl SystemError {
+    fn from_resource<E: Display>(system: &'static str, error: ResourceError<E>) -> Self {
+        match error {
+            ResourceError::NotFound(resource) => Self::MissingResource { system, resource },
+            ResourceError::BorrowError(resource, error) => Self::Borrowed {
+                system,
+                resource,
+                error: error.to_string(),
+            },
+        }
+    }
+}
+
+/// A function that can be run as a system.
+///
+/// Implemented for functions and closures that return a [`BobaResult`] and take up to three
+/// resource parameters as `&T` or `&mut T`. Resources are fetched from [`BobaResources`] every run.
+pub trait SystemFunction<Marker>: 'static {
+    fn run_system(
+        &mut self,
+        registry: &mut PearlRegistry,
+        resources: &mut BobaResources,
+    ) -> BobaResult;
+}
+
+/// A [`BobaStage`] that runs a function every time it is run.
+///
+/// Created using [`system`] or [`stage_fn`].
+pub struct SystemStage<F, Marker> {
+    function: F,
+    _marker: PhantomData<fn() -> Marker>,
+}
+
+/// Creates a stage from a function with typed resource parameters.
+///
+/// ```ignore
+/// app.main_stages.append(system(|time: &GameTime, score: &mut Score| {
+///     score.value += time.delta;
+///     Ok(())
+/// }));
+/// ```
+///
+/// If a resource is missing, the stage fails with a [`SystemError`] naming the system and the resource.
+pub fn system<Marker, F>(function: F) -> SystemStage<F, Marker>
+where
+    F: SystemFunction<Marker>,
+{
+    SystemStage {
+        function,
+        _marker: PhantomData,
+    }
+}
+
+impl<Marker, F> BobaStage for SystemStage<F, Marker>
+where
+    Marker: 'static,
+    F: SystemFunction<Marker>,
+{
+    type Data = ();
+
+    fn run(&mut self, registry: &mut PearlRegistry, resources: &mut BobaResources) -> BobaResult {
+        self.function.run_system(registry, resources)
+    }
+}
+
+/// Marker for functions that take the registry and resources directly.
+pub struct RawStage;
+
+/// Creates a stage from a function that takes the [`PearlRegistry`] and [`BobaResources`] directly,
+/// the same way [`BobaStage::run`] does.
+pub fn stage_fn<F>(function: F) -> SystemStage<F, RawStage>
+where
+    F: FnMut(&mut PearlRegistry, &mut BobaResources) -> BobaResult + 'static,
+{
+    SystemStage {
+        function,
+        _marker: PhantomData,
+    }
+}
+
+impl<F> SystemFunction<RawStage> for F
+where
+    F: FnMut(&mut PearlRegistry, &mut BobaResources) -> BobaResult + 'static,
+{
+    fn run_system(
+        &mut self,
+        registry: &mut PearlRegistry,
+        resources: &mut BobaResources,
+    ) -> BobaResult {
+        (self)(registry, resources)
+    }
+}
+
+macro_rules! param_type {
+    (Read, $type:ident) => {
+        &$type
+    };
+    (Write, $type:ident) => {
+        &mut $type
+    };
+}
+
+macro_rules! fetch_param {
+    (Read, $type:ident, $resources:ident) => {
+        $resources.get::<$type>()
+    };
+    (Write, $type:ident, $resources:ident) => {
+        $resources.get_mut::<$type>()
+    };
+}
+
+macro_rules! borrow_param {
+    (Read, $guard:ident) => {
+        &*$guard
+    };
+    (Write, $guard:ident) => {
+        &mut *$guard
+    };
+}
+
+macro_rules! impl_system_function {
+    ($($type:ident $var:ident: $kind:ident),*) => {
+        impl<F, $($type),*> SystemFunction<fn($(param_type!($kind, $type)),*)> for F
+        where
+            F: FnMut($(param_type!($kind, $type)),*) -> BobaResult + 'static,
+            $($type: 'static,)*
+        {
+            #[allow(unused_variables, unused_mut)]
+            fn run_system(&mut self, _: &mut PearlRegistry, resources: &mut BobaResources) -> BobaResult {
+                $(
+                    let mut $var = fetch_param!($kind, $type, resources)
+                        .map_err(|e| SystemError::from_resource(type_name::<F>(), e))?;
+                )*
+                (self)($(borrow_param!($kind, $var)),*)
+            }
+        }
+    };
+}
+
+impl_system_function!();
+impl_system_function!(A a: Read);
+impl_system_function!(A a: Write);
+impl_system_function!(A a: Read, B b: Read);
+impl_system_function!(A a: Read, B b: Write);
+impl_system_function!(A a: Write, B b: Read);
+impl_system_function!(A a: Write, B b: Write);
+impl_system_function!(A a: Read, B b: Read, C c: Read);
+impl_system_function!(A a: Read, B b: Read, C c: Write);
+impl_system_function!(A a: Read, B b: Write, C c: Read);
+impl_system_function!(A a: Read, B b: Write, C c: Write);
+impl_system_function!(A a: Write, B b: Read, C c: Read);
+impl_system_function!(A a: Write, B b: Read, C c: Write);
+impl_system_function!(A a: Write, B b: Write, C c: Read);
+impl_system_function!(A a: Write, B b: Write, C c: Write);
+
+#[cfg(test)]
+mod tests {
+    use crate::{BobaResources, BobaResult, PearlRegistry, StageCollection};
+
+    use super::{stage_fn, system};
+
+    struct Delta(f32);
+    struct Total(f32);
+    struct Runs(u32);
+
+    fn accumulate(delta: &Delta, total: &mut Total) -> BobaResult {
+        total.0 += delta.0;
+        Ok(())
+    }
+
+    #[test]
+    fn closure_resources() {
+        let mut registry = PearlRegistry::default();
+        let mut resources = BobaResources::default();
+        resources.add(Delta(0.5));
+        resources.add(Total(0.));
+        resources.add(Runs(0));
+
+        let mut stages = StageCollection::default();
+        stages.append(system(accumulate));
+        stages.append(system(|runs: &mut Runs| {
+            runs.0 += 1;
+            Ok(())
+        }));
+        stages.append(stage_fn(
+            |_: &mut PearlRegistry, resources: &mut BobaResources| {
+                resources.get_mut::<Runs>()?.0 += 1;
+                Ok(())
+            },
+        ));
+
+        stages.run(&mut registry, &mut resources);
+        stages.run(&mut registry, &mut resources);
+
+        assert!(resources.get::<Total>().unwrap().0 == 1.);
+        assert!(resources.get::<Runs>().unwrap().0 == 4);
+    }
+
+    #[test]
+    fn missing_resource() {
+        use crate::BobaStage;
+
+        let mut registry = PearlRegistry::default();
+        let mut resources = BobaResources::default();
+        resources.add(Delta(0.5));
+
+        let mut stage = system(accumulate);
+        let error = stage.run(&mut registry, &mut resources).unwrap_err();
+        let message = error.to_string();
+
+        assert!(message.contains("accumulate"));
+        assert!(message.contains("Total"));
+        assert!(message.contains("does not exist"));
+    }
+
+    #[test]
+    fn duplicate_borrow() {
+        use crate::BobaStage;
+
+        let mut registry = PearlRegistry::default();
+        let mut resources = BobaResources::default();
+        resources.add(Total(0.));
+
+        let mut stage = system(|_: &Total, _: &mut Total| Ok(()));
+        let error = stage.run(&mut registry, &mut resources).unwrap_err();
+
+        assert!(error.to_string().contains("could not borrow"));
+    }
+}
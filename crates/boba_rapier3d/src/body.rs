@@ -0,0 +1,349 @@
+use boba_3d::{
+    glam::{Quat, Vec3},
+    pearls::BobaTransform,
+};
+use boba_core::{Pearl, PearlError};
+use rapier3d::prelude::{
+    Collider, ColliderHandle, Isometry, RigidBody, RigidBodyHandle, RigidBodyType,
+};
+use thiserror::Error;
+
+use crate::RapierPhysics;
+
+#[derive(Debug, Error)]
+pub enum RapierBodyError {
+    #[error("The rigid body does not exist in the physics world")]
+    MissingBody,
+    #[error("The collider is not attached to the rigid body")]
+    ForeignCollider,
+    #[error("Could not read the body transform. Error: {0}")]
+    TransformError(#[from] PearlError),
+}
+
+/// A rigid body created by [`RapierPhysics`] and the transform it moves.
+///
+/// Every method takes the physics world the body was created in.
+///
+/// Dynamic, fixed and velocity based kinematic bodies move their transform after every step, so changes to the transform are overwritten.
+/// Use [`teleport`](Self::teleport) or [`teleport_to_transform`](Self::teleport_to_transform) to move them instead.
+/// Position based kinematic bodies are moved by their transform, and follow it smoothly on the next step.
+///
+/// The body is despawned with [`despawn`](Self::despawn), or automatically on the next step after its transform is destroyed.
+#[derive(Clone)]
+pub struct RapierBody {
+    pub handle: RigidBodyHandle,
+    pub transform: Pearl<BobaTransform>,
+}
+
+impl RapierBody {
+    /// Returns true if the body still exists in `physics`
+    pub fn exists(&self, physics: &RapierPhysics) -> bool {
+        physics.rigid_body(self.handle).is_some()
+    }
+
+    /// Adds a force that is applied every step until it is reset
+    pub fn add_force(
+        &self,
+        physics: &mut RapierPhysics,
+        force: Vec3,
+    ) -> Result<(), RapierBodyError> {
+        self.body_mut(physics)?.add_force(force.into(), true);
+        Ok(())
+    }
+
+    /// Adds a torque that is applied every step until it is reset
+    pub fn add_torque(
+        &self,
+        physics: &mut RapierPhysics,
+        torque: Vec3,
+    ) -> Result<(), RapierBodyError> {
+        self.body_mut(physics)?.add_torque(torque.into(), true);
+        Ok(())
+    }
+
+    /// Removes every force and torque added to the body
+    pub fn reset_forces(&self, physics: &mut RapierPhysics) -> Result<(), RapierBodyError> {
+        let body = self.body_mut(physics)?;
+        body.reset_forces(true);
+        body.reset_torques(true);
+        Ok(())
+    }
+
+    /// Changes the velocity of the body right away with an `impulse`
+    pub fn apply_impulse(
+        &self,
+        physics: &mut RapierPhysics,
+        impulse: Vec3,
+    ) -> Result<(), RapierBodyError> {
+        self.body_mut(physics)?.apply_impulse(impulse.into(), true);
+        Ok(())
+    }
+
+    /// Changes the angular velocity of the body right away with an `impulse`
+    pub fn apply_torque_impulse(
+        &self,
+        physics: &mut RapierPhysics,
+        impulse: Vec3,
+    ) -> Result<(), RapierBodyError> {
+        self.body_mut(physics)?
+            .apply_torque_impulse(impulse.into(), true);
+        Ok(())
+    }
+
+    pub fn linear_velocity(&self, physics: &RapierPhysics) -> Result<Vec3, RapierBodyError> {
+        Ok((*self.body(physics)?.linvel()).into())
+    }
+
+    pub fn set_linear_velocity(
+        &self,
+        physics: &mut RapierPhysics,
+        velocity: Vec3,
+    ) -> Result<(), RapierBodyError> {
+        self.body_mut(physics)?.set_linvel(velocity.into(), true);
+        Ok(())
+    }
+
+    pub fn angular_velocity(&self, physics: &RapierPhysics) -> Result<Vec3, RapierBodyError> {
+        Ok((*self.body(physics)?.angvel()).into())
+    }
+
+    pub fn set_angular_velocity(
+        &self,
+        physics: &mut RapierPhysics,
+        velocity: Vec3,
+    ) -> Result<(), RapierBodyError> {
+        self.body_mut(physics)?.set_angvel(velocity.into(), true);
+        Ok(())
+    }
+
+    pub fn body_type(&self, physics: &RapierPhysics) -> Result<RigidBodyType, RapierBodyError> {
+        Ok(self.body(physics)?.body_type())
+    }
+
+    pub fn set_body_type(
+        &self,
+        physics: &mut RapierPhysics,
+        body_type: RigidBodyType,
+    ) -> Result<(), RapierBodyError> {
+        self.body_mut(physics)?.set_body_type(body_type, true);
+        Ok(())
+    }
+
+    /// Moves the body to `position` and `rotation` right away, without moving through anything in between.
+    ///
+    /// The transform is moved with it, and the body keeps its velocity.
+    pub fn teleport(
+        &self,
+        physics: &mut RapierPhysics,
+        position: Vec3,
+        rotation: Quat,
+    ) -> Result<(), RapierBodyError> {
+        let position = Isometry::from_parts(position.into(), rotation.into());
+        physics.teleport_body(self.handle, position)
+    }
+
+    /// Teleports the body to the current world position and rotation of its transform.
+    ///
+    /// This is the way to keep changes made directly to the transform of a dynamic body.
+    pub fn teleport_to_transform(
+        &self,
+        physics: &mut RapierPhysics,
+    ) -> Result<(), RapierBodyError> {
+        let (position, rotation) = {
+            let transform = self.transform.borrow()?;
+            (transform.world_position(), transform.world_rotation())
+        };
+        self.teleport(physics, position, rotation)
+    }
+
+    /// Gets the handles of every collider attached to the body
+    pub fn colliders(
+        &self,
+        physics: &RapierPhysics,
+    ) -> Result<Vec<ColliderHandle>, RapierBodyError> {
+        Ok(self.body(physics)?.colliders().to_vec())
+    }
+
+    /// Attaches a new `collider` to the body
+    pub fn add_collider(
+        &self,
+        physics: &mut RapierPhysics,
+        collider: Collider,
+    ) -> Result<ColliderHandle, RapierBodyError> {
+        physics.attach_collider(self.handle, collider)
+    }
+
+    /// Removes the `collider` from the body and returns it
+    pub fn remove_collider(
+        &self,
+        physics: &mut RapierPhysics,
+        collider: ColliderHandle,
+    ) -> Result<Collider, RapierBodyError> {
+        physics.detach_collider(self.handle, collider)
+    }
+
+    /// Removes the body and all of its colliders from `physics`.
+    ///
+    /// The transform is left where it is, and is no longer moved by physics.
+    pub fn despawn(&self, physics: &mut RapierPhysics) -> Result<(), RapierBodyError> {
+        match physics.remove_body(self.handle) {
+            true => Ok(()),
+            false => Err(RapierBodyError::MissingBody),
+        }
+    }
+
+    fn body<'a>(&self, physics: &'a RapierPhysics) -> Result<&'a RigidBody, RapierBodyError> {
+        physics
+            .rigid_body(self.handle)
+            .ok_or(RapierBodyError::MissingBody)
+    }
+
+    fn body_mut<'a>(
+        &self,
+        physics: &'a mut RapierPhysics,
+    ) -> Result<&'a mut RigidBody, RapierBodyError> {
+        physics
+            .rigid_body_mut(self.handle)
+            .ok_or(RapierBodyError::MissingBody)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boba_3d::pearls::SetTransformParent;
+    use rapier3d::prelude::{ColliderBuilder, RigidBodyBuilder};
+
+    use super::*;
+
+    fn ball(physics: &mut RapierPhysics) -> RapierBody {
+        physics.create_transform(
+            RigidBodyBuilder::dynamic().build(),
+            ColliderBuilder::ball(0.5).build(),
+        )
+    }
+
+    #[test]
+    fn forces_and_velocity() {
+        let mut physics = RapierPhysics::new();
+        physics.gravity = Vec3::ZERO;
+        let body = ball(&mut physics);
+
+        let mass = physics.rigid_body(body.handle).unwrap().mass();
+        body.apply_impulse(&mut physics, Vec3::X * mass).unwrap();
+        let velocity = body.linear_velocity(&physics).unwrap();
+        assert!(velocity.abs_diff_eq(Vec3::X, 0.0001), "{velocity}");
+
+        body.set_linear_velocity(&mut physics, Vec3::ZERO).unwrap();
+        body.add_force(&mut physics, Vec3::Y * mass).unwrap();
+        for _ in 0..60 {
+            physics.step();
+        }
+        let velocity = body.linear_velocity(&physics).unwrap();
+        assert!(velocity.abs_diff_eq(Vec3::Y, 0.01), "{velocity}");
+        assert!(body.transform.borrow().unwrap().world_position().y > 0.4);
+
+        body.reset_forces(&mut physics).unwrap();
+        body.set_body_type(&mut physics, RigidBodyType::Fixed)
+            .unwrap();
+        assert_eq!(body.body_type(&physics).unwrap(), RigidBodyType::Fixed);
+        assert_eq!(body.linear_velocity(&physics).unwrap(), Vec3::ZERO);
+    }
+
+    #[test]
+    fn colliders_and_removal() {
+        let mut physics = RapierPhysics::new();
+        let body = ball(&mut physics);
+        let other = ball(&mut physics);
+
+        let collider = body
+            .add_collider(&mut physics, ColliderBuilder::cuboid(1., 1., 1.).build())
+            .unwrap();
+        assert_eq!(body.colliders(&physics).unwrap().len(), 2);
+        assert!(matches!(
+            other.remove_collider(&mut physics, collider),
+            Err(RapierBodyError::ForeignCollider)
+        ));
+        body.remove_collider(&mut physics, collider).unwrap();
+        assert_eq!(body.colliders(&physics).unwrap().len(), 1);
+
+        body.despawn(&mut physics).unwrap();
+        assert!(!body.exists(&physics));
+        assert!(matches!(
+            body.despawn(&mut physics),
+            Err(RapierBodyError::MissingBody)
+        ));
+
+        // bodies are despawned when their transform is destroyed
+        other.transform.destroy().unwrap();
+        assert!(other.exists(&physics));
+        physics.step();
+        assert!(!other.exists(&physics));
+    }
+
+    #[test]
+    fn kinematic_bodies_follow_transforms() {
+        let mut physics = RapierPhysics::new();
+        let platform = physics.create_transform(
+            RigidBodyBuilder::kinematic_position_based().build(),
+            ColliderBuilder::cuboid(1., 0.1, 1.).build(),
+        );
+        let parent = Pearl::wrap(BobaTransform::from_position(Vec3::Y * 5.));
+        let mut transform = platform.transform.clone();
+        transform.set_parent(parent.clone()).unwrap();
+        transform.borrow_mut().unwrap().set_local_position(Vec3::X);
+
+        physics.step();
+        let body = physics.rigid_body(platform.handle).unwrap();
+        let position: Vec3 = (*body.translation()).into();
+        assert!(
+            position.abs_diff_eq(Vec3::new(1., 5., 0.), 0.0001),
+            "{position}"
+        );
+        let world = transform.borrow().unwrap().world_position();
+        assert!(world.abs_diff_eq(Vec3::new(1., 5., 0.), 0.0001), "{world}");
+
+        // the body moves smoothly, so it has a velocity towards the next target
+        transform
+            .borrow_mut()
+            .unwrap()
+            .set_local_position(Vec3::X * 2.);
+        physics.step();
+        assert!(platform.linear_velocity(&physics).unwrap().x > 0.);
+    }
+
+    #[test]
+    fn teleporting() {
+        let mut physics = RapierPhysics::new();
+        physics.gravity = Vec3::ZERO;
+        let body = ball(&mut physics);
+        body.set_linear_velocity(&mut physics, Vec3::X).unwrap();
+
+        // editing the transform of a dynamic body is overwritten by the next step
+        let moved = Vec3::Y * 10.;
+        body.transform
+            .borrow_mut()
+            .unwrap()
+            .set_local_position(moved);
+        physics.step();
+        assert!(body.transform.borrow().unwrap().world_position().y < 1.);
+
+        body.transform
+            .borrow_mut()
+            .unwrap()
+            .set_local_position(moved);
+        body.teleport_to_transform(&mut physics).unwrap();
+        physics.step();
+        let position = body.transform.borrow().unwrap().world_position();
+        assert!((position.y - 10.).abs() < 0.0001, "{position}");
+        assert!(body
+            .linear_velocity(&physics)
+            .unwrap()
+            .abs_diff_eq(Vec3::X, 0.0001));
+
+        let rotation = Quat::from_rotation_y(1.);
+        body.teleport(&mut physics, Vec3::NEG_Y, rotation).unwrap();
+        let transform = body.transform.borrow().unwrap();
+        assert!(transform.world_position().abs_diff_eq(Vec3::NEG_Y, 0.0001));
+        assert!(transform.world_rotation().abs_diff_eq(rotation, 0.0001));
+    }
+}
@@ -0,0 +1,99 @@
+use boba_3d::{glam::Vec3, pearls::BobaTransform};
+use boba_core::Pearl;
+use rapier3d::prelude::{ColliderHandle, RigidBodyHandle};
+
+/// A collider and the body and transform it belongs to.
+///
+/// This is one side of a [`RapierCollision`] or [`RapierContactForce`], or the collider found by a scene query.
+#[derive(Clone)]
+pub struct CollisionBody {
+    pub collider: ColliderHandle,
+    /// The rigid body the collider is attached to, or `None` if it has no body or was removed
+    pub body: Option<RigidBodyHandle>,
+    /// The transform connected to the body, or `None` if the body was not created by [`RapierPhysics`](crate::RapierPhysics)
+    pub transform: Option<Pearl<BobaTransform>>,
+}
+
+impl CollisionBody {
+    /// Returns true if this collider belongs to the body connected to `transform`
+    pub fn is(&self, transform: &Pearl<BobaTransform>) -> bool {
+        self.transform.as_ref() == Some(transform)
+    }
+}
+
+/// Whether two colliders started or stopped touching
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionState {
+    Started,
+    Stopped,
+}
+
+/// Two colliders that started or stopped touching during a physics step.
+///
+/// Only colliders built with [`ActiveEvents::COLLISION_EVENTS`](rapier3d::prelude::ActiveEvents::COLLISION_EVENTS)
+/// create these events, and only one of the two colliders needs the flag.
+#[derive(Clone)]
+pub struct RapierCollision {
+    pub state: CollisionState,
+    pub first: CollisionBody,
+    pub second: CollisionBody,
+    /// True if at least one of the colliders is a sensor
+    pub sensor: bool,
+    /// True if the collision stopped because one of the colliders was removed
+    pub removed: bool,
+}
+
+impl RapierCollision {
+    pub fn started(&self) -> bool {
+        self.state == CollisionState::Started
+    }
+
+    pub fn stopped(&self) -> bool {
+        self.state == CollisionState::Stopped
+    }
+
+    /// Gets the body that `transform` collided with, or `None` if `transform` is not part of this collision
+    pub fn other(&self, transform: &Pearl<BobaTransform>) -> Option<&CollisionBody> {
+        other(&self.first, &self.second, transform)
+    }
+}
+
+/// The contact forces between two colliders during a physics step.
+///
+/// Only colliders built with [`ActiveEvents::CONTACT_FORCE_EVENTS`](rapier3d::prelude::ActiveEvents::CONTACT_FORCE_EVENTS)
+/// create these events, and only when the total force magnitude is above the
+/// [`contact_force_event_threshold`](rapier3d::prelude::Collider::contact_force_event_threshold) of the collider.
+#[derive(Clone)]
+pub struct RapierContactForce {
+    pub first: CollisionBody,
+    pub second: CollisionBody,
+    /// The sum of all the forces between the two colliders
+    pub total_force: Vec3,
+    /// The sum of the magnitudes of every contact force between the two colliders
+    pub total_force_magnitude: f32,
+    /// The direction of the largest contact force
+    pub max_force_direction: Vec3,
+    /// The magnitude of the largest contact force
+    pub max_force_magnitude: f32,
+}
+
+impl RapierContactForce {
+    /// Gets the body that `transform` is pushing against, or `None` if `transform` is not part of this contact
+    pub fn other(&self, transform: &Pearl<BobaTransform>) -> Option<&CollisionBody> {
+        other(&self.first, &self.second, transform)
+    }
+}
+
+fn other<'a>(
+    first: &'a CollisionBody,
+    second: &'a CollisionBody,
+    transform: &Pearl<BobaTransform>,
+) -> Option<&'a CollisionBody> {
+    if first.is(transform) {
+        Some(second)
+    } else if second.is(transform) {
+        Some(first)
+    } else {
+        None
+    }
+}
@@ -1,6 +1,12 @@
+mod body;
+mod events;
 mod physics;
+mod queries;
 
+pub use body::*;
+pub use events::*;
 pub use physics::*;
+pub use queries::*;
 
 pub mod stages;
 
@@ -1,10 +1,24 @@
-use boba_3d::{glam::Vec3, pearls::BobaTransform};
+use boba_3d::{
+    glam::{Quat, Vec3},
+    pearls::BobaTransform,
+};
 use boba_core::Pearl;
+use indexmap::IndexMap;
 use log::error;
-use rapier3d::prelude::{
-    BroadPhase, CCDSolver, Collider, ColliderSet, ImpulseJointSet, IntegrationParameters,
-    IslandManager, MultibodyJointSet, NarrowPhase, PhysicsPipeline, RigidBody, RigidBodyHandle,
-    RigidBodySet,
+use rapier3d::{
+    crossbeam::channel::{self, Receiver},
+    parry::query::TOIStatus,
+    prelude::{
+        BroadPhase, CCDSolver, ChannelEventCollector, Collider, ColliderHandle, ColliderSet,
+        CollisionEvent, ContactForceEvent, ImpulseJointSet, IntegrationParameters, IslandManager,
+        Isometry, MultibodyJointSet, NarrowPhase, PhysicsPipeline, QueryFilter, QueryPipeline, Ray,
+        Real, RigidBody, RigidBodyHandle, RigidBodySet, RigidBodyType, Shape, Vector,
+    },
+};
+
+use crate::{
+    CollisionBody, CollisionState, PointHit, RapierBody, RapierBodyError, RapierCollision,
+    RapierContactForce, RayHit, ShapeHit,
 };
 
 struct RigidBodyConnection {
@@ -13,7 +27,29 @@ struct RigidBodyConnection {
 }
 
 impl RigidBodyConnection {
-    fn sync(&mut self, rigid_body_set: &RigidBodySet) {
+    /// Returns true if the transform moves the body, instead of the body moving the transform
+    fn drives_body(&self, rigid_body_set: &RigidBodySet) -> bool {
+        rigid_body_set[self.handle].body_type() == RigidBodyType::KinematicPositionBased
+    }
+
+    /// Sets the next kinematic position of the body to the world position and rotation of the transform
+    fn pull(&self, rigid_body_set: &mut RigidBodySet) {
+        let transform = match self.transform.borrow() {
+            Ok(t) => t,
+            Err(e) => {
+                error!("Error reading kinematic physics transform. Error: {e}");
+                return;
+            }
+        };
+
+        let position = Isometry::from_parts(
+            transform.world_position().into(),
+            transform.world_rotation().into(),
+        );
+        rigid_body_set[self.handle].set_next_kinematic_position(position);
+    }
+
+    fn sync(&self, rigid_body_set: &RigidBodySet) {
         let mut transform = match self.transform.borrow_mut() {
             Ok(t) => t,
             Err(e) => {
@@ -23,15 +59,28 @@ impl RigidBodyConnection {
         };
 
         let sync_data = &rigid_body_set[self.handle];
-        transform.set_local_position(sync_data.position().translation.into());
-        transform.set_local_rotation(sync_data.position().rotation.into());
+        transform.set_world_position(sync_data.position().translation.into());
+        transform.set_world_rotation(sync_data.position().rotation.into());
     }
 }
 
+/// A rapier physics world that moves the [`BobaTransform`] of every body it creates.
+///
+/// Bodies are created with [`create_transform`](Self::create_transform), which returns a [`RapierBody`] to control them.
+///
+/// Collision and contact force events from the latest [`step`](Self::step) can be read with
+/// [`collisions`](Self::collisions) and [`contact_forces`](Self::contact_forces),
+/// and are sent to pearls by the [`OnCollision`](crate::stages::OnCollision) and
+/// [`OnContactForce`](crate::stages::OnContactForce) stages.
+///
+/// Scene queries like [`cast_ray`](Self::cast_ray) see the colliders as they were after the latest step.
+/// Colliders created or moved since then are only found after the next step, or after calling [`update_queries`](Self::update_queries).
 pub struct RapierPhysics {
     pub gravity: Vec3,
 
-    connections: Vec<RigidBodyConnection>,
+    connections: IndexMap<RigidBodyHandle, RigidBodyConnection>,
+    collisions: Vec<RapierCollision>,
+    contact_forces: Vec<RapierContactForce>,
 
     integration_parameters: IntegrationParameters,
     physics_pipeline: PhysicsPipeline,
@@ -43,15 +92,22 @@ pub struct RapierPhysics {
     impulse_joint_set: ImpulseJointSet,
     multibody_joint_set: MultibodyJointSet,
     ccd_solver: CCDSolver,
+    query_pipeline: QueryPipeline,
     physics_hooks: (),
-    event_handler: (),
+    event_handler: ChannelEventCollector,
+    collision_receiver: Receiver<CollisionEvent>,
+    contact_force_receiver: Receiver<ContactForceEvent>,
 }
 
 impl Default for RapierPhysics {
     fn default() -> Self {
+        let (collision_sender, collision_receiver) = channel::unbounded();
+        let (contact_force_sender, contact_force_receiver) = channel::unbounded();
         Self {
             gravity: Vec3::new(0., -9.81, 0.),
-            connections: Vec::new(),
+            connections: IndexMap::new(),
+            collisions: Vec::new(),
+            contact_forces: Vec::new(),
             integration_parameters: Default::default(),
             physics_pipeline: Default::default(),
             island_manager: Default::default(),
@@ -62,8 +118,11 @@ impl Default for RapierPhysics {
             impulse_joint_set: Default::default(),
             multibody_joint_set: Default::default(),
             ccd_solver: Default::default(),
+            query_pipeline: Default::default(),
             physics_hooks: Default::default(),
-            event_handler: Default::default(),
+            event_handler: ChannelEventCollector::new(collision_sender, contact_force_sender),
+            collision_receiver,
+            contact_force_receiver,
         }
     }
 }
@@ -73,7 +132,19 @@ impl RapierPhysics {
         Default::default()
     }
 
+    /// Steps the simulation forward and moves the transforms of every body.
+    ///
+    /// Bodies whose transform was destroyed are despawned before the step.
+    /// Kinematic position based bodies are the other way around, and move to where their transform is during the step.
     pub fn step(&mut self) {
+        self.remove_destroyed();
+
+        for connection in self.connections.values() {
+            if connection.drives_body(&self.rigid_body_set) {
+                connection.pull(&mut self.rigid_body_set);
+            }
+        }
+
         self.physics_pipeline.step(
             &self.gravity.into(),
             &self.integration_parameters,
@@ -89,16 +160,325 @@ impl RapierPhysics {
             &self.event_handler,
         );
 
-        for connection in &mut self.connections {
-            connection.sync(&self.rigid_body_set);
+        for connection in self.connections.values() {
+            if !connection.drives_body(&self.rigid_body_set) {
+                connection.sync(&self.rigid_body_set);
+            }
         }
+
+        self.update_queries();
+
+        self.collisions.clear();
+        while let Ok(event) = self.collision_receiver.try_recv() {
+            let (state, first, second) = match event {
+                CollisionEvent::Started(first, second, _) => {
+                    (CollisionState::Started, first, second)
+                }
+                CollisionEvent::Stopped(first, second, _) => {
+                    (CollisionState::Stopped, first, second)
+                }
+            };
+            let collision = RapierCollision {
+                state,
+                first: self.collision_body(first),
+                second: self.collision_body(second),
+                sensor: event.sensor(),
+                removed: event.removed(),
+            };
+            self.collisions.push(collision);
+        }
+
+        self.contact_forces.clear();
+        while let Ok(event) = self.contact_force_receiver.try_recv() {
+            let contact_force = RapierContactForce {
+                first: self.collision_body(event.collider1),
+                second: self.collision_body(event.collider2),
+                total_force: event.total_force.into(),
+                total_force_magnitude: event.total_force_magnitude,
+                max_force_direction: event.max_force_direction.into(),
+                max_force_magnitude: event.max_force_magnitude,
+            };
+            self.contact_forces.push(contact_force);
+        }
+    }
+
+    /// Gets the collisions that started or stopped during the latest [`step`](Self::step)
+    pub fn collisions(&self) -> &[RapierCollision] {
+        &self.collisions
+    }
+
+    /// Gets the contact forces from the latest [`step`](Self::step)
+    pub fn contact_forces(&self) -> &[RapierContactForce] {
+        &self.contact_forces
+    }
+
+    fn collision_body(&self, collider: ColliderHandle) -> CollisionBody {
+        let body = self
+            .collider_set
+            .get(collider)
+            .and_then(|collider| collider.parent());
+        let transform = body
+            .and_then(|body| self.connections.get(&body))
+            .map(|connection| connection.transform.clone());
+
+        CollisionBody {
+            collider,
+            body,
+            transform,
+        }
+    }
+
+    /// Moves every body and collider by the opposite of `shift`, to follow a [`FloatingOrigin`](boba_3d::large_world::FloatingOrigin) that moved by `shift`.
+    ///
+    /// Connected transforms are synced right away, so they stay in place until the next step.
+    /// Transforms that drive a kinematic body are left alone, because they are shifted with the origin themselves.
+    /// This is usually called from [`FloatingOrigin::on_rebase`](boba_3d::large_world::FloatingOrigin::on_rebase).
+    pub fn shift_origin(&mut self, shift: Vec3) {
+        let offset: Vector<Real> = (-shift).into();
+        for (_, body) in self.rigid_body_set.iter_mut() {
+            // setting the translation also overwrites where a kinematic body is moving to, so that is shifted too
+            let next_translation = body.next_position().translation.vector + offset;
+            let translation = body.translation() + offset;
+            body.set_translation(translation, false);
+            body.set_next_kinematic_translation(next_translation);
+        }
+
+        // colliders only follow their body during a step, so they are moved here for the queries
+        for (_, collider) in self.collider_set.iter_mut() {
+            let position = match (collider.parent(), collider.position_wrt_parent()) {
+                (Some(parent), Some(relative)) => match self.rigid_body_set.get(parent) {
+                    Some(body) => body.position() * relative,
+                    None => continue,
+                },
+                _ => Isometry::from_parts(
+                    (collider.translation() + offset).into(),
+                    *collider.rotation(),
+                ),
+            };
+            collider.set_position(position);
+        }
+
+        // transforms that drive their body are already shifted with the origin
+        for connection in self.connections.values() {
+            if !connection.drives_body(&self.rigid_body_set) {
+                connection.sync(&self.rigid_body_set);
+            }
+        }
+
+        self.update_queries();
+    }
+
+    /// Updates the scene queries to see every collider where it is right now.
+    ///
+    /// This is already done after every [`step`](Self::step).
+    pub fn update_queries(&mut self) {
+        self.query_pipeline.update(
+            &self.island_manager,
+            &self.rigid_body_set,
+            &self.collider_set,
+        );
+    }
+
+    /// Casts a ray from `origin` along `direction`, and gets the first collider it hits within `max_distance`.
+    ///
+    /// If `solid` is true, a ray starting inside a collider hits it right away at the origin.
+    /// Otherwise it hits the collider where it leaves it.
+    pub fn cast_ray(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        max_distance: f32,
+        solid: bool,
+        filter: QueryFilter,
+    ) -> Option<RayHit> {
+        let direction = direction.try_normalize()?;
+        let ray = Ray::new(origin.into(), direction.into());
+        let (collider, intersection) = self.query_pipeline.cast_ray_and_get_normal(
+            &self.rigid_body_set,
+            &self.collider_set,
+            &ray,
+            max_distance,
+            solid,
+            filter,
+        )?;
+
+        Some(RayHit {
+            body: self.collision_body(collider),
+            distance: intersection.toi,
+            point: origin + direction * intersection.toi,
+            normal: intersection.normal.into(),
+        })
+    }
+
+    /// Moves `shape` from `position` with `velocity`, and gets the first collider it hits before `max_time`.
+    pub fn cast_shape(
+        &self,
+        position: Vec3,
+        rotation: Quat,
+        velocity: Vec3,
+        shape: &dyn Shape,
+        max_time: f32,
+        filter: QueryFilter,
+    ) -> Option<ShapeHit> {
+        let (collider, toi) = self.query_pipeline.cast_shape(
+            &self.rigid_body_set,
+            &self.collider_set,
+            &Isometry::from_parts(position.into(), rotation.into()),
+            &velocity.into(),
+            shape,
+            max_time,
+            filter,
+        )?;
+
+        let collider_position = self.collider_set[collider].position();
+        Some(ShapeHit {
+            body: self.collision_body(collider),
+            time: toi.toi,
+            point: (collider_position * toi.witness1).into(),
+            normal: (collider_position * toi.normal1).into_inner().into(),
+            penetrating: toi.status == TOIStatus::Penetrating,
+        })
+    }
+
+    /// Gets every collider that overlaps `shape` placed at `position` with `rotation`
+    pub fn intersections_with_shape(
+        &self,
+        position: Vec3,
+        rotation: Quat,
+        shape: &dyn Shape,
+        filter: QueryFilter,
+    ) -> Vec<CollisionBody> {
+        let mut bodies = Vec::new();
+        self.query_pipeline.intersections_with_shape(
+            &self.rigid_body_set,
+            &self.collider_set,
+            &Isometry::from_parts(position.into(), rotation.into()),
+            shape,
+            filter,
+            |collider| {
+                bodies.push(self.collision_body(collider));
+                true
+            },
+        );
+        bodies
+    }
+
+    /// Gets the closest point to `point` on any collider.
+    ///
+    /// If `solid` is true, a point inside a collider is its own closest point.
+    /// Otherwise it is projected onto the collider surface.
+    pub fn project_point(&self, point: Vec3, solid: bool, filter: QueryFilter) -> Option<PointHit> {
+        let (collider, projection) = self.query_pipeline.project_point(
+            &self.rigid_body_set,
+            &self.collider_set,
+            &point.into(),
+            solid,
+            filter,
+        )?;
+
+        Some(PointHit {
+            body: self.collision_body(collider),
+            point: projection.point.into(),
+            inside: projection.is_inside,
+        })
+    }
+
+    /// Gets the rigid body with `handle`, or `None` if it does not exist
+    pub fn rigid_body(&self, handle: RigidBodyHandle) -> Option<&RigidBody> {
+        self.rigid_body_set.get(handle)
+    }
+
+    /// Gets the rigid body with `handle` mutably, or `None` if it does not exist
+    pub fn rigid_body_mut(&mut self, handle: RigidBodyHandle) -> Option<&mut RigidBody> {
+        self.rigid_body_set.get_mut(handle)
     }
 
-    pub fn create_transform(
+    pub(crate) fn attach_collider(
         &mut self,
-        rigidbody: RigidBody,
+        body: RigidBodyHandle,
         collider: Collider,
-    ) -> Pearl<BobaTransform> {
+    ) -> Result<ColliderHandle, RapierBodyError> {
+        if !self.rigid_body_set.contains(body) {
+            return Err(RapierBodyError::MissingBody);
+        }
+
+        Ok(self
+            .collider_set
+            .insert_with_parent(collider, body, &mut self.rigid_body_set))
+    }
+
+    pub(crate) fn detach_collider(
+        &mut self,
+        body: RigidBodyHandle,
+        collider: ColliderHandle,
+    ) -> Result<Collider, RapierBodyError> {
+        if !self.rigid_body_set.contains(body) {
+            return Err(RapierBodyError::MissingBody);
+        }
+
+        match self.collider_set.get(collider) {
+            Some(attached) if attached.parent() == Some(body) => (),
+            _ => return Err(RapierBodyError::ForeignCollider),
+        }
+
+        self.collider_set
+            .remove(
+                collider,
+                &mut self.island_manager,
+                &mut self.rigid_body_set,
+                true,
+            )
+            .ok_or(RapierBodyError::ForeignCollider)
+    }
+
+    /// Moves the body with `handle` to `position` right away, and syncs its transform
+    pub(crate) fn teleport_body(
+        &mut self,
+        handle: RigidBodyHandle,
+        position: Isometry<Real>,
+    ) -> Result<(), RapierBodyError> {
+        let body = self
+            .rigid_body_set
+            .get_mut(handle)
+            .ok_or(RapierBodyError::MissingBody)?;
+        body.set_position(position, true);
+
+        if let Some(connection) = self.connections.get(&handle) {
+            connection.sync(&self.rigid_body_set);
+        }
+        Ok(())
+    }
+
+    /// Removes the body with `handle` and its colliders, returning false if it did not exist
+    pub(crate) fn remove_body(&mut self, handle: RigidBodyHandle) -> bool {
+        self.connections.swap_remove(&handle);
+        self.rigid_body_set
+            .remove(
+                handle,
+                &mut self.island_manager,
+                &mut self.collider_set,
+                &mut self.impulse_joint_set,
+                &mut self.multibody_joint_set,
+                true,
+            )
+            .is_some()
+    }
+
+    /// Removes every body whose transform pearl was destroyed
+    fn remove_destroyed(&mut self) {
+        let destroyed = self
+            .connections
+            .values()
+            .filter(|connection| matches!(connection.transform.is_destroyed(), Ok(true)))
+            .map(|connection| connection.handle)
+            .collect::<Vec<_>>();
+
+        for handle in destroyed {
+            self.remove_body(handle);
+        }
+    }
+
+    pub fn create_transform(&mut self, rigidbody: RigidBody, collider: Collider) -> RapierBody {
         let transform = Pearl::wrap(BobaTransform::from_position_rotation(
             rigidbody.position().translation.into(),
             rigidbody.position().rotation.into(),
@@ -113,16 +493,16 @@ impl RapierPhysics {
             transform: transform.clone(),
         };
 
-        self.connections.push(connection);
+        self.connections.insert(handle, connection);
 
-        transform
+        RapierBody { handle, transform }
     }
 
     pub fn create_transform_multi_collider(
         &mut self,
         rigidbody: RigidBody,
         colliders: Vec<Collider>,
-    ) -> Pearl<BobaTransform> {
+    ) -> RapierBody {
         let transform = Pearl::wrap(BobaTransform::from_position_rotation(
             rigidbody.position().translation.into(),
             rigidbody.position().rotation.into(),
@@ -142,8 +522,216 @@ impl RapierPhysics {
             transform: transform.clone(),
         };
 
-        self.connections.push(connection);
+        self.connections.insert(handle, connection);
+
+        RapierBody { handle, transform }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boba_3d::large_world::FloatingOrigin;
+    use boba_core::BobaResources;
+    use rapier3d::prelude::{vector, ActiveEvents, ColliderBuilder, RigidBodyBuilder, SharedShape};
+
+    use super::*;
+
+    #[test]
+    fn ball_hits_ground() {
+        let mut physics = RapierPhysics::new();
+        let ground = physics.create_transform(
+            RigidBodyBuilder::fixed().build(),
+            ColliderBuilder::cuboid(10., 0.1, 10.).build(),
+        );
+        let ball = physics.create_transform(
+            RigidBodyBuilder::dynamic()
+                .translation(vector![0., 2., 0.])
+                .build(),
+            ColliderBuilder::ball(0.5)
+                .active_events(ActiveEvents::COLLISION_EVENTS | ActiveEvents::CONTACT_FORCE_EVENTS)
+                .build(),
+        );
+
+        let mut steps = 0;
+        while physics.collisions().is_empty() {
+            assert!(steps < 200, "the ball never hit the ground");
+            assert!(physics.contact_forces().is_empty());
+            physics.step();
+            steps += 1;
+        }
+
+        let collision = &physics.collisions()[0];
+        assert!(collision.started());
+        assert!(!collision.sensor);
+        assert!(collision
+            .other(&ball.transform)
+            .unwrap()
+            .is(&ground.transform));
+        assert!(collision
+            .other(&ground.transform)
+            .unwrap()
+            .is(&ball.transform));
+        assert!(collision.first.body.is_some() && collision.second.body.is_some());
+
+        // events only last until the next step, and the resting ball keeps pushing on the ground
+        for _ in 0..50 {
+            physics.step();
+        }
+        assert!(physics.collisions().is_empty());
+        let contact_force = &physics.contact_forces()[0];
+        assert!(contact_force
+            .other(&ground.transform)
+            .unwrap()
+            .is(&ball.transform));
+        assert!(contact_force.total_force_magnitude > 0.);
+        let height = ball.transform.borrow().unwrap().world_position().y;
+        assert!((height - 0.6).abs() < 0.05, "{height}");
+    }
+
+    #[test]
+    fn shift_then_raycast() {
+        let mut physics = RapierPhysics::new();
+        let ball = physics.create_transform(
+            RigidBodyBuilder::fixed()
+                .translation(vector![100., 2., 0.])
+                .build(),
+            ColliderBuilder::ball(0.5).build(),
+        );
+        let mut ground = ColliderBuilder::cuboid(10., 0.1, 10.).build();
+        ground.set_translation(vector![100., 0., 0.]);
+        physics.collider_set.insert(ground);
+        physics.update_queries();
+
+        // queries see the shifted colliders right away, without a step
+        physics.shift_origin(Vec3::X * 100.);
+        let hit = physics
+            .cast_ray(Vec3::Y * 5., Vec3::NEG_Y, 10., true, QueryFilter::new())
+            .unwrap();
+        assert!(hit.body.is(&ball.transform));
+        assert!(hit.point.abs_diff_eq(Vec3::Y * 2.5, 0.001));
+        assert!(ball
+            .transform
+            .borrow()
+            .unwrap()
+            .world_position()
+            .abs_diff_eq(Vec3::Y * 2., 0.001));
+
+        let hit = physics
+            .cast_ray(
+                Vec3::new(5., 5., 0.),
+                Vec3::NEG_Y,
+                10.,
+                true,
+                QueryFilter::new(),
+            )
+            .unwrap();
+        assert!(hit.body.body.is_none());
+        assert!((hit.distance - 4.9).abs() < 0.001);
+    }
+
+    #[test]
+    fn shift_kinematic() {
+        let mut physics = RapierPhysics::new();
+        let mut origin = FloatingOrigin::default();
+        let platform = physics.create_transform(
+            RigidBodyBuilder::kinematic_position_based()
+                .translation(vector![100., 0., 0.])
+                .build(),
+            ColliderBuilder::cuboid(1., 0.1, 1.).build(),
+        );
+        physics.rigid_body_set[platform.handle]
+            .set_next_kinematic_translation(vector![101., 0., 0.]);
+
+        // the transform was moved this frame, and is not pulled back to the body by the shift
+        platform
+            .transform
+            .borrow_mut()
+            .unwrap()
+            .set_world_position(Vec3::new(105., 0., 0.));
+        origin.rebase(Vec3::X * 100., &mut BobaResources::default());
+        physics.shift_origin(Vec3::X * 100.);
+
+        let transform = platform.transform.borrow().unwrap();
+        assert!(transform.world_position().abs_diff_eq(Vec3::X * 5., 0.001));
+        let body = &physics.rigid_body_set[platform.handle];
+        assert!((body.translation().x).abs() < 0.001);
+        assert!((body.next_position().translation.x - 1.).abs() < 0.001);
+    }
+
+    #[test]
+    fn scene_queries() {
+        let mut physics = RapierPhysics::new();
+        let ground = physics.create_transform(
+            RigidBodyBuilder::fixed().build(),
+            ColliderBuilder::cuboid(10., 0.1, 10.).build(),
+        );
+        let ball = physics.create_transform(
+            RigidBodyBuilder::fixed()
+                .translation(vector![0., 2., 0.])
+                .build(),
+            ColliderBuilder::ball(0.5).build(),
+        );
+
+        // new colliders are not seen until the queries are updated
+        let down = |physics: &RapierPhysics, filter| {
+            physics.cast_ray(Vec3::Y * 5., Vec3::NEG_Y * 2., 10., true, filter)
+        };
+        assert!(down(&physics, QueryFilter::new()).is_none());
+        physics.update_queries();
+
+        let hit = down(&physics, QueryFilter::new()).unwrap();
+        assert!(hit.body.is(&ball.transform));
+        assert!((hit.distance - 2.5).abs() < 0.001);
+        assert!(hit.point.abs_diff_eq(Vec3::Y * 2.5, 0.001));
+        assert!(hit.normal.abs_diff_eq(Vec3::Y, 0.001));
+
+        let filter = QueryFilter::new().exclude_rigid_body(hit.body.body.unwrap());
+        let hit = down(&physics, filter).unwrap();
+        assert!(hit.body.is(&ground.transform));
+        assert!((hit.distance - 4.9).abs() < 0.001);
+        assert!(physics
+            .cast_ray(Vec3::Y, Vec3::X, 10., true, QueryFilter::new())
+            .is_none());
+
+        let shape = SharedShape::ball(0.25);
+        let hit = physics
+            .cast_shape(
+                Vec3::new(3., 1., 0.),
+                Quat::IDENTITY,
+                Vec3::NEG_Y,
+                &*shape,
+                10.,
+                QueryFilter::new(),
+            )
+            .unwrap();
+        assert!(hit.body.is(&ground.transform));
+        assert!(!hit.penetrating);
+        assert!((hit.time - 0.65).abs() < 0.001);
+        assert!((hit.point.y - 0.1).abs() < 0.001);
+        assert!(hit.normal.abs_diff_eq(Vec3::Y, 0.001));
+
+        let shape = SharedShape::cuboid(1., 1., 1.);
+        let overlaps =
+            physics.intersections_with_shape(Vec3::Y, Quat::IDENTITY, &*shape, QueryFilter::new());
+        assert_eq!(overlaps.len(), 2);
+        let overlaps = physics.intersections_with_shape(
+            Vec3::Y * 4.,
+            Quat::IDENTITY,
+            &*shape,
+            QueryFilter::new(),
+        );
+        assert!(overlaps.is_empty());
 
-        transform
+        let projection = physics
+            .project_point(Vec3::Y * 3., true, QueryFilter::new())
+            .unwrap();
+        assert!(projection.body.is(&ball.transform));
+        assert!(!projection.inside);
+        assert!(projection.point.abs_diff_eq(Vec3::Y * 2.5, 0.001));
+        let projection = physics
+            .project_point(Vec3::Y * 2., true, QueryFilter::new())
+            .unwrap();
+        assert!(projection.inside);
+        assert!(projection.point.abs_diff_eq(Vec3::Y * 2., 0.001));
     }
 }
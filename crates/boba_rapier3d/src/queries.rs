@@ -0,0 +1,39 @@
+use boba_3d::glam::Vec3;
+
+use crate::CollisionBody;
+
+/// The first collider hit by [`RapierPhysics::cast_ray`](crate::RapierPhysics::cast_ray)
+#[derive(Clone)]
+pub struct RayHit {
+    pub body: CollisionBody,
+    /// The distance along the ray to the hit point
+    pub distance: f32,
+    /// The world position where the ray hit the collider
+    pub point: Vec3,
+    /// The world space normal of the collider surface at the hit point
+    pub normal: Vec3,
+}
+
+/// The first collider hit by [`RapierPhysics::cast_shape`](crate::RapierPhysics::cast_shape)
+#[derive(Clone)]
+pub struct ShapeHit {
+    pub body: CollisionBody,
+    /// The time of impact, in units of the cast velocity
+    pub time: f32,
+    /// The world position of the contact on the hit collider
+    pub point: Vec3,
+    /// The world space normal of the hit collider surface at the contact point
+    pub normal: Vec3,
+    /// True if the shape was already touching the collider when the cast started
+    pub penetrating: bool,
+}
+
+/// The closest point on a collider found by [`RapierPhysics::project_point`](crate::RapierPhysics::project_point)
+#[derive(Clone)]
+pub struct PointHit {
+    pub body: CollisionBody,
+    /// The world position of the closest point on the collider
+    pub point: Vec3,
+    /// True if the projected point is inside the collider
+    pub inside: bool,
+}
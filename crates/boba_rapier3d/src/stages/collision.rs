@@ -0,0 +1,120 @@
+use boba_core::{BobaResources, BobaResult, BobaStage, PearlRegistry};
+
+use crate::{RapierCollision, RapierContactForce, RapierPhysics};
+
+/// Runs pearls once for every [`RapierCollision`] from the latest physics step.
+///
+/// This is run by [`OnRapierUpdate`](super::OnRapierUpdate) right after each step,
+/// so it does not need to be added to the app.
+#[derive(Default)]
+pub struct OnCollision;
+
+impl BobaStage for OnCollision {
+    type Data = RapierCollision;
+
+    fn run(&mut self, registry: &mut PearlRegistry, resources: &mut BobaResources) -> BobaResult {
+        let collisions = resources.get::<RapierPhysics>()?.collisions().to_vec();
+        for collision in &collisions {
+            registry.run_stage::<OnCollision>(collision, resources);
+        }
+        Ok(())
+    }
+}
+
+/// Runs pearls once for every [`RapierContactForce`] from the latest physics step.
+///
+/// This is run by [`OnRapierUpdate`](super::OnRapierUpdate) right after each step,
+/// so it does not need to be added to the app.
+#[derive(Default)]
+pub struct OnContactForce;
+
+impl BobaStage for OnContactForce {
+    type Data = RapierContactForce;
+
+    fn run(&mut self, registry: &mut PearlRegistry, resources: &mut BobaResources) -> BobaResult {
+        let contact_forces = resources.get::<RapierPhysics>()?.contact_forces().to_vec();
+        for contact_force in &contact_forces {
+            registry.run_stage::<OnContactForce>(contact_force, resources);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boba_3d::pearls::BobaTransform;
+    use boba_core::{register_pearl_stages, Pearl, PearlStage};
+    use rapier3d::prelude::{vector, ActiveEvents, ColliderBuilder, RigidBodyBuilder};
+
+    use super::*;
+
+    struct Recorder {
+        transform: Pearl<BobaTransform>,
+        hits: usize,
+        pushes: usize,
+    }
+
+    register_pearl_stages!(Recorder: OnCollision, OnContactForce);
+
+    impl PearlStage<OnCollision> for Recorder {
+        fn update(
+            pearl: &Pearl<Self>,
+            collision: &RapierCollision,
+            _: &mut BobaResources,
+        ) -> BobaResult {
+            let mut recorder = pearl.borrow_mut()?;
+            if collision.started() && collision.other(&recorder.transform).is_some() {
+                recorder.hits += 1;
+            }
+            Ok(())
+        }
+    }
+
+    impl PearlStage<OnContactForce> for Recorder {
+        fn update(
+            pearl: &Pearl<Self>,
+            _: &RapierContactForce,
+            _: &mut BobaResources,
+        ) -> BobaResult {
+            pearl.borrow_mut()?.pushes += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn pearls_receive_events() {
+        let mut physics = RapierPhysics::new();
+        physics.create_transform(
+            RigidBodyBuilder::fixed().build(),
+            ColliderBuilder::cuboid(10., 0.1, 10.).build(),
+        );
+        let ball = physics.create_transform(
+            RigidBodyBuilder::dynamic()
+                .translation(vector![0., 1., 0.])
+                .build(),
+            ColliderBuilder::ball(0.5)
+                .active_events(ActiveEvents::COLLISION_EVENTS | ActiveEvents::CONTACT_FORCE_EVENTS)
+                .build(),
+        );
+
+        let mut registry = PearlRegistry::default();
+        let recorder = Pearl::wrap(Recorder {
+            transform: ball.transform,
+            hits: 0,
+            pushes: 0,
+        });
+        registry.add(recorder.clone());
+
+        let mut resources = BobaResources::default();
+        resources.add(physics);
+        for _ in 0..100 {
+            resources.get_mut::<RapierPhysics>().unwrap().step();
+            OnCollision.run(&mut registry, &mut resources).unwrap();
+            OnContactForce.run(&mut registry, &mut resources).unwrap();
+        }
+
+        let recorder = recorder.borrow().unwrap();
+        assert_eq!(recorder.hits, 1);
+        assert!(recorder.pushes > 0);
+    }
+}
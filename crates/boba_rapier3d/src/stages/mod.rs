@@ -1,3 +1,5 @@
+mod collision;
 mod update;
 
+pub use collision::*;
 pub use update::*;
@@ -1,9 +1,11 @@
 use std::time::Instant;
 
-use boba_core::{BobaResult, BobaStage};
+use boba_core::{BobaResult, BobaStage, StageClock};
 
 use crate::RapierPhysics;
 
+use super::{OnCollision, OnContactForce};
+
 #[derive(Default)]
 pub struct OnRapierUpdate {
     time_collector: f32,
@@ -18,13 +20,16 @@ impl BobaStage for OnRapierUpdate {
         registry: &mut boba_core::PearlRegistry,
         resources: &mut boba_core::BobaResources,
     ) -> BobaResult {
-        self.time_collector += match &self.instant {
+        let elapsed = match &self.instant {
             Some(instant) => instant.elapsed().as_secs_f32(),
             None => 0.,
         };
+        self.time_collector += StageClock::resolve::<OnRapierUpdate>(resources, elapsed)?;
 
         if self.time_collector > (1. / 50.) {
             resources.get_mut::<RapierPhysics>()?.step();
+            OnCollision.run(registry, resources)?;
+            OnContactForce.run(registry, resources)?;
             registry.run_stage::<OnRapierUpdate>(&(), resources);
             self.time_collector = 0.;
         }
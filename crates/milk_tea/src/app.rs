@@ -1,23 +1,49 @@
-use boba_core::{stages::BobaUpdate, BobaResources, BobaStage, PearlRegistry, StageCollection};
+use boba_3d::constraints::OnConstraintUpdate;
+use boba_core::{
+    stages::{
+        BobaFirst, BobaFixedUpdate, BobaLast, BobaLateUpdate, BobaPostRender, BobaPreRender,
+        BobaPreUpdate, BobaUpdate,
+    },
+    BobaResources, BobaSnapshot, PearlRegistry, StageCollection,
+};
+use log::{error, info};
 
 use winit::{
     dpi::PhysicalSize,
     error::OsError,
-    event::{Event, WindowEvent},
+    event::{ElementState, Event, VirtualKeyCode, WindowEvent},
     event_loop::EventLoop,
     window::WindowBuilder,
 };
 
 use crate::{
-    events::{MilkTeaEvent, MilkTeaSize},
-    MilkTeaRenderAdapter, MilkTeaWindow,
+    events::MilkTeaSize, InputRecording, MilkTeaPlayback, MilkTeaRenderAdapter,
+    MilkTeaRenderStages, MilkTeaWindow, RecordedEvent, ReplayError,
 };
 
 pub struct MilkTeaApp {
     pub registry: PearlRegistry,
     pub startup_stages: StageCollection,
+
+    /// Runs every frame before rendering. Contains [`BobaFirst`], [`BobaPreUpdate`], [`BobaUpdate`],
+    /// [`BobaFixedUpdate`], [`BobaLateUpdate`] and [`OnConstraintUpdate`] by default.
+    ///
+    /// Physics stages belong between [`BobaFixedUpdate`] and [`BobaLateUpdate`],
+    /// and can be placed there with [`StageCollection::insert_after`].
     pub main_stages: StageCollection,
+
+    /// Run by the [`MilkTeaRenderAdapter`] every frame right before the window is drawn.
+    /// Contains [`BobaPreRender`] by default.
+    pub pre_render_stages: StageCollection,
+
+    /// Run by the [`MilkTeaRenderAdapter`] every frame right after the window is drawn.
+    /// Contains [`BobaPostRender`] and [`BobaLast`] by default.
+    pub post_render_stages: StageCollection,
     pub resources: BobaResources,
+    pub playback: MilkTeaPlayback,
+
+    /// When pressed, logs a [`BobaSnapshot`] of the app at the info level. Defaults to `F12` in debug builds.
+    pub snapshot_key: Option<VirtualKeyCode>,
 }
 
 impl Default for MilkTeaApp {
@@ -27,11 +53,23 @@ impl Default for MilkTeaApp {
             registry: Default::default(),
             startup_stages: Default::default(),
             main_stages: Default::default(),
+            pre_render_stages: Default::default(),
+            post_render_stages: Default::default(),
             resources: Default::default(),
+            playback: Default::default(),
+            snapshot_key: cfg!(debug_assertions).then_some(VirtualKeyCode::F12),
         };
 
         // add default stages
+        new.main_stages.append(BobaFirst);
+        new.main_stages.append(BobaPreUpdate::default());
         new.main_stages.append(BobaUpdate::default());
+        new.main_stages.append(BobaFixedUpdate::default());
+        new.main_stages.append(BobaLateUpdate::default());
+        new.main_stages.append(OnConstraintUpdate);
+        new.pre_render_stages.append(BobaPreRender);
+        new.post_render_stages.append(BobaPostRender);
+        new.post_render_stages.append(BobaLast);
 
         // return
         new
@@ -53,8 +91,7 @@ impl MilkTeaApp {
         let mut window = MilkTeaWindow::<T>::new(window);
 
         // run the startup stages
-        self.startup_stages
-            .run(&mut self.registry, &mut self.resources);
+        self.start();
 
         // run the main event loop
         event_loop.run(move |event, _, control_flow| {
@@ -62,38 +99,294 @@ impl MilkTeaApp {
 
             match event {
                 Event::WindowEvent { ref event, .. } => match event {
-                    WindowEvent::CloseRequested => control_flow.set_exit(),
+                    WindowEvent::CloseRequested => {
+                        self.finish_playback();
+                        control_flow.set_exit();
+                    }
                     WindowEvent::Resized(size) => {
-                        MilkTeaEvent::new(MilkTeaSize::new(size.width, size.height))
-                            .run(&mut self.registry, &mut self.resources)
-                            .unwrap();
+                        self.handle_event(RecordedEvent::Resize(MilkTeaSize::new(
+                            size.width,
+                            size.height,
+                        )));
                     }
                     WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-                        MilkTeaEvent::new(MilkTeaSize::new(
+                        self.handle_event(RecordedEvent::Resize(MilkTeaSize::new(
                             new_inner_size.width,
                             new_inner_size.height,
-                        ))
-                        .run(&mut self.registry, &mut self.resources)
-                        .unwrap();
+                        )));
                     }
                     WindowEvent::KeyboardInput {
                         device_id: _,
                         input,
                         is_synthetic: _,
                     } => {
-                        MilkTeaEvent::new(*input)
-                            .run(&mut self.registry, &mut self.resources)
-                            .unwrap();
+                        self.handle_event(RecordedEvent::Keyboard(*input));
                     }
                     _ => (),
                 },
                 Event::MainEventsCleared => {
-                    self.main_stages
-                        .run(&mut self.registry, &mut self.resources);
-                    window.render(&mut self.registry, &mut self.resources);
+                    let running = self.update_frame(|registry, resources, stages| {
+                        window.render(registry, resources, stages);
+                    });
+
+                    if !running {
+                        self.finish_playback();
+                        control_flow.set_exit();
+                    }
                 }
                 _ => (),
             }
         })
     }
+
+    /// Runs the app without a window until its replay is finished.
+    ///
+    /// Returns the result of checking the final state against the recording.
+    pub fn run_headless(&mut self) -> Result<(), ReplayError> {
+        if !matches!(self.playback, MilkTeaPlayback::Replay(_)) {
+            return Err(ReplayError::NotReplaying);
+        }
+
+        self.start();
+        while self.update_frame(skip_render) {}
+        self.dispatch_trailing_events();
+
+        match &self.playback {
+            MilkTeaPlayback::Replay(replayer) => replayer.verify(&self.registry, &self.resources),
+            _ => Err(ReplayError::NotReplaying),
+        }
+    }
+
+    /// Captures a snapshot of the stages, pearls and resources in this app
+    pub fn snapshot(&self) -> BobaSnapshot {
+        BobaSnapshot::capture(
+            &self.registry,
+            &self.resources,
+            &[
+                ("startup", &self.startup_stages),
+                ("main", &self.main_stages),
+                ("pre render", &self.pre_render_stages),
+                ("post render", &self.post_render_stages),
+            ],
+        )
+    }
+
+    /// Installs the clock for the current playback and runs the startup stages
+    pub(crate) fn start(&mut self) {
+        self.resources.add(self.playback.clock());
+        self.startup_stages
+            .run(&mut self.registry, &mut self.resources);
+    }
+
+    /// Sends a live event to the app.
+    ///
+    /// Live events are recorded when recording, and ignored when replaying.
+    pub(crate) fn handle_event(&mut self, event: RecordedEvent) {
+        if let RecordedEvent::Keyboard(input) = &event {
+            let pressed = input.state == ElementState::Pressed;
+            if pressed
+                && input.virtual_keycode.is_some()
+                && input.virtual_keycode == self.snapshot_key
+            {
+                info!("{}", self.snapshot());
+            }
+        }
+
+        match &mut self.playback {
+            MilkTeaPlayback::Replay(_) => return,
+            MilkTeaPlayback::Record(recorder) => recorder.record_event(event.clone()),
+            MilkTeaPlayback::Live => (),
+        }
+
+        if let Err(e) = event.dispatch(&mut self.registry, &mut self.resources) {
+            error!("There was an error while dispatching event {event:?}. Error: {e}");
+        }
+    }
+
+    /// Runs a single frame of the main stages, then calls `render`, which runs the render stages around drawing.
+    ///
+    /// Returns `false` if the app is replaying and there are no frames left.
+    pub(crate) fn update_frame(
+        &mut self,
+        render: impl FnOnce(&mut PearlRegistry, &mut BobaResources, MilkTeaRenderStages),
+    ) -> bool {
+        if let MilkTeaPlayback::Replay(replayer) = &mut self.playback {
+            let Some(events) = replayer.begin_frame(&self.resources) else {
+                return false;
+            };
+
+            for event in events {
+                if let Err(e) = event.dispatch(&mut self.registry, &mut self.resources) {
+                    error!("There was an error while replaying event {event:?}. Error: {e}");
+                }
+            }
+        }
+
+        self.main_stages
+            .run(&mut self.registry, &mut self.resources);
+        let stages =
+            MilkTeaRenderStages::new(&mut self.pre_render_stages, &mut self.post_render_stages);
+        render(&mut self.registry, &mut self.resources, stages);
+
+        if let MilkTeaPlayback::Record(recorder) = &mut self.playback {
+            recorder.end_frame(&self.resources);
+        }
+
+        true
+    }
+
+    /// Stops recording and returns the finished recording
+    pub(crate) fn finish_recording(&mut self) -> Option<InputRecording> {
+        let MilkTeaPlayback::Record(recorder) = std::mem::take(&mut self.playback) else {
+            return None;
+        };
+
+        Some(recorder.finish(&self.registry, &self.resources))
+    }
+
+    fn dispatch_trailing_events(&mut self) {
+        let MilkTeaPlayback::Replay(replayer) = &self.playback else {
+            return;
+        };
+
+        for event in replayer.trailing_events() {
+            if let Err(e) = event.dispatch(&mut self.registry, &mut self.resources) {
+                error!("There was an error while replaying event {event:?}. Error: {e}");
+            }
+        }
+    }
+
+    /// Saves a finished recording or verifies a finished replay
+    fn finish_playback(&mut self) {
+        match &self.playback {
+            MilkTeaPlayback::Live => (),
+            MilkTeaPlayback::Record(recorder) => {
+                let Some(path) = recorder.path().map(|p| p.to_path_buf()) else {
+                    return;
+                };
+
+                let Some(recording) = self.finish_recording() else {
+                    return;
+                };
+
+                match recording.save(&path) {
+                    Ok(_) => info!("Saved input recording to {}", path.display()),
+                    Err(e) => error!("Could not save input recording. Error: {e}"),
+                }
+            }
+            MilkTeaPlayback::Replay(_) => {
+                self.dispatch_trailing_events();
+                let MilkTeaPlayback::Replay(replayer) = &self.playback else {
+                    return;
+                };
+
+                match replayer.verify(&self.registry, &self.resources) {
+                    Ok(_) => info!("Replay finished in the recorded state"),
+                    Err(e) => error!("Replay finished. {e}"),
+                }
+            }
+        }
+    }
+}
+
+/// Runs the render stages of a frame without drawing anything
+pub(crate) fn skip_render(
+    registry: &mut PearlRegistry,
+    resources: &mut BobaResources,
+    mut stages: MilkTeaRenderStages,
+) {
+    stages.pre_render(registry, resources);
+    stages.post_render(registry, resources);
+}
+
+#[cfg(test)]
+mod tests {
+    use boba_core::{register_pearl_stages, BobaResult, BobaStage, Pearl, PearlStage};
+
+    use super::*;
+
+    struct Order(Vec<&'static str>);
+
+    struct PhysicsStage;
+
+    impl BobaStage for PhysicsStage {
+        type Data = ();
+
+        fn run(&mut self, _: &mut PearlRegistry, resources: &mut BobaResources) -> BobaResult {
+            resources.get_mut::<Order>()?.0.push("physics");
+            Ok(())
+        }
+    }
+
+    struct Follower;
+
+    register_pearl_stages!(
+        Follower: BobaUpdate,
+        BobaLateUpdate,
+        OnConstraintUpdate,
+        BobaPreRender,
+        BobaPostRender
+    );
+
+    impl PearlStage<BobaUpdate> for Follower {
+        fn update(_: &Pearl<Self>, _: &f32, resources: &mut BobaResources) -> BobaResult {
+            resources.get_mut::<Order>()?.0.push("update");
+            Ok(())
+        }
+    }
+
+    impl PearlStage<BobaLateUpdate> for Follower {
+        fn update(_: &Pearl<Self>, _: &f32, resources: &mut BobaResources) -> BobaResult {
+            resources.get_mut::<Order>()?.0.push("late update");
+            Ok(())
+        }
+    }
+
+    impl PearlStage<OnConstraintUpdate> for Follower {
+        fn update(_: &Pearl<Self>, _: &(), resources: &mut BobaResources) -> BobaResult {
+            resources.get_mut::<Order>()?.0.push("constraints");
+            Ok(())
+        }
+    }
+
+    impl PearlStage<BobaPreRender> for Follower {
+        fn update(_: &Pearl<Self>, _: &(), resources: &mut BobaResources) -> BobaResult {
+            resources.get_mut::<Order>()?.0.push("pre render");
+            Ok(())
+        }
+    }
+
+    impl PearlStage<BobaPostRender> for Follower {
+        fn update(_: &Pearl<Self>, _: &(), resources: &mut BobaResources) -> BobaResult {
+            resources.get_mut::<Order>()?.0.push("post render");
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn stage_order() {
+        let mut app = MilkTeaApp::default();
+        app.main_stages
+            .insert_after::<BobaFixedUpdate, _>(PhysicsStage);
+        app.registry.add(Pearl::wrap(Follower));
+        app.resources.add(Order(Vec::new()));
+
+        app.update_frame(|registry, resources, mut stages| {
+            stages.pre_render(registry, resources);
+            resources.get_mut::<Order>().unwrap().0.push("draw");
+            stages.post_render(registry, resources);
+        });
+
+        let order = &app.resources.get::<Order>().unwrap().0;
+        let expected = [
+            "update",
+            "physics",
+            "late update",
+            "constraints",
+            "pre render",
+            "draw",
+            "post render",
+        ];
+        assert_eq!(order, &expected);
+    }
 }
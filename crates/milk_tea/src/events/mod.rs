@@ -2,7 +2,9 @@ mod event;
 
 pub use event::*;
 
-#[derive(Debug, Clone, Copy)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MilkTeaSize {
     pub width: u32,
     pub height: u32,
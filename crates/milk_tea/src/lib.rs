@@ -1,7 +1,9 @@
 mod app;
+mod recording;
 mod window;
 
 pub use app::*;
+pub use recording::*;
 pub use window::*;
 
 pub mod events;
@@ -0,0 +1,471 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use boba_core::{BobaResources, BobaResult, BobaStage, PearlRegistry, StageClock, StageDelta};
+use log::error;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use winit::event::KeyboardInput;
+
+use crate::events::{MilkTeaEvent, MilkTeaSize};
+
+/// A function that reduces the state of an app to a single value.
+///
+/// Used to check that a replay ends in the same state as the run it was recorded from.
+pub type ChecksumHook = Box<dyn Fn(&PearlRegistry, &BobaResources) -> u64>;
+
+/// An error returned when saving or loading an [`InputRecording`].
+#[derive(Debug, Error)]
+pub enum RecordingError {
+    #[error("Could not access recording file. Error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not encode recording. Error: {0}")]
+    Encode(#[from] rmp_serde::encode::Error),
+    #[error("Could not decode recording. Error: {0}")]
+    Decode(#[from] rmp_serde::decode::Error),
+}
+
+/// An error returned when verifying a finished replay.
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("The app is not set up to replay a recording")]
+    NotReplaying,
+    #[error("The recording or replay does not have a checksum hook")]
+    MissingChecksum,
+    #[error("Replay ended in a different state. Expected checksum {expected}, found {found}")]
+    ChecksumMismatch { expected: u64, found: u64 },
+}
+
+/// An event that was sent to the app, and can be sent again during a replay.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RecordedEvent {
+    Keyboard(KeyboardInput),
+    Resize(MilkTeaSize),
+}
+
+impl RecordedEvent {
+    /// Runs the matching [`MilkTeaEvent`] stage for this event
+    pub fn dispatch(
+        &self,
+        registry: &mut PearlRegistry,
+        resources: &mut BobaResources,
+    ) -> BobaResult {
+        match self {
+            RecordedEvent::Keyboard(input) => MilkTeaEvent::new(*input).run(registry, resources),
+            RecordedEvent::Resize(size) => MilkTeaEvent::new(*size).run(registry, resources),
+        }
+    }
+}
+
+/// A stage delta that was consumed during a recorded frame.
+///
+/// The stage is an index into the [`stages`](InputRecording::stages) of the recording.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedDelta {
+    pub stage: u16,
+    pub delta: f32,
+}
+
+/// All the events and deltas for a single frame.
+///
+/// Events are sent before the main stages of the frame are run.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    pub events: Vec<RecordedEvent>,
+    pub deltas: Vec<RecordedDelta>,
+}
+
+/// A compact record of every input and delta of a run.
+///
+/// Events that arrived after the last full frame are kept in `trailing`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputRecording {
+    /// The name of every stage that consumed a delta, which the frames refer to by index
+    pub stages: Vec<String>,
+    pub frames: Vec<RecordedFrame>,
+    pub trailing: Vec<RecordedEvent>,
+    pub checksum: Option<u64>,
+}
+
+impl InputRecording {
+    /// Loads a recording from the file at `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    /// Saves the recording to the file at `path`
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), RecordingError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads a recording from `reader`
+    pub fn read_from(reader: impl Read) -> Result<Self, RecordingError> {
+        Ok(rmp_serde::decode::from_read(reader)?)
+    }
+
+    /// Writes the recording to `writer`
+    pub fn write_to(&self, writer: &mut impl Write) -> Result<(), RecordingError> {
+        Ok(rmp_serde::encode::write(writer, self)?)
+    }
+
+    /// Converts a stage delta into a recorded delta, adding its stage to the name table if it is new
+    fn record_delta(&mut self, delta: StageDelta) -> RecordedDelta {
+        let stage = match self.stages.iter().position(|stage| *stage == delta.stage) {
+            Some(index) => index,
+            None => {
+                self.stages.push(delta.stage);
+                self.stages.len() - 1
+            }
+        };
+
+        RecordedDelta {
+            stage: stage as u16,
+            delta: delta.delta,
+        }
+    }
+
+    /// Converts a recorded delta back into a stage delta, using the name table.
+    ///
+    /// Returns `None` if the stage index is not in the table.
+    fn stage_delta(&self, delta: &RecordedDelta) -> Option<StageDelta> {
+        Some(StageDelta {
+            stage: self.stages.get(delta.stage as usize)?.clone(),
+            delta: delta.delta,
+        })
+    }
+}
+
+/// Records the events and deltas of a running app.
+pub struct InputRecorder {
+    path: Option<PathBuf>,
+    recording: InputRecording,
+    current: RecordedFrame,
+    checksum: Option<ChecksumHook>,
+}
+
+impl InputRecorder {
+    /// Creates a recorder that keeps the recording in memory
+    pub fn new() -> Self {
+        Self {
+            path: None,
+            recording: Default::default(),
+            current: Default::default(),
+            checksum: None,
+        }
+    }
+
+    /// Creates a recorder that saves the recording to `path` when the app closes
+    pub fn to_file(path: impl Into<PathBuf>) -> Self {
+        let mut new = Self::new();
+        new.path = Some(path.into());
+        new
+    }
+
+    /// Sets a hook that stores a checksum of the final app state in the recording
+    pub fn with_checksum(
+        mut self,
+        hook: impl Fn(&PearlRegistry, &BobaResources) -> u64 + 'static,
+    ) -> Self {
+        self.checksum = Some(Box::new(hook));
+        self
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Records an event for the current frame
+    pub fn record_event(&mut self, event: RecordedEvent) {
+        self.current.events.push(event);
+    }
+
+    /// Ends the current frame, collecting all deltas from the [`StageClock`] in `resources`
+    pub fn end_frame(&mut self, resources: &BobaResources) {
+        let mut frame = std::mem::take(&mut self.current);
+        if let Ok(mut clock) = resources.get_mut::<StageClock>() {
+            let deltas = clock.take_recorded();
+            frame.deltas = deltas
+                .into_iter()
+                .map(|delta| self.recording.record_delta(delta))
+                .collect();
+        }
+
+        self.recording.frames.push(frame);
+    }
+
+    /// Consumes the recorder and produces the final recording
+    pub fn finish(mut self, registry: &PearlRegistry, resources: &BobaResources) -> InputRecording {
+        self.recording.trailing = self.current.events;
+        self.recording.checksum = self.checksum.map(|hook| hook(registry, resources));
+        self.recording
+    }
+}
+
+impl Default for InputRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Plays back an [`InputRecording`] in place of live input and timing.
+pub struct InputReplayer {
+    recording: InputRecording,
+    frame: usize,
+    checksum: Option<ChecksumHook>,
+}
+
+impl InputReplayer {
+    pub fn new(recording: InputRecording) -> Self {
+        Self {
+            recording,
+            frame: 0,
+            checksum: None,
+        }
+    }
+
+    /// Loads the recording at `path` to be replayed
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        Ok(Self::new(InputRecording::load(path)?))
+    }
+
+    /// Sets a hook that is used to verify the final app state against the recording
+    pub fn with_checksum(
+        mut self,
+        hook: impl Fn(&PearlRegistry, &BobaResources) -> u64 + 'static,
+    ) -> Self {
+        self.checksum = Some(Box::new(hook));
+        self
+    }
+
+    pub fn recording(&self) -> &InputRecording {
+        &self.recording
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.recording.frames.len()
+    }
+
+    /// Starts the next frame of the replay.
+    ///
+    /// Queues the recorded deltas into the [`StageClock`] in `resources`, and returns the events to dispatch.
+    /// Returns `None` when there are no frames left.
+    pub fn begin_frame(&mut self, resources: &BobaResources) -> Option<&[RecordedEvent]> {
+        let frame = self.recording.frames.get(self.frame)?;
+        self.frame += 1;
+
+        if let Ok(mut clock) = resources.get_mut::<StageClock>() {
+            clock.queue_replay(frame.deltas.iter().filter_map(|delta| {
+                let stage_delta = self.recording.stage_delta(delta);
+                if stage_delta.is_none() {
+                    error!("Recorded delta refers to unknown stage {}", delta.stage);
+                }
+                stage_delta
+            }));
+        }
+
+        Some(&frame.events)
+    }
+
+    /// Gets the events that were recorded after the last full frame
+    pub fn trailing_events(&self) -> &[RecordedEvent] {
+        &self.recording.trailing
+    }
+
+    /// Checks that the app ended in the same state as the recorded run
+    pub fn verify(
+        &self,
+        registry: &PearlRegistry,
+        resources: &BobaResources,
+    ) -> Result<(), ReplayError> {
+        let (Some(hook), Some(expected)) = (&self.checksum, self.recording.checksum) else {
+            return Err(ReplayError::MissingChecksum);
+        };
+
+        let found = hook(registry, resources);
+        match found == expected {
+            true => Ok(()),
+            false => Err(ReplayError::ChecksumMismatch { expected, found }),
+        }
+    }
+}
+
+/// How a [`MilkTeaApp`](crate::MilkTeaApp) receives its input and timing.
+#[derive(Default)]
+pub enum MilkTeaPlayback {
+    #[default]
+    Live,
+    Record(InputRecorder),
+    Replay(InputReplayer),
+}
+
+impl MilkTeaPlayback {
+    /// Creates the [`StageClock`] that matches this playback mode
+    pub fn clock(&self) -> StageClock {
+        match self {
+            MilkTeaPlayback::Live => StageClock::live(),
+            MilkTeaPlayback::Record(_) => StageClock::recording(),
+            MilkTeaPlayback::Replay(_) => StageClock::replaying(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boba_core::{
+        register_pearl_stages, stages::BobaUpdate, BobaResources, BobaResult, Pearl, PearlStage,
+    };
+    use winit::event::{ElementState, KeyboardInput, VirtualKeyCode};
+
+    use crate::{
+        events::MilkTeaEvent, InputRecorder, InputRecording, InputReplayer, MilkTeaApp,
+        MilkTeaPlayback, RecordedEvent, ReplayError,
+    };
+
+    #[derive(Default)]
+    struct TestState {
+        moved: f32,
+        presses: u32,
+    }
+
+    #[derive(Default)]
+    struct TestMover {
+        direction: f32,
+    }
+
+    register_pearl_stages!(TestMover: BobaUpdate, MilkTeaEvent<KeyboardInput>);
+
+    impl PearlStage<BobaUpdate> for TestMover {
+        fn update(pearl: &Pearl<Self>, delta: &f32, resources: &mut BobaResources) -> BobaResult {
+            let pearl = pearl.borrow()?;
+            resources.get_mut::<TestState>()?.moved += pearl.direction * delta;
+            Ok(())
+        }
+    }
+
+    impl PearlStage<MilkTeaEvent<KeyboardInput>> for TestMover {
+        fn update(
+            pearl: &Pearl<Self>,
+            data: &KeyboardInput,
+            resources: &mut BobaResources,
+        ) -> BobaResult {
+            let mut pearl = pearl.borrow_mut()?;
+            pearl.direction = match data.state {
+                ElementState::Pressed => 1.,
+                ElementState::Released => 0.,
+            };
+            resources.get_mut::<TestState>()?.presses += 1;
+            Ok(())
+        }
+    }
+
+    fn checksum(_: &boba_core::PearlRegistry, resources: &BobaResources) -> u64 {
+        let state = resources.get::<TestState>().unwrap();
+        (state.moved.to_bits() as u64) << 32 | state.presses as u64
+    }
+
+    #[allow(deprecated)]
+    fn key(state: ElementState) -> RecordedEvent {
+        RecordedEvent::Keyboard(KeyboardInput {
+            scancode: 0,
+            state,
+            virtual_keycode: Some(VirtualKeyCode::Right),
+            modifiers: Default::default(),
+        })
+    }
+
+    fn test_app(playback: MilkTeaPlayback) -> MilkTeaApp {
+        let mut app = MilkTeaApp {
+            playback,
+            ..Default::default()
+        };
+        app.registry.add(Pearl::wrap(TestMover::default()));
+        app.resources.add(TestState::default());
+        app
+    }
+
+    #[test]
+    fn save_and_load() {
+        let mut recorder = InputRecorder::new();
+        recorder.record_event(key(ElementState::Pressed));
+        recorder.end_frame(&BobaResources::default());
+        let recording = recorder.finish(&Default::default(), &Default::default());
+
+        let mut bytes = Vec::new();
+        recording.write_to(&mut bytes).unwrap();
+        let loaded = InputRecording::read_from(bytes.as_slice()).unwrap();
+
+        assert!(loaded == recording);
+    }
+
+    #[test]
+    fn replay_matches_recording() {
+        let mut app = test_app(MilkTeaPlayback::Record(
+            InputRecorder::new().with_checksum(checksum),
+        ));
+        app.start();
+        for frame in 0..10 {
+            match frame {
+                2 => app.handle_event(key(ElementState::Pressed)),
+                7 => app.handle_event(key(ElementState::Released)),
+                _ => (),
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+            app.update_frame(crate::skip_render);
+        }
+        let recording = app.finish_recording().unwrap();
+        assert!(recording.frames.len() == 10);
+
+        // every stage name is written once, and deltas refer to it by index
+        let update = recording
+            .stages
+            .iter()
+            .position(|stage| stage == "BobaUpdate");
+        let update = update.unwrap() as u16;
+        assert!(recording.frames.iter().all(|frame| frame
+            .deltas
+            .iter()
+            .filter(|delta| delta.stage == update)
+            .count()
+            == 1));
+        let mut names = recording.stages.clone();
+        names.sort();
+        names.dedup();
+        assert!(names.len() == recording.stages.len());
+
+        let mut replay = test_app(MilkTeaPlayback::Replay(
+            InputReplayer::new(recording).with_checksum(checksum),
+        ));
+        replay.run_headless().unwrap();
+
+        let original = app.resources.get::<TestState>().unwrap();
+        let replayed = replay.resources.get::<TestState>().unwrap();
+        assert!(original.presses == 2);
+        assert!(original.moved > 0.);
+        assert!(original.moved == replayed.moved);
+    }
+
+    #[test]
+    fn replay_detects_mismatch() {
+        let mut recorder = InputRecorder::new().with_checksum(checksum);
+        recorder.record_event(key(ElementState::Pressed));
+        let recording = recorder.finish(&Default::default(), &{
+            let mut resources = BobaResources::default();
+            resources.add(TestState::default());
+            resources
+        });
+
+        let mut replay = test_app(MilkTeaPlayback::Replay(
+            InputReplayer::new(recording).with_checksum(checksum),
+        ));
+        assert!(matches!(
+            replay.run_headless(),
+            Err(ReplayError::ChecksumMismatch { .. })
+        ));
+    }
+}
@@ -1,4 +1,4 @@
-use boba_core::{BobaResources, BobaResult, PearlRegistry};
+use boba_core::{BobaResources, BobaResult, PearlRegistry, StageCollection};
 use log::error;
 use winit::{dpi::PhysicalSize, window::Window};
 
@@ -6,14 +6,45 @@ pub trait MilkTeaRenderAdapter: 'static {
     fn build(window: &Window) -> Self
     where
         Self: Sized;
+
+    /// Draws a frame to the window.
+    ///
+    /// The adapter runs [`MilkTeaRenderStages::pre_render`] right before drawing,
+    /// and [`MilkTeaRenderStages::post_render`] right after, even if nothing could be drawn.
     fn render(
         &mut self,
         window_size: PhysicalSize<u32>,
         registry: &mut PearlRegistry,
         resources: &mut BobaResources,
+        stages: MilkTeaRenderStages,
     ) -> BobaResult;
 }
 
+/// The stages that a [`MilkTeaRenderAdapter`] runs around drawing a frame.
+pub struct MilkTeaRenderStages<'a> {
+    pre_render: &'a mut StageCollection,
+    post_render: &'a mut StageCollection,
+}
+
+impl<'a> MilkTeaRenderStages<'a> {
+    pub fn new(pre_render: &'a mut StageCollection, post_render: &'a mut StageCollection) -> Self {
+        Self {
+            pre_render,
+            post_render,
+        }
+    }
+
+    /// Runs the stages that prepare the frame to be drawn
+    pub fn pre_render(&mut self, registry: &mut PearlRegistry, resources: &mut BobaResources) {
+        self.pre_render.run(registry, resources);
+    }
+
+    /// Runs the stages that follow the drawn frame
+    pub fn post_render(&mut self, registry: &mut PearlRegistry, resources: &mut BobaResources) {
+        self.post_render.run(registry, resources);
+    }
+}
+
 pub struct MilkTeaWindow<T: MilkTeaRenderAdapter> {
     renderer: T,
     window: Window,
@@ -27,9 +58,14 @@ impl<T: MilkTeaRenderAdapter> MilkTeaWindow<T> {
         }
     }
 
-    pub(crate) fn render(&mut self, registry: &mut PearlRegistry, resources: &mut BobaResources) {
+    pub(crate) fn render(
+        &mut self,
+        registry: &mut PearlRegistry,
+        resources: &mut BobaResources,
+        stages: MilkTeaRenderStages,
+    ) {
         let size = self.window.inner_size();
-        if let Err(e) = self.renderer.render(size, registry, resources) {
+        if let Err(e) = self.renderer.render(size, registry, resources, stages) {
             error!("There was an error when rendering the milk tea window. Error: {e}");
         }
     }
@@ -1,5 +1,9 @@
-use boba_3d::pearls::BobaTransform;
-use boba_core::Pearl;
+use boba_3d::{
+    geometry::Ray,
+    glam::{Mat4, Vec2, Vec3, Vec4Swizzles},
+    pearls::BobaTransform,
+};
+use boba_core::{Pearl, PearlError};
 use log::error;
 
 use crate::{
@@ -26,7 +30,17 @@ impl Default for TaroCameraSettings {
     }
 }
 
+impl TaroCameraSettings {
+    /// Creates the perspective projection matrix for a camera with these settings
+    pub fn projection_matrix(&self, aspect: f32) -> Mat4 {
+        Mat4::perspective_rh(self.fovy.to_radians(), aspect, self.znear, self.zfar)
+    }
+}
+
 /// Core struct to render images to a [`RenderTexture`]
+///
+/// Viewport coordinates go from `(0, 0)` in the top left of the rendered image to `(1, 1)` in the bottom right,
+/// so a cursor position can be converted by dividing it by the window size.
 pub struct TaroCamera {
     aspect_ratio: f32,
     camera_matrix: Taro<Bind<Buffer<Uniform<CameraMatrix>>>>,
@@ -61,6 +75,56 @@ impl TaroCamera {
         }
     }
 
+    /// Gets the aspect ratio of the last texture the camera rendered to
+    pub fn aspect_ratio(&self) -> f32 {
+        self.aspect_ratio
+    }
+
+    /// Sets the aspect ratio of the camera. It is replaced the next time the camera renders.
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f32) {
+        self.aspect_ratio = aspect_ratio;
+    }
+
+    /// Gets the `projection * view` matrix of the camera
+    pub fn view_projection_matrix(&self) -> Result<Mat4, PearlError> {
+        let transform = self.transform.borrow()?;
+        let view =
+            CameraMatrix::view_matrix(transform.world_position(), transform.world_rotation());
+        Ok(self.settings.projection_matrix(self.aspect_ratio) * view)
+    }
+
+    /// Creates a ray from the near plane of the camera through `viewport`
+    pub fn viewport_to_ray(&self, viewport: Vec2) -> Result<Ray, PearlError> {
+        let inverse = self.view_projection_matrix()?.inverse();
+        let ndc = Vec2::new(viewport.x * 2. - 1., 1. - viewport.y * 2.);
+        let near = inverse.project_point3(ndc.extend(0.));
+        let far = inverse.project_point3(ndc.extend(1.));
+        Ok(Ray::new(near, far - near))
+    }
+
+    /// Converts a world space `point` into viewport coordinates.
+    ///
+    /// The `z` value is the depth of the point in front of the camera in world units, and is negative behind it.
+    pub fn world_to_viewport(&self, point: Vec3) -> Result<Vec3, PearlError> {
+        let view_projection = self.view_projection_matrix()?;
+        let transform = self.transform.borrow()?;
+        let depth = (point - transform.world_position()).dot(transform.forward());
+
+        let clip = view_projection * point.extend(1.);
+        let ndc = clip.xy() / clip.w;
+        Ok(Vec3::new((ndc.x + 1.) * 0.5, (1. - ndc.y) * 0.5, depth))
+    }
+
+    /// Converts `viewport` coordinates into a world space point, `viewport.z` world units in front of the camera
+    pub fn viewport_to_world(&self, viewport: Vec3) -> Result<Vec3, PearlError> {
+        let ray = self.viewport_to_ray(viewport.truncate())?;
+        let transform = self.transform.borrow()?;
+        let forward = transform.forward();
+        let origin_depth = (ray.origin - transform.world_position()).dot(forward);
+        let distance = (viewport.z - origin_depth) / ray.direction.dot(forward);
+        Ok(ray.origin + ray.direction * distance)
+    }
+
     /// Replaces the cameras current [`RenderPipeline`] with a new `pipeline`
     pub fn set_pipeline(&mut self, pipeline: impl RenderPipeline) {
         self.pipeline = Box::new(pipeline)
@@ -97,3 +161,76 @@ impl TaroCamera {
             .render(texture, pearls, &self.camera_matrix, hardware);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use boba_3d::glam::Quat;
+
+    use super::*;
+
+    const EPSILON: f32 = 0.001;
+
+    struct EmptyPipeline;
+
+    impl RenderPipeline for EmptyPipeline {
+        fn render(
+            &mut self,
+            _: &RenderTexture,
+            _: &TaroRenderPearls,
+            _: &Taro<Bind<Buffer<Uniform<CameraMatrix>>>>,
+            _: &TaroHardware,
+        ) {
+        }
+    }
+
+    fn camera() -> TaroCamera {
+        let transform = BobaTransform::from_position_rotation(
+            Vec3::new(0., 2., -10.),
+            Quat::from_rotation_y(0.3),
+        );
+        let mut camera = TaroCamera::new_simple(transform, EmptyPipeline);
+        camera.set_aspect_ratio(16. / 9.);
+        camera
+    }
+
+    #[test]
+    fn center_ray() {
+        let camera = camera();
+        let ray = camera.viewport_to_ray(Vec2::splat(0.5)).unwrap();
+        let transform = camera.transform.borrow().unwrap();
+
+        assert!(ray.direction.abs_diff_eq(transform.forward(), EPSILON));
+        let near = transform.world_position() + transform.forward() * camera.settings.znear;
+        assert!(ray.origin.abs_diff_eq(near, EPSILON));
+    }
+
+    #[test]
+    fn viewport_round_trip() {
+        let camera = camera();
+        let viewport = Vec3::new(0.2, 0.9, 15.);
+
+        let world = camera.viewport_to_world(viewport).unwrap();
+        let back = camera.world_to_viewport(world).unwrap();
+        assert!(back.abs_diff_eq(viewport, EPSILON), "{back} != {viewport}");
+
+        // the world point is on the ray through the same viewport position
+        let ray = camera.viewport_to_ray(viewport.truncate()).unwrap();
+        assert!(ray.closest_point(world).abs_diff_eq(world, EPSILON));
+    }
+
+    #[test]
+    fn viewport_orientation() {
+        let camera = camera();
+        let transform = camera.transform.borrow().unwrap();
+        let ahead = transform.world_position() + transform.forward() * 10.;
+
+        // up is the top of the viewport, and right is the right of the viewport
+        let up = camera.world_to_viewport(ahead + transform.up()).unwrap();
+        assert!(up.y < 0.5 && (up.x - 0.5).abs() < EPSILON);
+        let right = camera.world_to_viewport(ahead + transform.right()).unwrap();
+        assert!(right.x > 0.5 && (right.y - 0.5).abs() < EPSILON);
+
+        let behind = camera.world_to_viewport(transform.world_position() - transform.forward());
+        assert!(behind.unwrap().z < 0.);
+    }
+}
@@ -3,9 +3,9 @@ use wgpu::util::DeviceExt;
 use crate::{Bind, BindCompiler, BindSettings, Compiler, Taro, TaroHardware};
 
 pub type UniformBuffer<T> = Buffer<Uniform<T>>;
-pub type StorageBuffer<T> = Buffer<Uniform<T>>;
+pub type StorageBuffer<T> = Buffer<Storage<T, true>>;
 pub type UniformBinding<T> = Bind<Buffer<Uniform<T>>>;
-pub type StorageBinding<T> = Bind<Buffer<Uniform<T>>>;
+pub type StorageBinding<T> = Bind<Buffer<Storage<T, true>>>;
 
 /// Required for data to be uploaded to the GPU
 pub trait BytesBuilder: Default + 'static {
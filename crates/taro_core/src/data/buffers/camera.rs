@@ -23,20 +23,28 @@ impl BytesBuilder for CameraMatrix {
     }
 }
 
+impl From<Mat4> for CameraMatrix {
+    fn from(value: Mat4) -> Self {
+        Self {
+            matrix_data: value.to_cols_array_2d(),
+        }
+    }
+}
+
 impl CameraMatrix {
     /// Creates a new camera matrix with the provided properties
     pub fn new(position: Vec3, rotation: Quat, aspect: f32, settings: &TaroCameraSettings) -> Self {
-        let target = position + rotation * Vec3::Z;
-        let view = Mat4::look_at_rh(position, target, Vec3::Y);
-        let proj = Mat4::perspective_rh(
-            settings.fovy.to_radians(),
-            aspect,
-            settings.znear,
-            settings.zfar,
-        );
+        let view = Self::view_matrix(position, rotation);
+        let proj = settings.projection_matrix(aspect);
 
         Self {
             matrix_data: (proj * view).to_cols_array_2d(),
         }
     }
+
+    /// Creates the view matrix for a camera at `position` looking down the `Z` axis of `rotation`
+    pub fn view_matrix(position: Vec3, rotation: Quat) -> Mat4 {
+        let target = position + rotation * Vec3::Z;
+        Mat4::look_at_rh(position, target, Vec3::Y)
+    }
 }
@@ -0,0 +1,51 @@
+use boba_3d::glam::Mat4;
+
+use crate::data::BytesBuilder;
+
+/// Skinning matrices for every joint of a skeleton, uploaded as a storage buffer.
+///
+/// The size of a compiled buffer is fixed by its default value,
+/// so the default should be created with [`JointMatrices::identity`] using the joint count.
+#[derive(Debug, Clone)]
+pub struct JointMatrices {
+    matrices: Vec<[[f32; 4]; 4]>,
+}
+
+impl Default for JointMatrices {
+    fn default() -> Self {
+        // storage buffers cannot be empty
+        Self::identity(1)
+    }
+}
+
+impl BytesBuilder for JointMatrices {
+    const LABEL: &'static str = "Joint Matrices";
+    fn build_bytes(&self) -> &[u8] {
+        bytemuck::cast_slice(&self.matrices)
+    }
+}
+
+impl JointMatrices {
+    /// Creates `count` identity matrices
+    pub fn identity(count: usize) -> Self {
+        Self {
+            matrices: vec![Mat4::IDENTITY.to_cols_array_2d(); count.max(1)],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.matrices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.matrices.is_empty()
+    }
+}
+
+impl From<&[Mat4]> for JointMatrices {
+    fn from(value: &[Mat4]) -> Self {
+        Self {
+            matrices: value.iter().map(|m| m.to_cols_array_2d()).collect(),
+        }
+    }
+}
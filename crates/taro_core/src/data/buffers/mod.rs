@@ -1,9 +1,11 @@
 mod camera;
 mod color;
+mod joints;
 mod light;
 mod transform;
 
 pub use camera::*;
 pub use color::*;
+pub use joints::*;
 pub use light::*;
 pub use transform::*;
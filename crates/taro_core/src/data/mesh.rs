@@ -6,6 +6,10 @@ use std::{
     sync::atomic::{AtomicU32, Ordering},
 };
 
+use boba_3d::{
+    geometry::{Aabb, BoundingSphere, Ray},
+    glam::Vec3,
+};
 use tobj::LoadError;
 use wgpu::util::DeviceExt;
 
@@ -27,6 +31,42 @@ impl Vertex {
     };
 }
 
+/// A [`Vertex`] that can be deformed by up to four joints of a skeleton
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SkinnedVertex {
+    pub position: [f32; 3],
+    pub uv: [f32; 2],
+    pub normal: [f32; 3],
+    pub joints: [u32; 4],
+    pub weights: [f32; 4],
+}
+
+impl SkinnedVertex {
+    pub const BUFFER_LAYOUT: wgpu::VertexBufferLayout<'_> = wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<SkinnedVertex>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &wgpu::vertex_attr_array![
+            0 => Float32x3,
+            1 => Float32x2,
+            2 => Float32x3,
+            3 => Uint32x4,
+            4 => Float32x4
+        ],
+    };
+
+    /// Creates a skinned vertex from a `vertex` and its joint influences
+    pub fn new(vertex: Vertex, joints: [u32; 4], weights: [f32; 4]) -> Self {
+        Self {
+            position: vertex.position,
+            uv: vertex.uv,
+            normal: vertex.normal,
+            joints,
+            weights,
+        }
+    }
+}
+
 pub struct MeshData<T> {
     raw_buffer: wgpu::Buffer,
     length: AtomicU32,
@@ -70,6 +110,29 @@ impl MeshData<Vertex> {
     }
 }
 
+impl MeshData<SkinnedVertex> {
+    fn new(vertices: &[SkinnedVertex], hardware: &TaroHardware) -> Self {
+        Self {
+            _type: Default::default(),
+            length: AtomicU32::new(vertices.len() as u32),
+            raw_buffer: hardware
+                .device()
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Skinned Vertex Buffer"),
+                    contents: bytemuck::cast_slice(vertices),
+                    usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                }),
+        }
+    }
+
+    fn write(&self, vertices: &[SkinnedVertex], hardware: &TaroHardware) {
+        hardware
+            .queue()
+            .write_buffer(&self.raw_buffer, 0, bytemuck::cast_slice(vertices));
+        self.length.store(vertices.len() as u32, Ordering::Relaxed);
+    }
+}
+
 impl MeshData<u16> {
     fn new(indices: &[u16], hardware: &TaroHardware) -> Self {
         Self {
@@ -111,6 +174,8 @@ impl MeshBuffer {
 pub struct Mesh {
     vertices: Box<[Vertex]>,
     indices: Box<[u16]>,
+    bounds: Aabb,
+    bounding_sphere: BoundingSphere,
 }
 
 impl Mesh {
@@ -162,11 +227,96 @@ impl Mesh {
     }
 
     pub fn from_vertices(vertices: &[Vertex], indices: &[u16]) -> Taro<Self> {
+        let (bounds, bounding_sphere) = calculate_bounds(vertices.iter().map(|v| v.position));
         Taro::new(Self {
             vertices: Box::<[Vertex]>::from(vertices),
             indices: Box::<[u16]>::from(indices),
+            bounds,
+            bounding_sphere,
         })
     }
+
+    pub fn vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    pub fn indices(&self) -> &[u16] {
+        &self.indices
+    }
+
+    /// Gets the local space bounding box of the mesh.
+    ///
+    /// Calculated when the mesh is created, so it does not include vertices written to the hardware later.
+    pub fn bounds(&self) -> &Aabb {
+        &self.bounds
+    }
+
+    /// Gets the local space bounding sphere of the mesh.
+    ///
+    /// Calculated when the mesh is created, so it does not include vertices written to the hardware later.
+    pub fn bounding_sphere(&self) -> &BoundingSphere {
+        &self.bounding_sphere
+    }
+
+    /// Finds the closest triangle hit by a local space `ray`.
+    ///
+    /// Triangles are hit from both sides. Uses the vertices the mesh was created with.
+    pub fn intersect_ray(&self, ray: &Ray) -> Option<MeshRayHit> {
+        ray.intersect_aabb(&self.bounds)?;
+
+        let mut closest: Option<MeshRayHit> = None;
+        for (triangle, indices) in self.indices.chunks_exact(3).enumerate() {
+            let [a, b, c] = [0, 1, 2].map(|i| {
+                let vertex = self.vertices.get(indices[i] as usize)?;
+                Some(Vec3::from(vertex.position))
+            });
+            let (Some(a), Some(b), Some(c)) = (a, b, c) else {
+                continue;
+            };
+
+            let Some(distance) = ray.intersect_triangle(a, b, c) else {
+                continue;
+            };
+
+            if closest.is_some_and(|hit| hit.distance <= distance) {
+                continue;
+            }
+
+            // face the normal towards the ray, as triangles are hit from both sides
+            let normal = (b - a).cross(c - a).normalize_or_zero();
+            closest = Some(MeshRayHit {
+                distance,
+                point: ray.at(distance),
+                normal: match normal.dot(ray.direction) > 0. {
+                    true => -normal,
+                    false => normal,
+                },
+                triangle,
+            });
+        }
+
+        closest
+    }
+}
+
+/// A triangle hit by a ray
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshRayHit {
+    /// The distance along the ray to the hit
+    pub distance: f32,
+    pub point: Vec3,
+    /// The normal of the triangle, facing towards the ray
+    pub normal: Vec3,
+    /// The index of the triangle in the index buffer, which is every 3 indices
+    pub triangle: usize,
+}
+
+/// Calculates the bounds of a set of vertex positions. Meshes without vertices have zero sized bounds.
+fn calculate_bounds(positions: impl Iterator<Item = [f32; 3]> + Clone) -> (Aabb, BoundingSphere) {
+    let points = positions.map(Vec3::from);
+    let bounds = Aabb::from_points(points.clone()).unwrap_or_default();
+    let sphere = BoundingSphere::from_points(points).unwrap_or_default();
+    (bounds, sphere)
 }
 
 impl Compiler for Mesh {
@@ -191,3 +341,121 @@ impl Taro<Mesh> {
         buffer.index_buffer.write(indices, hardware);
     }
 }
+
+pub struct SkinnedMeshBuffer {
+    vertex_buffer: MeshData<SkinnedVertex>,
+    index_buffer: MeshData<u16>,
+}
+
+impl SkinnedMeshBuffer {
+    pub fn vertex_buffer(&self) -> &MeshData<SkinnedVertex> {
+        &self.vertex_buffer
+    }
+
+    pub fn index_buffer(&self) -> &MeshData<u16> {
+        &self.index_buffer
+    }
+}
+
+/// A mesh made of [`SkinnedVertex`] data, to be deformed by a skeleton
+pub struct SkinnedMesh {
+    vertices: Box<[SkinnedVertex]>,
+    indices: Box<[u16]>,
+    bounds: Aabb,
+    bounding_sphere: BoundingSphere,
+}
+
+impl SkinnedMesh {
+    pub fn from_vertices(vertices: &[SkinnedVertex], indices: &[u16]) -> Taro<Self> {
+        let (bounds, bounding_sphere) = calculate_bounds(vertices.iter().map(|v| v.position));
+        Taro::new(Self {
+            vertices: Box::<[SkinnedVertex]>::from(vertices),
+            indices: Box::<[u16]>::from(indices),
+            bounds,
+            bounding_sphere,
+        })
+    }
+
+    /// Gets the local space bounding box of the mesh in its bind pose
+    pub fn bounds(&self) -> &Aabb {
+        &self.bounds
+    }
+
+    /// Gets the local space bounding sphere of the mesh in its bind pose
+    pub fn bounding_sphere(&self) -> &BoundingSphere {
+        &self.bounding_sphere
+    }
+
+    pub fn vertices(&self) -> &[SkinnedVertex] {
+        &self.vertices
+    }
+
+    pub fn indices(&self) -> &[u16] {
+        &self.indices
+    }
+}
+
+impl Compiler for SkinnedMesh {
+    type Compiled = SkinnedMeshBuffer;
+
+    fn new_taro_compile(&self, hardware: &TaroHardware) -> Self::Compiled {
+        SkinnedMeshBuffer {
+            vertex_buffer: MeshData::<SkinnedVertex>::new(&self.vertices, hardware),
+            index_buffer: MeshData::<u16>::new(&self.indices, hardware),
+        }
+    }
+}
+
+impl Taro<SkinnedMesh> {
+    pub fn write_vertices(&self, vertices: &[SkinnedVertex], hardware: &TaroHardware) {
+        let buffer = self.get_or_compile(hardware);
+        buffer.vertex_buffer.write(vertices, hardware);
+    }
+
+    pub fn write_indices(&self, indices: &[u16], hardware: &TaroHardware) {
+        let buffer = self.get_or_compile(hardware);
+        buffer.index_buffer.write(indices, hardware);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quad(z: f32) -> [Vertex; 4] {
+        [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)].map(|(x, y)| Vertex {
+            position: [x, y, z],
+            uv: [0., 0.],
+            normal: [0., 0., -1.],
+        })
+    }
+
+    #[test]
+    fn ray_intersection() {
+        let vertices = [quad(2.), quad(5.)].concat();
+        let indices = [0, 1, 2, 0, 2, 3, 4, 5, 6, 4, 6, 7];
+        let mesh = Mesh::from_vertices(&vertices, &indices);
+        assert_eq!(
+            mesh.bounds(),
+            &Aabb::new(Vec3::new(-1., -1., 2.), Vec3::new(1., 1., 5.))
+        );
+
+        let hit = mesh
+            .intersect_ray(&Ray::new(Vec3::new(0.5, 0., 0.), Vec3::Z))
+            .unwrap();
+        assert_eq!(hit.distance, 2.);
+        assert_eq!(hit.point, Vec3::new(0.5, 0., 2.));
+        assert_eq!(hit.normal, -Vec3::Z);
+        assert!(hit.triangle < 2);
+
+        // from behind the closest quad is the other one, and the normal faces the ray
+        let hit = mesh
+            .intersect_ray(&Ray::new(Vec3::new(0., 0.5, 10.), -Vec3::Z))
+            .unwrap();
+        assert_eq!(hit.distance, 5.);
+        assert_eq!(hit.normal, Vec3::Z);
+        assert!(hit.triangle >= 2);
+
+        assert_eq!(mesh.intersect_ray(&Ray::new(Vec3::X * 2., Vec3::Z)), None);
+    }
+}
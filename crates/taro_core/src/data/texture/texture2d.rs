@@ -37,6 +37,11 @@ impl<T: TextureBuilder> Texture2D<T> {
             usage: wgpu::TextureUsages::empty(),
         }))
     }
+
+    /// Gets the width and height of the texture in pixels
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
 }
 
 impl<T: TextureBuilder> Compiler for Texture2D<T> {
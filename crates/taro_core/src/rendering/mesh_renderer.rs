@@ -1,11 +1,14 @@
 use std::sync::Arc;
 
-use boba_3d::pearls::BobaTransform;
+use boba_3d::{
+    geometry::{Aabb, Ray},
+    pearls::BobaTransform,
+};
 use boba_core::Pearl;
 use log::error;
 
 use crate::{
-    data::{buffers::TransformMatrix, Buffer, Mesh, UniformBinding},
+    data::{buffers::TransformMatrix, Buffer, Mesh, MeshRayHit, UniformBinding},
     Bind, Taro, TaroHardware,
 };
 
@@ -55,4 +58,72 @@ impl<Shader> TaroMeshRenderer<Shader> {
     pub fn get_model_matrix(&self) -> &Taro<UniformBinding<TransformMatrix>> {
         &self.model_matrix
     }
+
+    /// Gets the world space bounds of the mesh, or `None` if the transform cannot be read
+    pub fn world_bounds(&self) -> Option<Aabb> {
+        let transform = self.transform.borrow().ok()?;
+        Some(transform.world_bounds(self.mesh.bounds()))
+    }
+
+    /// Finds the closest triangle of the mesh hit by a world space `ray`.
+    ///
+    /// Returns `None` if the ray misses or the transform cannot be read.
+    pub fn intersect_ray(&self, ray: &Ray) -> Option<MeshRayHit> {
+        let world_matrix = self.transform.borrow().ok()?.world_matrix();
+        let inverse = world_matrix.inverse();
+        let local_ray = Ray::new(
+            inverse.transform_point3(ray.origin),
+            inverse.transform_vector3(ray.direction),
+        );
+
+        let hit = self.mesh.intersect_ray(&local_ray)?;
+        let point = world_matrix.transform_point3(hit.point);
+        Some(MeshRayHit {
+            distance: point.distance(ray.origin),
+            point,
+            normal: inverse
+                .transpose()
+                .transform_vector3(hit.normal)
+                .normalize_or_zero(),
+            triangle: hit.triangle,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boba_3d::glam::{Quat, Vec3};
+
+    use crate::data::Vertex;
+
+    use super::*;
+
+    const EPSILON: f32 = 0.0001;
+
+    #[test]
+    fn world_ray_intersection() {
+        let vertices = [(-1., -1.), (1., -1.), (0., 1.)].map(|(x, y)| Vertex {
+            position: [x, y, 0.],
+            uv: [0., 0.],
+            normal: [0., 0., 1.],
+        });
+        let mesh = Mesh::from_vertices(&vertices, &[0, 1, 2]);
+        let transform = BobaTransform::new(
+            Vec3::new(0., 0., 10.),
+            Quat::from_rotation_x(std::f32::consts::FRAC_PI_2),
+            Vec3::splat(3.),
+        );
+        let renderer = TaroMeshRenderer::new_simple(transform, mesh, Arc::new(()));
+
+        // the triangle is scaled up and lies flat at y = 0
+        let ray = Ray::new(Vec3::new(0., 5., 11.), -Vec3::Y);
+        let hit = renderer.intersect_ray(&ray).unwrap();
+        assert!((hit.distance - 5.).abs() < EPSILON);
+        assert!(hit.point.abs_diff_eq(Vec3::new(0., 0., 11.), EPSILON));
+        assert!(hit.normal.abs_diff_eq(Vec3::Y, EPSILON));
+
+        let bounds = renderer.world_bounds().unwrap();
+        assert!(bounds.min.abs_diff_eq(Vec3::new(-3., 0., 7.), EPSILON));
+        assert!(bounds.max.abs_diff_eq(Vec3::new(3., 0., 13.), EPSILON));
+    }
 }
@@ -1,11 +1,13 @@
 mod mesh_renderer;
 mod pearls;
 mod pipeline;
+mod skinned_mesh_renderer;
 mod texture;
 
 pub use mesh_renderer::*;
 pub use pearls::*;
 pub use pipeline::*;
+pub use skinned_mesh_renderer::*;
 pub use texture::*;
 
 pub mod shaders;
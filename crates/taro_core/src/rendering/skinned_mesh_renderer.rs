@@ -0,0 +1,93 @@
+use std::sync::Arc;
+
+use boba_3d::{animation::Skeleton, glam::Mat4, pearls::BobaTransform};
+use boba_core::Pearl;
+use log::error;
+
+use crate::{
+    data::{
+        buffers::{JointMatrices, TransformMatrix},
+        Buffer, SkinnedMesh, StorageBinding, UniformBinding,
+    },
+    Bind, Taro, TaroHardware,
+};
+
+/// Renders a [`SkinnedMesh`] deformed by the joints of a [`Skeleton`].
+///
+/// Joint matrices are calculated relative to `transform`, so the model matrix is applied after skinning.
+pub struct TaroSkinnedMeshRenderer<Shader> {
+    model_matrix: Taro<UniformBinding<TransformMatrix>>,
+    joint_matrices: Taro<StorageBinding<JointMatrices>>,
+    joint_cache: Vec<Mat4>,
+    skeleton: Skeleton,
+
+    pub transform: Pearl<BobaTransform>,
+    pub shader: Arc<Shader>,
+    pub mesh: Taro<SkinnedMesh>,
+}
+
+impl<Shader> TaroSkinnedMeshRenderer<Shader> {
+    pub fn new(
+        transform: Pearl<BobaTransform>,
+        skeleton: Skeleton,
+        mesh: Taro<SkinnedMesh>,
+        shader: Arc<Shader>,
+    ) -> Self {
+        let joints = JointMatrices::identity(skeleton.len());
+        Self {
+            mesh,
+            transform,
+            shader,
+            joint_cache: Vec::with_capacity(skeleton.len()),
+            skeleton,
+            model_matrix: Bind::new(Buffer::new(wgpu::BufferUsages::empty())),
+            joint_matrices: Bind::new(Buffer::new_with_default(
+                wgpu::BufferUsages::empty(),
+                joints.into(),
+            )),
+        }
+    }
+
+    pub fn skeleton(&self) -> &Skeleton {
+        &self.skeleton
+    }
+
+    /// Writes the model matrix and the joint matrices to the GPU.
+    ///
+    /// Must be called once per frame before using the bindings of this renderer.
+    pub fn update_matrices(&mut self, hardware: &TaroHardware) {
+        let world_matrix = match self.transform.borrow() {
+            Ok(t) => t.world_matrix(),
+            Err(e) => {
+                error!(
+                    "Error when recalculating skinned model matrix. Old matrices will be used. Error: {e}"
+                );
+                return;
+            }
+        };
+
+        let matrix: TransformMatrix = world_matrix.into();
+        self.model_matrix
+            .bind_data()
+            .write_to_hardware(matrix.into(), hardware);
+
+        if self.skeleton.is_empty() {
+            return;
+        }
+
+        self.skeleton
+            .write_joint_matrices(world_matrix, &mut self.joint_cache);
+        let joints = JointMatrices::from(self.joint_cache.as_slice());
+        self.joint_matrices
+            .bind_data()
+            .write_to_hardware(joints.into(), hardware);
+    }
+
+    pub fn get_model_matrix(&self) -> &Taro<UniformBinding<TransformMatrix>> {
+        &self.model_matrix
+    }
+
+    pub fn get_joint_matrices(&self) -> &Taro<StorageBinding<JointMatrices>> {
+        &self.joint_matrices
+    }
+}
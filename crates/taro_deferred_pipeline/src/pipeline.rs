@@ -6,11 +6,12 @@ use taro_core::{
     },
     rendering::{
         shaders::LitShader, RenderPipeline, RenderTexture, TaroMeshRenderer, TaroRenderPearls,
+        TaroSkinnedMeshRenderer,
     },
     wgpu, Bind, BindGroup, BindGroupBuilder, Taro, TaroHardware,
 };
 
-use crate::shaders::DeferredShader;
+use crate::shaders::{DeferredShader, DeferredSkinnedShader};
 
 pub struct DeferredPipeline {
     image_size: (u32, u32),
@@ -87,6 +88,10 @@ impl RenderPipeline for DeferredPipeline {
         };
 
         let lit_renderers = pearls.collect::<TaroMeshRenderer<LitShader>>();
+        let mut skinned_renderers = pearls.collect_mut::<TaroSkinnedMeshRenderer<LitShader>>();
+        for renderer in skinned_renderers.iter_mut() {
+            renderer.update_matrices(hardware);
+        }
 
         // --- POSITION PASS ---
         {
@@ -113,6 +118,17 @@ impl RenderPipeline for DeferredPipeline {
                     hardware,
                 )
             }
+
+            for renderer in skinned_renderers.iter() {
+                renderer.shader.render_skinned_gbuffer_position(
+                    &renderer.mesh,
+                    camera_matrix,
+                    renderer.get_model_matrix(),
+                    renderer.get_joint_matrices(),
+                    &mut pass,
+                    hardware,
+                )
+            }
         }
 
         // --- NORMAL PASS ---
@@ -140,6 +156,17 @@ impl RenderPipeline for DeferredPipeline {
                     hardware,
                 )
             }
+
+            for renderer in skinned_renderers.iter() {
+                renderer.shader.render_skinned_gbuffer_normal(
+                    &renderer.mesh,
+                    camera_matrix,
+                    renderer.get_model_matrix(),
+                    renderer.get_joint_matrices(),
+                    &mut pass,
+                    hardware,
+                )
+            }
         }
 
         // --- ALBEDO PASS ---
@@ -167,6 +194,17 @@ impl RenderPipeline for DeferredPipeline {
                     hardware,
                 )
             }
+
+            for renderer in skinned_renderers.iter() {
+                renderer.shader.render_skinned_gbuffer_albedo(
+                    &renderer.mesh,
+                    camera_matrix,
+                    renderer.get_model_matrix(),
+                    renderer.get_joint_matrices(),
+                    &mut pass,
+                    hardware,
+                )
+            }
         }
 
         // --- SPECULAR PASS ---
@@ -194,6 +232,17 @@ impl RenderPipeline for DeferredPipeline {
                     hardware,
                 )
             }
+
+            for renderer in skinned_renderers.iter() {
+                renderer.shader.render_skinned_gbuffer_specular(
+                    &renderer.mesh,
+                    camera_matrix,
+                    renderer.get_model_matrix(),
+                    renderer.get_joint_matrices(),
+                    &mut pass,
+                    hardware,
+                )
+            }
         }
 
         // --- COPY ALBEDO BACK INTO RENDER TEXTURE ---
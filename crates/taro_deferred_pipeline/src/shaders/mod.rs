@@ -1,11 +1,12 @@
 mod lit;
+mod skinned_lit;
 
 pub use lit::*;
 
 use taro_core::{
     data::{
-        buffers::{CameraMatrix, TransformMatrix},
-        Mesh, UniformBinding,
+        buffers::{CameraMatrix, JointMatrices, TransformMatrix},
+        Mesh, SkinnedMesh, StorageBinding, UniformBinding,
     },
     wgpu, Taro, TaroHardware,
 };
@@ -47,3 +48,46 @@ pub trait DeferredShader: 'static {
         hardware: &TaroHardware,
     );
 }
+
+/// A [`DeferredShader`] that can also render a [`SkinnedMesh`]
+pub trait DeferredSkinnedShader: DeferredShader {
+    fn render_skinned_gbuffer_position<'pass>(
+        &'pass self,
+        mesh: &'pass Taro<SkinnedMesh>,
+        camera_matrix: &'pass Taro<UniformBinding<CameraMatrix>>,
+        model_matrix: &'pass Taro<UniformBinding<TransformMatrix>>,
+        joint_matrices: &'pass Taro<StorageBinding<JointMatrices>>,
+        pass: &mut wgpu::RenderPass<'pass>,
+        hardware: &TaroHardware,
+    );
+
+    fn render_skinned_gbuffer_normal<'pass>(
+        &'pass self,
+        mesh: &'pass Taro<SkinnedMesh>,
+        camera_matrix: &'pass Taro<UniformBinding<CameraMatrix>>,
+        model_matrix: &'pass Taro<UniformBinding<TransformMatrix>>,
+        joint_matrices: &'pass Taro<StorageBinding<JointMatrices>>,
+        pass: &mut wgpu::RenderPass<'pass>,
+        hardware: &TaroHardware,
+    );
+
+    fn render_skinned_gbuffer_albedo<'pass>(
+        &'pass self,
+        mesh: &'pass Taro<SkinnedMesh>,
+        camera_matrix: &'pass Taro<UniformBinding<CameraMatrix>>,
+        model_matrix: &'pass Taro<UniformBinding<TransformMatrix>>,
+        joint_matrices: &'pass Taro<StorageBinding<JointMatrices>>,
+        pass: &mut wgpu::RenderPass<'pass>,
+        hardware: &TaroHardware,
+    );
+
+    fn render_skinned_gbuffer_specular<'pass>(
+        &'pass self,
+        mesh: &'pass Taro<SkinnedMesh>,
+        camera_matrix: &'pass Taro<UniformBinding<CameraMatrix>>,
+        model_matrix: &'pass Taro<UniformBinding<TransformMatrix>>,
+        joint_matrices: &'pass Taro<StorageBinding<JointMatrices>>,
+        pass: &mut wgpu::RenderPass<'pass>,
+        hardware: &TaroHardware,
+    );
+}
@@ -0,0 +1,243 @@
+use once_map::OnceMap;
+use taro_core::{
+    data::{
+        buffers::{CameraMatrix, JointMatrices, TransformMatrix},
+        SkinnedMesh, SkinnedVertex, StorageBinding, UniformBinding,
+    },
+    rendering::shaders::LitShader,
+    wgpu, HardwareId, Taro, TaroHardware,
+};
+
+use super::DeferredSkinnedShader;
+
+/// The settings that change between each gbuffer pass
+struct SkinnedPass {
+    label: &'static str,
+    entry_point: &'static str,
+    format: wgpu::TextureFormat,
+    blend: Option<wgpu::BlendState>,
+}
+
+type PipelineMap = OnceMap<HardwareId, wgpu::RenderPipeline>;
+
+#[allow(clippy::too_many_arguments)]
+fn render_skinned<'pass>(
+    shader: &'pass LitShader,
+    settings: SkinnedPass,
+    pipelines: &'static PipelineMap,
+    mesh: &'pass Taro<SkinnedMesh>,
+    camera_matrix: &'pass Taro<UniformBinding<CameraMatrix>>,
+    model_matrix: &'pass Taro<UniformBinding<TransformMatrix>>,
+    joint_matrices: &'pass Taro<StorageBinding<JointMatrices>>,
+    pass: &mut wgpu::RenderPass<'pass>,
+    hardware: &TaroHardware,
+) {
+    let mesh_buffer = mesh.get_or_compile(hardware);
+
+    let camera_binding = camera_matrix.get_or_compile(hardware);
+    let model_binding = model_matrix.get_or_compile(hardware);
+    let lit_binding = shader.bindings().get_or_compile(hardware);
+    let joint_binding = joint_matrices.get_or_compile(hardware);
+
+    let pipeline = pipelines
+        .get_or_init(*hardware.id(), || {
+            let layout =
+                hardware
+                    .device()
+                    .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                        label: Some(&format!("{} Pipeline Layout", settings.label)),
+                        bind_group_layouts: &[
+                            camera_binding.layout(),
+                            model_binding.layout(),
+                            lit_binding.layout(),
+                            joint_binding.layout(),
+                        ],
+                        push_constant_ranges: &[],
+                    });
+
+            let module = &hardware
+                .device()
+                .create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some("Skinned Lit Shader"),
+                    source: wgpu::ShaderSource::Wgsl(include_str!("skinned_lit.wgsl").into()),
+                });
+
+            hardware
+                .device()
+                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some(&format!("{} Pipeline", settings.label)),
+                    layout: Some(&layout),
+                    vertex: wgpu::VertexState {
+                        module,
+                        entry_point: "vs_main",
+                        buffers: &[SkinnedVertex::BUFFER_LAYOUT],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module,
+                        entry_point: settings.entry_point,
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format: settings.format,
+                            blend: settings.blend,
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: wgpu::PrimitiveState {
+                        topology: wgpu::PrimitiveTopology::TriangleList,
+                        strip_index_format: None,
+                        front_face: wgpu::FrontFace::Ccw,
+                        cull_mode: Some(wgpu::Face::Back),
+                        polygon_mode: wgpu::PolygonMode::Fill,
+                        unclipped_depth: false,
+                        conservative: false,
+                    },
+                    depth_stencil: Some(wgpu::DepthStencilState {
+                        format: wgpu::TextureFormat::Depth32Float,
+                        depth_write_enabled: true,
+                        depth_compare: wgpu::CompareFunction::Less,
+                        stencil: wgpu::StencilState::default(),
+                        bias: wgpu::DepthBiasState::default(),
+                    }),
+                    multisample: wgpu::MultisampleState {
+                        count: 1,
+                        mask: !0,
+                        alpha_to_coverage_enabled: false,
+                    },
+                    multiview: None,
+                })
+        })
+        .into_data();
+
+    pass.set_pipeline(pipeline);
+    pass.set_bind_group(0, camera_binding.bind_group(), &[]);
+    pass.set_bind_group(1, model_binding.bind_group(), &[]);
+    pass.set_bind_group(2, lit_binding.bind_group(), &[]);
+    pass.set_bind_group(3, joint_binding.bind_group(), &[]);
+    pass.set_vertex_buffer(0, mesh_buffer.vertex_buffer().raw_buffer().slice(..));
+    pass.set_index_buffer(
+        mesh_buffer.index_buffer().raw_buffer().slice(..),
+        wgpu::IndexFormat::Uint16,
+    );
+    pass.draw_indexed(0..mesh_buffer.index_buffer().len(), 0, 0..1);
+}
+
+impl DeferredSkinnedShader for LitShader {
+    fn render_skinned_gbuffer_position<'pass>(
+        &'pass self,
+        mesh: &'pass Taro<SkinnedMesh>,
+        camera_matrix: &'pass Taro<UniformBinding<CameraMatrix>>,
+        model_matrix: &'pass Taro<UniformBinding<TransformMatrix>>,
+        joint_matrices: &'pass Taro<StorageBinding<JointMatrices>>,
+        pass: &mut wgpu::RenderPass<'pass>,
+        hardware: &TaroHardware,
+    ) {
+        static PIPELINE: PipelineMap = OnceMap::new();
+        let settings = SkinnedPass {
+            label: "Deferred Skinned Position",
+            entry_point: "position_main",
+            format: wgpu::TextureFormat::Rgba32Float,
+            blend: None,
+        };
+
+        render_skinned(
+            self,
+            settings,
+            &PIPELINE,
+            mesh,
+            camera_matrix,
+            model_matrix,
+            joint_matrices,
+            pass,
+            hardware,
+        );
+    }
+
+    fn render_skinned_gbuffer_normal<'pass>(
+        &'pass self,
+        mesh: &'pass Taro<SkinnedMesh>,
+        camera_matrix: &'pass Taro<UniformBinding<CameraMatrix>>,
+        model_matrix: &'pass Taro<UniformBinding<TransformMatrix>>,
+        joint_matrices: &'pass Taro<StorageBinding<JointMatrices>>,
+        pass: &mut wgpu::RenderPass<'pass>,
+        hardware: &TaroHardware,
+    ) {
+        static PIPELINE: PipelineMap = OnceMap::new();
+        let settings = SkinnedPass {
+            label: "Deferred Skinned Normal",
+            entry_point: "normal_main",
+            format: wgpu::TextureFormat::Rgba32Float,
+            blend: None,
+        };
+
+        render_skinned(
+            self,
+            settings,
+            &PIPELINE,
+            mesh,
+            camera_matrix,
+            model_matrix,
+            joint_matrices,
+            pass,
+            hardware,
+        );
+    }
+
+    fn render_skinned_gbuffer_albedo<'pass>(
+        &'pass self,
+        mesh: &'pass Taro<SkinnedMesh>,
+        camera_matrix: &'pass Taro<UniformBinding<CameraMatrix>>,
+        model_matrix: &'pass Taro<UniformBinding<TransformMatrix>>,
+        joint_matrices: &'pass Taro<StorageBinding<JointMatrices>>,
+        pass: &mut wgpu::RenderPass<'pass>,
+        hardware: &TaroHardware,
+    ) {
+        static PIPELINE: PipelineMap = OnceMap::new();
+        let settings = SkinnedPass {
+            label: "Deferred Skinned Albedo",
+            entry_point: "albedo_main",
+            format: wgpu::TextureFormat::Bgra8UnormSrgb,
+            blend: Some(wgpu::BlendState::REPLACE),
+        };
+
+        render_skinned(
+            self,
+            settings,
+            &PIPELINE,
+            mesh,
+            camera_matrix,
+            model_matrix,
+            joint_matrices,
+            pass,
+            hardware,
+        );
+    }
+
+    fn render_skinned_gbuffer_specular<'pass>(
+        &'pass self,
+        mesh: &'pass Taro<SkinnedMesh>,
+        camera_matrix: &'pass Taro<UniformBinding<CameraMatrix>>,
+        model_matrix: &'pass Taro<UniformBinding<TransformMatrix>>,
+        joint_matrices: &'pass Taro<StorageBinding<JointMatrices>>,
+        pass: &mut wgpu::RenderPass<'pass>,
+        hardware: &TaroHardware,
+    ) {
+        static PIPELINE: PipelineMap = OnceMap::new();
+        let settings = SkinnedPass {
+            label: "Deferred Skinned Specular",
+            entry_point: "specular_main",
+            format: wgpu::TextureFormat::Rgba32Float,
+            blend: None,
+        };
+
+        render_skinned(
+            self,
+            settings,
+            &PIPELINE,
+            mesh,
+            camera_matrix,
+            model_matrix,
+            joint_matrices,
+            pass,
+            hardware,
+        );
+    }
+}
@@ -0,0 +1,109 @@
+@group(0) @binding(0)
+var<uniform> camera_matrix: mat4x4<f32>;
+@group(1) @binding(0)
+var<uniform> model_matrix: mat4x4<f32>;
+
+@group(2)@binding(0)
+var s_diffuse: sampler;
+@group(2) @binding(1)
+var t_diffuse: texture_2d<f32>;
+@group(2) @binding(2)
+var<uniform> color: vec4<f32>;
+
+@group(3) @binding(0)
+var<storage, read> joint_matrices: array<mat4x4<f32>>;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) normal: vec3<f32>,
+    @location(3) joints: vec4<u32>,
+    @location(4) weights: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) world_position: vec3<f32>,
+    @location(3) world_normal: vec3<f32>,
+}
+
+// blends the joint matrices using normalized weights, matching `blend_joint_matrices` in boba_3d
+fn skin_matrix(joints: vec4<u32>, weights: vec4<f32>) -> mat4x4<f32> {
+    let count = arrayLength(&joint_matrices);
+    var blended = mat4x4<f32>(
+        vec4<f32>(0.0, 0.0, 0.0, 0.0),
+        vec4<f32>(0.0, 0.0, 0.0, 0.0),
+        vec4<f32>(0.0, 0.0, 0.0, 0.0),
+        vec4<f32>(0.0, 0.0, 0.0, 0.0),
+    );
+    var total = 0.0;
+    for (var i = 0; i < 4; i++) {
+        let joint = joints[i];
+        let weight = weights[i];
+        if (joint < count && weight > 0.0) {
+            blended += joint_matrices[joint] * weight;
+            total += weight;
+        }
+    }
+
+    if (total <= 0.0) {
+        return mat4x4<f32>(
+            vec4<f32>(1.0, 0.0, 0.0, 0.0),
+            vec4<f32>(0.0, 1.0, 0.0, 0.0),
+            vec4<f32>(0.0, 0.0, 1.0, 0.0),
+            vec4<f32>(0.0, 0.0, 0.0, 1.0),
+        );
+    }
+
+    return blended * (1.0 / total);
+}
+
+// the cofactor matrix of the upper 3x3, matching `normal_matrix` in boba_3d
+fn normal_matrix(m: mat4x4<f32>) -> mat3x3<f32> {
+    let x = m[0].xyz;
+    let y = m[1].xyz;
+    let z = m[2].xyz;
+    let cofactor = mat3x3<f32>(cross(y, z), cross(z, x), cross(x, y));
+    return cofactor * select(1.0, -1.0, dot(x, cross(y, z)) < 0.0);
+}
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+
+    let skin = skin_matrix(model.joints, model.weights);
+    let skinned_position = skin * vec4<f32>(model.position, 1.0);
+    let skinned_normal = normalize(normal_matrix(skin) * model.normal);
+    let world_position = model_matrix * skinned_position;
+
+    out.normal = skinned_normal;
+    out.tex_coords = model.tex_coords;
+    out.world_position = world_position.xyz;
+    out.clip_position = camera_matrix * world_position;
+    out.world_normal = normalize(normal_matrix(model_matrix) * skinned_normal);
+    return out;
+}
+
+@fragment
+fn position_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(in.world_position, 1.0);
+}
+
+@fragment
+fn normal_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(in.world_normal, 1.0);
+}
+
+@fragment
+fn albedo_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(t_diffuse, s_diffuse, in.tex_coords) * color;
+}
+
+@fragment
+fn specular_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+}
@@ -1,6 +1,7 @@
+use boba_2d::rendering::TaroCamera2D;
 use boba_core::ResourceError;
 use log::warn;
-use milk_tea::{events::MilkTeaEvent, MilkTeaRenderAdapter};
+use milk_tea::{events::MilkTeaEvent, MilkTeaRenderAdapter, MilkTeaRenderStages};
 use taro_core::{
     rendering::{RenderTexture, TaroRenderPearls},
     wgpu, HardwareBuilder, TaroCamera, TaroHardware,
@@ -48,11 +49,32 @@ impl MilkTeaRenderAdapter for TaroGraphicsAdapter {
         window_size: milk_tea::winit::dpi::PhysicalSize<u32>,
         registry: &mut boba_core::PearlRegistry,
         resources: &mut boba_core::BobaResources,
+        mut stages: MilkTeaRenderStages,
+    ) -> boba_core::BobaResult {
+        stages.pre_render(registry, resources);
+        let result = self.draw(window_size, registry, resources);
+        stages.post_render(registry, resources);
+        result
+    }
+}
+
+impl TaroGraphicsAdapter {
+    /// Draws every camera to the window surface
+    fn draw(
+        &mut self,
+        window_size: milk_tea::winit::dpi::PhysicalSize<u32>,
+        registry: &mut boba_core::PearlRegistry,
+        resources: &mut boba_core::BobaResources,
     ) -> boba_core::BobaResult {
         registry.run_stage::<MilkTeaEvent<OnTaroMilkTeaRender>>(&OnTaroMilkTeaRender, resources);
 
-        // get cameras resource
-        let mut camera = resources.get_mut::<TaroCamera>()?;
+        // get cameras resources, 3d cameras render first so that 2d cameras can draw over them
+        let mut camera = optional_resource(resources.get_mut::<TaroCamera>())?;
+        let mut camera_2d = optional_resource(resources.get_mut::<TaroCamera2D>())?;
+        if camera.is_none() && camera_2d.is_none() {
+            warn!("No 'TaroCamera' or 'TaroCamera2D' found in resources.");
+            return Ok(());
+        }
 
         // configure surface if necessary
         if self.config.width != window_size.width || self.config.height != window_size.height {
@@ -66,7 +88,12 @@ impl MilkTeaRenderAdapter for TaroGraphicsAdapter {
         let render_texture = RenderTexture::new(size, self.surface.get_current_texture()?);
 
         let mut render_camera = |pearls: &TaroRenderPearls| {
-            camera.render(&render_texture, pearls, &self.hardware);
+            if let Some(camera) = &mut camera {
+                camera.render(&render_texture, pearls, &self.hardware);
+            }
+            if let Some(camera) = &mut camera_2d {
+                camera.render(&render_texture, pearls, &self.hardware);
+            }
         };
 
         // get pearls to render and use closure to render them
@@ -85,3 +112,14 @@ impl MilkTeaRenderAdapter for TaroGraphicsAdapter {
         Ok(())
     }
 }
+
+/// Treats a missing resource as `None`, keeping any other error
+fn optional_resource<T, E>(
+    resource: Result<T, ResourceError<E>>,
+) -> Result<Option<T>, ResourceError<E>> {
+    match resource {
+        Ok(resource) => Ok(Some(resource)),
+        Err(ResourceError::NotFound(_)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
@@ -21,19 +21,19 @@ fn main() {
     // create app
     let mut app = MilkTeaApp::default();
 
-    // create physics handler and rigidbody transforms
+    // create physics handler and rigidbodies
     let mut physics = RapierPhysics::new();
-    let ground_transform = physics.create_transform(
+    let ground = physics.create_transform(
         RigidBodyBuilder::fixed().build(),
         ColliderBuilder::cuboid(5., 0.01, 5.).build(),
     );
-    let ball_transform = physics.create_transform(
+    let ball = physics.create_transform(
         RigidBodyBuilder::dynamic()
             .translation(Vec3::new(-0.18, 1.5, 0.).into())
             .build(),
         ColliderBuilder::ball(0.5).build(),
     );
-    let cube_transform = physics.create_transform(
+    let cube = physics.create_transform(
         RigidBodyBuilder::dynamic()
             .translation(Vec3::new(0.18, 3., -0.15).into())
             .build(),
@@ -54,19 +54,19 @@ fn main() {
     );
 
     let plane_renderer = TaroMeshRenderer::new(
-        ground_transform.clone(),
+        ground.transform.clone(),
         Mesh::new(File::open("./assets/plane.obj").unwrap()).unwrap(),
         grid_shader.clone(),
     );
 
     let sphere_renderer = TaroMeshRenderer::new(
-        ball_transform.clone(),
+        ball.transform.clone(),
         Mesh::new(File::open("./assets/sphere.obj").unwrap()).unwrap(),
         boba_shader.clone(),
     );
 
     let cube_renderer = TaroMeshRenderer::new(
-        cube_transform.clone(),
+        cube.transform.clone(),
         Mesh::new(File::open("./assets/cube.obj").unwrap()).unwrap(),
         boba_shader.clone(),
     );
@@ -88,7 +88,8 @@ fn main() {
     );
 
     // add all required stages
-    app.main_stages.append(OnRapierUpdate::default());
+    app.main_stages
+        .insert_after::<BobaFixedUpdate, _>(OnRapierUpdate::default());
 
     // add all created resources
     app.resources.add(physics);
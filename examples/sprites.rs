@@ -0,0 +1,83 @@
+use std::rc::Rc;
+
+use boba::prelude::*;
+use boba_2d::rendering::{TaroCamera2D, TaroSpritePipeline, TaroSpriteRenderer, TextureAtlas};
+use taro_core::{
+    data::{
+        buffers::Color,
+        texture::{Texture2D, Texture2DView},
+    },
+    rendering::TaroRenderPearls,
+};
+use taro_milk_tea::TaroGraphicsAdapter;
+
+pub struct Spinner {
+    pub transform: Pearl<BobaTransform2D>,
+    pub speed: f32,
+}
+
+register_pearl_stages!(Spinner: BobaUpdate);
+
+impl PearlStage<BobaUpdate> for Spinner {
+    fn update(pearl: &Pearl<Self>, delta: &f32, _resources: &mut BobaResources) -> BobaResult {
+        let pearl = pearl.borrow_mut()?;
+        pearl.transform.borrow_mut()?.rotate(pearl.speed * delta);
+        Ok(())
+    }
+}
+
+fn main() {
+    // create app
+    let mut app = MilkTeaApp::default();
+
+    // split the uv grid into a 4 by 4 atlas, and use the whole boba logo as a single sprite
+    let grid_texture = Texture2D::from_bytes(include_bytes!("../assets/uv_grid.png")).unwrap();
+    let grid_atlas = Rc::new(TextureAtlas::from_grid(
+        Texture2DView::from_texture(grid_texture),
+        4,
+        4,
+    ));
+    let boba_texture =
+        Texture2D::from_bytes(include_bytes!("../readme_assets/boba-logo.png")).unwrap();
+    let boba_atlas = Rc::new(TextureAtlas::new(Texture2DView::from_texture(boba_texture)));
+
+    let mut render_pearls = TaroRenderPearls::default();
+
+    // a row of tiles from the grid atlas, drawn in a single batch behind the logo
+    for index in 0..8 {
+        let position = Vec2::new(index as f32 - 3.5, -2.);
+        let tile = TaroSpriteRenderer::new_simple(
+            BobaTransform2D::from_position(position).with_z_order(-1),
+            grid_atlas.clone(),
+        )
+        .with_region(index)
+        .with_flip(index % 2 == 1, false);
+        render_pearls.add(Pearl::wrap(tile));
+    }
+
+    // a spinning logo with a tinted child that orbits it
+    let logo = TaroSpriteRenderer::new_simple(BobaTransform2D::default(), boba_atlas.clone())
+        .with_size(Vec2::splat(3.));
+    let mut moon =
+        TaroSpriteRenderer::new_simple(BobaTransform2D::from_position(Vec2::X * 3.), boba_atlas)
+            .with_tint(Color::from_rgba(1., 0.5, 0.5, 0.8));
+    moon.transform.set_parent(logo.transform.clone()).unwrap();
+
+    let spinner = Spinner {
+        transform: logo.transform.clone(),
+        speed: 1.,
+    };
+    app.registry.add(Pearl::wrap(spinner));
+    render_pearls.add(Pearl::wrap(logo));
+    render_pearls.add(Pearl::wrap(moon));
+
+    // create an orthographic camera that draws the sprites
+    let camera = TaroCamera2D::new_simple(BobaTransform2D::default(), TaroSpritePipeline::new());
+
+    // add all created resources
+    app.resources.add(render_pearls);
+    app.resources.add(camera);
+
+    // run the app
+    app.run::<TaroGraphicsAdapter>().unwrap();
+}
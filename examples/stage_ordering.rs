@@ -50,6 +50,10 @@ fn main() {
     app.startup_stages.append(Stage4);
     app.startup_stages.insert(Stage2);
     app.startup_stages.prepend(Stage1);
+    app.startup_stages.append(system(|| {
+        println!("Running closure stage");
+        Ok(())
+    }));
 
     app.run::<TaroGraphicsAdapter>().unwrap();
 }
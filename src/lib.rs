@@ -1,8 +1,16 @@
 pub use boba_core as core;
 pub use milk_tea;
 pub mod prelude {
+    pub use boba_2d::pearls::*;
+    pub use boba_3d::constraints::*;
+    pub use boba_3d::geometry::*;
     pub use boba_3d::glam::*;
+    pub use boba_3d::ik::*;
+    pub use boba_3d::large_world::*;
     pub use boba_3d::pearls::*;
+    pub use boba_3d::spatial::*;
+    pub use boba_3d::spline::*;
+    pub use boba_3d::tween::*;
     pub use boba_core::stages::*;
     pub use boba_core::*;
     pub use milk_tea::MilkTeaApp;